@@ -22,6 +22,8 @@ pub struct Activity {
 }
 
 crud!(Activity {});
+//or logic delete: delete_by_map set `delete_flag = 1`, delete_by_map_physical delete rows
+//crud!(Activity {}, logic_delete = "delete_flag");
 
 #[tokio::main]
 pub async fn main() -> Result<(), Error> {
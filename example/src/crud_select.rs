@@ -1,11 +1,11 @@
 use rbatis::dark_std::defer;
 use rbatis::rbdc::datetime::DateTime;
-use rbatis::{crud, Error, RBatis};
+use rbatis::{crud, Error, PageRequest, Query, RBatis};
 use rbs::value;
 use serde_json::json;
 
 /// table
-#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize, rbatis::Columns)]
 pub struct Activity {
     pub id: Option<String>,
     pub name: Option<String>,
@@ -48,5 +48,26 @@ pub async fn main() -> Result<(), Error> {
     )
     .await?;
     println!("select_by_method = {}", json!(data));
+
+    let query = Query::new()
+        .eq(Activity::COL.status, 1)
+        .or(|q| {
+            q.like(Activity::COL.name, "%1%")
+                .is_not_null(Activity::COL.remark)
+        })
+        .order_by_desc(Activity::COL.create_time)
+        .limit(10);
+    let data = Activity::select_by_wrapper(&rb, &query).await?;
+    println!("select_by_wrapper = {}", json!(data));
+    let count = Activity::count_by_wrapper(&rb, &query).await?;
+    println!("count_by_wrapper = {}", count);
+
+    let data = Activity::select_page_by_map(
+        &rb,
+        &PageRequest::new(1, 10),
+        value! {"order_by": "id desc"},
+    )
+    .await?;
+    println!("select_page_by_map = {}", json!(data));
     Ok(())
 }
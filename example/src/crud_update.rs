@@ -22,6 +22,8 @@ pub struct Activity {
 }
 
 crud!(Activity {});
+//or optimistic lock: update_by_map add `version = ?` and set `version + 1`, return version conflict error if no row updated
+//crud!(Activity {}, version = "version");
 
 #[tokio::main]
 pub async fn main() -> Result<(), Error> {
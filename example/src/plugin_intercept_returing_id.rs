@@ -9,6 +9,8 @@ use serde_json::json;
 use std::sync::Arc;
 
 /// Postgres insert sql returning id Intercept
+///
+/// `crud!` also gen `insert_returning`/`insert_batch_returning` to return the inserted rows without an Intercept
 #[derive(Debug)]
 pub struct ReturningIdPlugin {}
 
@@ -29,8 +31,10 @@ impl Intercept for ReturningIdPlugin {
                 ResultType::Exec(exec_r) => {
                     let id = rb.query(&new_sql, new_args).await?;
                     let id: String = rbatis::decode(id)?;
-                    let mut exec = ExecResult::default();
-                    exec.last_insert_id = id.into();
+                    let exec = ExecResult {
+                        last_insert_id: id.into(),
+                        ..Default::default()
+                    };
                     *exec_r = Ok(exec);
                     Ok(Action::Return)
                 }
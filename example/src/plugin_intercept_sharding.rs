@@ -0,0 +1,66 @@
+use log::LevelFilter;
+use rbatis::dark_std::defer;
+use rbatis::intercept_sharding::{ShardingAlgorithm, ShardingIntercept, ShardingRule};
+use rbatis::{crud, Error, RBatis, PRIMARY_DS};
+use rbs::value;
+use serde_json::json;
+use std::sync::Arc;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct Orders {
+    pub id: Option<i64>,
+    pub user_id: Option<i64>,
+    pub amount: Option<i64>,
+}
+
+crud!(Orders {});
+#[tokio::main]
+pub async fn main() -> Result<(), Error> {
+    _ = fast_log::init(fast_log::Config::new().console().level(LevelFilter::Debug));
+    defer!(|| {
+        log::logger().flush();
+    });
+    let rb = RBatis::new();
+    rb.init(rbdc_sqlite::SqliteDriver {}, "sqlite://target/sqlite_0.db")?;
+    rb.init_ds(
+        "ds_1",
+        rbdc_sqlite::SqliteDriver {},
+        "sqlite://target/sqlite_1.db",
+    )?;
+    // orders_00..orders_03 by `user_id % 4`, orders_00/orders_02 on primary, orders_01/orders_03 on ds_1
+    // push after the intercepts changing the sql, the sql of shards not run the intercepts again
+    rb.intercepts.push(Arc::new(
+        ShardingIntercept::new().rule(
+            ShardingRule::new("orders", "user_id", ShardingAlgorithm::Mod(4))
+                .datasources(vec![PRIMARY_DS, "ds_1"]),
+        ),
+    ));
+    // create table on all shards
+    _ = rb
+        .exec(
+            "CREATE TABLE IF NOT EXISTS orders ( id INTEGER PRIMARY KEY, user_id INTEGER, amount INTEGER);",
+            vec![],
+        )
+        .await;
+
+    for user_id in 0..4 {
+        let table = Orders {
+            id: Some(user_id),
+            user_id: Some(user_id),
+            amount: Some(100),
+        };
+        let data = Orders::insert(&rb, &table).await;
+        println!("insert = {}", json!(data));
+    }
+
+    // run on orders_03 of ds_1
+    let data = Orders::select_by_map(&rb, value! {"user_id": 3}).await;
+    println!("select_by_map = {}", json!(data));
+
+    // run on all shards and merge rows
+    let data = Orders::select_by_map(&rb, value! {"amount": 100}).await;
+    println!("select_by_map fan out = {}", json!(data));
+
+    _ = Orders::delete_by_map(&rb, value! {"amount": 100}).await;
+    Ok(())
+}
@@ -184,9 +184,11 @@ mod my_pool {
         }
 
         async fn get_timeout(&self, d: Duration) -> Result<Box<dyn Connection>, Error> {
-            let mut out = Timeouts::default();
-            out.create = Some(d);
-            out.wait = Some(d);
+            let out = Timeouts {
+                create: Some(d),
+                wait: Some(d),
+                ..Default::default()
+            };
             let guard = self
                 .inner
                 .timeout_get(&out)
@@ -5,7 +5,7 @@ use syn::ItemFn;
 
 use crate::codegen::loader_html::{load_html, Element};
 use crate::codegen::proc_macro::TokenStream as MacroTokenStream;
-use crate::codegen::string_util::{concat_str, find_convert_string};
+use crate::codegen::string_util::{concat_str, find_convert_string, un_packing_safe};
 use crate::codegen::syntax_tree_html::*;
 use crate::codegen::ParseArgs;
 use crate::error::Error;
@@ -244,6 +244,23 @@ fn handle_text_element(element: &Element, body: &mut TokenStream, ignore: &mut V
     let mut replace_num = 0;
 
     for (k, v) in convert_list {
+        let (k, safe) = if v.starts_with('$') {
+            un_packing_safe(&k)
+        } else {
+            (k.as_str(), false)
+        };
+        if v.starts_with('$') && !safe && cfg!(feature = "strict_sql") {
+            let err = syn::Error::new(
+                proc_macro2::Span::call_site(),
+                format!(
+                    "[rbatis-codegen] `{}` is not allowed by the feature `strict_sql`, use the bind arg `#{{{}}}` or mark it safe `${{safe {}}}`",
+                    v, k, k
+                ),
+            )
+            .to_compile_error();
+            *body = quote! { #body #err };
+            return;
+        }
         let method_impl = crate::codegen::func::impl_fn(
             &body.to_string(),
             "",
@@ -24,6 +24,15 @@ pub fn find_convert_string(arg: &str) -> LinkedList<(String, String)> {
     list
 }
 
+/// the `${}` expression marked safe by the `safe` prefix, for example `${safe table_name}` => (`table_name`, true)
+pub fn un_packing_safe(expr: &str) -> (&str, bool) {
+    let trimmed = expr.trim_start();
+    match trimmed.strip_prefix("safe") {
+        Some(v) if v.starts_with(char::is_whitespace) => (v.trim(), true),
+        _ => (expr, false),
+    }
+}
+
 pub fn count_string_num(s: &str, c: char) -> usize {
     let cs = s.chars();
     let mut num = 0;
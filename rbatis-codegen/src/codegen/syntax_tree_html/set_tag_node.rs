@@ -93,7 +93,7 @@ impl HtmlAstNode for SetTagNode {
 
             for_each_child_elements.push(Element {
                 tag: "".to_string(), // Represents a text node
-                data: "${k}=#{v},".to_string(),
+                data: "${safe k}=#{v},".to_string(),
                 attrs: HashMap::new(),
                 childs: vec![],
             });
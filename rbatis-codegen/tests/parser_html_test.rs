@@ -1,4 +1,4 @@
-use rbatis_codegen::codegen::parser_html::{load_mapper_map, load_mapper_vec};
+use rbatis_codegen::codegen::parser_html::{load_mapper_map, load_mapper_vec, parse_html};
 
 #[test]
 fn test_load_mapper_map_basic() {
@@ -83,3 +83,34 @@ fn test_load_mapper_vec_no_id() {
     assert_eq!(elements[0].tag, "select");
     assert_eq!(elements[0].attrs.get("id"), None);
 }
+
+#[test]
+fn test_parse_html_raw_arg() {
+    let html = r#"<select id="find_user">SELECT * FROM ${safe table} WHERE id = #{id}</select>"#;
+    let tokens = parse_html(html, "find_user", &mut vec![]).to_string();
+    assert!(!tokens.contains("compile_error"));
+
+    // the raw `${}` is a compile error with the feature `strict_sql`
+    let html = r#"<select id="find_user">SELECT * FROM users WHERE name = '${name}'</select>"#;
+    let tokens = parse_html(html, "find_user", &mut vec![]).to_string();
+    assert_eq!(
+        tokens.contains("compile_error"),
+        cfg!(feature = "strict_sql")
+    );
+}
+
+#[cfg(feature = "strict_sql")]
+#[test]
+fn test_parse_html_strict_sql() {
+    // the error tell how to fix the raw `${}`
+    let html = r#"<select id="find_user">SELECT * FROM ${table} WHERE id = #{id}</select>"#;
+    let tokens = parse_html(html, "find_user", &mut vec![]).to_string();
+    assert!(tokens.contains("compile_error"));
+    assert!(tokens.contains("#{table}"));
+    assert!(tokens.contains("${safe table}"));
+
+    // the `${safe}` in the nested node is allowed
+    let html = r#"<select id="find_user">SELECT * FROM users <where><if test="name != null">name = ${safe name}</if></where></select>"#;
+    let tokens = parse_html(html, "find_user", &mut vec![]).to_string();
+    assert!(!tokens.contains("compile_error"));
+}
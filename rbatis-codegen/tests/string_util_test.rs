@@ -1,5 +1,5 @@
 use rbatis_codegen::codegen::string_util::{
-    concat_str, count_string_num, find_convert_string, un_packing_string,
+    concat_str, count_string_num, find_convert_string, un_packing_safe, un_packing_string,
 };
 
 #[test]
@@ -99,3 +99,15 @@ fn test_concat_str() {
     concat_str(&mut text, "1=1");
     assert_eq!(text, "WHERE ( 1=1");
 }
+
+#[test]
+fn test_un_packing_safe() {
+    assert_eq!(un_packing_safe("safe table_name"), ("table_name", true));
+    assert_eq!(
+        un_packing_safe(" safe  key.operator_sql() "),
+        ("key.operator_sql()", true)
+    );
+    assert_eq!(un_packing_safe("table_name"), ("table_name", false));
+    assert_eq!(un_packing_safe("safe"), ("safe", false));
+    assert_eq!(un_packing_safe("safe_name"), ("safe_name", false));
+}
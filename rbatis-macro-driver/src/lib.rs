@@ -4,7 +4,7 @@ extern crate rbatis_codegen;
 
 use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
-use syn::{parse_macro_input, ItemFn, Token};
+use syn::{parse_macro_input, DeriveInput, ItemFn, Token};
 
 use crate::macros::html_sql_impl::impl_macro_html_sql;
 use crate::macros::py_sql_impl::impl_macro_py_sql;
@@ -102,6 +102,9 @@ pub fn sql(args: TokenStream, func: TokenStream) -> TokenStream {
 ///     WHERE id  = '2'")]
 ///   pub async fn py_select_rb(rb: &dyn Executor, name: &str) -> Option<MockTable> {}
 /// ```
+/// `#{arg}` is a bind arg(`?`), `${arg}` is formatted into the sql text(it may be sql injection).
+/// with the feature `strict_sql`, `${arg}` is a compile error unless it is marked safe `${safe arg}`.
+///
 /// or read from file
 /// ```rust
 /// //#[rbatis::py_sql(r#"include!("C:/rs/rbatis/target/debug/xx.py_sql")"#)]
@@ -225,3 +228,24 @@ pub fn rb_html(args: TokenStream, func: TokenStream) -> TokenStream {
 pub fn snake_name(args: TokenStream, func: TokenStream) -> TokenStream {
     macros::snake_name::snake_name(args, func)
 }
+
+/// derive the column name constants of a table struct, `Activity::COL.name` = `"name"`.
+/// it follows `#[serde(rename)]`, `#[serde(rename_all)]`, `#[serde(skip)]`, and
+/// `#[serde(flatten)]`(the flatten struct must also derive `Columns`, for example `Activity::COL.base.create_time`)
+/// ```log
+/// #[derive(serde::Serialize, serde::Deserialize, rbatis::Columns)]
+/// pub struct Activity {
+///     pub id: Option<String>,
+///     #[serde(rename = "title")]
+///     pub name: Option<String>,
+/// }
+/// assert_eq!(Activity::COL.name, "title");
+/// ```
+#[proc_macro_derive(Columns)]
+pub fn columns(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match macros::columns::impl_columns(&input) {
+        Ok(stream) => stream.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
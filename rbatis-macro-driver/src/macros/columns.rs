@@ -0,0 +1,226 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Fields, GenericArgument, LitStr, PathArguments, Token, Type};
+
+#[derive(Default)]
+struct SerdeAttr {
+    rename: Option<String>,
+    rename_all: Option<String>,
+    flatten: bool,
+    skip: bool,
+}
+
+fn skip_meta_value(meta: &syn::meta::ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        let _: syn::Expr = meta.value()?.parse()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        let content;
+        syn::parenthesized!(content in meta.input);
+        let _: TokenStream = content.parse()?;
+    }
+    Ok(())
+}
+
+/// `rename = "a"` or `rename(serialize = "a")`
+fn parse_rename(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Option<String>> {
+    if meta.input.peek(Token![=]) {
+        let s: LitStr = meta.value()?.parse()?;
+        return Ok(Some(s.value()));
+    }
+    let mut name = None;
+    meta.parse_nested_meta(|m| {
+        if m.path.is_ident("serialize") {
+            let s: LitStr = m.value()?.parse()?;
+            name = Some(s.value());
+            Ok(())
+        } else {
+            skip_meta_value(&m)
+        }
+    })?;
+    Ok(name)
+}
+
+fn parse_serde_attr(attrs: &[syn::Attribute]) -> syn::Result<SerdeAttr> {
+    let mut result = SerdeAttr::default();
+    for attr in attrs {
+        if !attr.path().is_ident("serde") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                if let Some(v) = parse_rename(&meta)? {
+                    result.rename = Some(v);
+                }
+            } else if meta.path.is_ident("rename_all") {
+                if let Some(v) = parse_rename(&meta)? {
+                    result.rename_all = Some(v);
+                }
+            } else if meta.path.is_ident("flatten") {
+                result.flatten = true;
+            } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_serializing") {
+                result.skip = true;
+            } else {
+                skip_meta_value(&meta)?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(result)
+}
+
+/// the serde `rename_all` rule
+fn rename_all(rule: &str, field: &str) -> String {
+    let words: Vec<&str> = field.split('_').filter(|v| !v.is_empty()).collect();
+    let capitalize = |w: &str| {
+        let mut c = w.chars();
+        match c.next() {
+            Some(f) => f.to_uppercase().collect::<String>() + c.as_str(),
+            None => String::new(),
+        }
+    };
+    match rule {
+        "lowercase" => field.to_lowercase(),
+        "UPPERCASE" => field.to_uppercase(),
+        "PascalCase" => words.iter().map(|w| capitalize(w)).collect(),
+        "camelCase" => {
+            let mut s = String::new();
+            for (i, w) in words.iter().enumerate() {
+                if i == 0 {
+                    s.push_str(w);
+                } else {
+                    s.push_str(&capitalize(w));
+                }
+            }
+            s
+        }
+        "SCREAMING_SNAKE_CASE" => field.to_uppercase(),
+        "kebab-case" => field.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => field.replace('_', "-").to_uppercase(),
+        _ => field.to_string(),
+    }
+}
+
+/// `Option<T>` -> `T`
+fn unwrap_option(ty: &Type) -> &Type {
+    if let Type::Path(p) = ty {
+        if let Some(last) = p.path.segments.last() {
+            if last.ident == "Option" {
+                if let PathArguments::AngleBracketed(args) = &last.arguments {
+                    if let Some(GenericArgument::Type(inner)) = args.args.first() {
+                        return inner;
+                    }
+                }
+            }
+        }
+    }
+    ty
+}
+
+pub(crate) fn impl_columns(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(f) => &f.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "#[derive(Columns)] only support the struct of named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "#[derive(Columns)] only support struct",
+            ))
+        }
+    };
+    let container = parse_serde_attr(&input.attrs)?;
+    let table = &input.ident;
+    let vis = &input.vis;
+    let columns_ident = format_ident!("{}Columns", table);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut struct_fields = vec![];
+    let mut const_fields = vec![];
+    let mut names = vec![];
+    for field in fields {
+        let attr = parse_serde_attr(&field.attrs)?;
+        if attr.skip {
+            continue;
+        }
+        let ident = field.ident.as_ref().unwrap();
+        if attr.flatten {
+            let ty = unwrap_option(&field.ty);
+            struct_fields.push(quote! {
+                pub #ident: <#ty as rbatis::Columns>::Columns
+            });
+            const_fields.push(quote! {
+                #ident: <#ty as rbatis::Columns>::COL
+            });
+            names.push(quote! {
+                names.extend(<#ty as rbatis::Columns>::column_names());
+            });
+            continue;
+        }
+        let field_name = ident.to_string();
+        let field_name = field_name.strip_prefix("r#").unwrap_or(&field_name);
+        let column = match (&attr.rename, &container.rename_all) {
+            (Some(v), _) => v.clone(),
+            (None, Some(rule)) => rename_all(rule, field_name),
+            (None, None) => field_name.to_string(),
+        };
+        let doc = format!("`{}`", column);
+        struct_fields.push(quote! {
+            #[doc = #doc]
+            pub #ident: &'static str
+        });
+        const_fields.push(quote! {
+            #ident: #column
+        });
+        names.push(quote! {
+            names.push(#column);
+        });
+    }
+    let doc = format!("the column names of `{}`, see `{}::COL`", table, table);
+    Ok(quote! {
+        #[doc = #doc]
+        #[derive(Clone, Copy, Debug)]
+        #vis struct #columns_ident {
+            #(#struct_fields,)*
+        }
+
+        impl #impl_generics rbatis::Columns for #table #ty_generics #where_clause {
+            type Columns = #columns_ident;
+            const COL: #columns_ident = #columns_ident {
+                #(#const_fields,)*
+            };
+
+            fn column_names() -> Vec<&'static str> {
+                let mut names = vec![];
+                #(#names)*
+                names
+            }
+        }
+
+        impl #impl_generics #table #ty_generics #where_clause {
+            /// the column names, for example `Self::COL.id`
+            pub const COL: #columns_ident = <Self as rbatis::Columns>::COL;
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::macros::columns::rename_all;
+
+    #[test]
+    fn test_rename_all() {
+        assert_eq!(rename_all("camelCase", "create_time"), "createTime");
+        assert_eq!(rename_all("PascalCase", "create_time"), "CreateTime");
+        assert_eq!(
+            rename_all("SCREAMING_SNAKE_CASE", "create_time"),
+            "CREATE_TIME"
+        );
+        assert_eq!(rename_all("kebab-case", "create_time"), "create-time");
+    }
+}
@@ -1,3 +1,4 @@
+pub mod columns;
 pub mod html_sql_impl;
 pub mod py_sql_impl;
 pub mod snake_name;
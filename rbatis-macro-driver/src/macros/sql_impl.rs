@@ -83,6 +83,7 @@ pub(crate) fn impl_macro_sql(target_fn: &ItemFn, args: &ParseArgs) -> TokenStrea
     let generic = target_fn.sig.generics.clone();
     //gen rust code templete
     let gen_token_temple = quote! {
+       #[allow(clippy::needless_question_mark)]
        pub async fn #func_name_ident #generic(#func_args_stream) -> #return_ty{
            let mut rb_args =vec![];
            #sql_args_gen
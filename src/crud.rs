@@ -1,26 +1,71 @@
-///PySql: gen insert/insert_batch, select_by_map, update_by_map, delete_by_map methods
+///PySql: gen insert/insert_batch, insert_returning/insert_batch_returning, upsert/upsert_batch, select_by_map, select_one_by_map, count_by_map, exists_by_map, select_page_by_map, update_by_map, delete_by_map, delete_by_map_physical methods,
+/// select_by_id, select_by_ids, update_by_id, update_batch_by_id, delete_by_id, delete_by_ids methods of the primary key,
+/// and select_by_wrapper, select_page_by_wrapper, count_by_wrapper, update_by_wrapper, delete_by_wrapper methods of the `Query` wrapper
+///
+/// options(`key = value` after the table name):
+/// * `logic_delete = "delete_flag"`: `delete_by_map` set `delete_flag = 1`,
+///   `select_by_map`/`update_by_map` add `delete_flag = 0` to the condition(if not set),
+///   `insert` set the null `delete_flag` to 0. `delete_by_map_physical` still delete rows.
+/// * `primary_key = "id"` or `primary_key = ["org_id", "user_id"]`: the primary key(default `id`),
+///   the update methods never set it, `select_by_id`/`select_by_ids`/`update_by_id`/`update_batch_by_id`/`delete_by_id`/`delete_by_ids` use it.
+/// * `version = "version"`: optimistic lock, `update_by_map` add `version = ?`(the version of table) to the condition
+///   and set `version` to `version + 1`. it return `version_conflict_error` if no row updated.
+///   the table of null version is updated without lock.
+///
+/// the table name and the columns of reserved words(for example `order`, `group`) are quoted by the driver,
+/// see `crud_traits::quote_identifier`. the key of condition map must be a column or `column operator`(for example `"age >="`),
+/// the other keys return error.
+///
+/// insert and update fill the null columns by the `FillHandler` of `RBatis::set_fill_handler()`,
+/// for example `create_time`/`update_time`.
 ///```rust
 /// use rbs::value;
-/// use rbatis::{Error, RBatis, rbdc::db::ExecResult};
+/// use rbatis::{Error, Page, PageRequest, RBatis, rbdc::db::ExecResult};
 ///
 /// #[derive(serde::Serialize, serde::Deserialize)]
 /// pub struct MockTable{
 ///    pub id: Option<String>
 /// }
 /// rbatis::crud!(MockTable{}); //or crud!(MockTable{},"mock_table");
+/// //or crud!(MockTable{}, logic_delete = "delete_flag");
+/// //or crud!(MockTable{}, "mock_table", logic_delete = "delete_flag");
+/// //or crud!(MockTable{}, primary_key = ["org_id", "user_id"]);
 ///
 /// //use
 /// async fn test_use(rb:&RBatis) -> Result<(),Error>{
 ///  let table = MockTable{id: Some("1".to_string())};
 ///  let result:ExecResult = MockTable::insert(rb, &table).await?;
 ///  let result:ExecResult = MockTable::insert_batch(rb, std::slice::from_ref(&table),10).await?;
+///  let tables:Vec<MockTable> = MockTable::insert_returning(rb, &table).await?;
+///  let tables:Vec<MockTable> = MockTable::insert_batch_returning(rb, std::slice::from_ref(&table),10).await?;
+///  let result:ExecResult = MockTable::upsert(rb, &table, &["id"]).await?;
+///  let result:ExecResult = MockTable::upsert_batch(rb, std::slice::from_ref(&table), &["id"], 10).await?;
 ///
 ///  let tables:Vec<MockTable> = MockTable::select_by_map(rb,value!{"id":"1"}).await?;
 ///  let tables:Vec<MockTable> = MockTable::select_by_map(rb,value!{"id":["1","2","3"]}).await?;
 ///  let tables:Vec<MockTable> = MockTable::select_by_map(rb,value!{"id":"1", "column": ["id", "name"]}).await?;
+///  let tables:Vec<MockTable> = MockTable::select_by_map(rb,value!{"order_by": "id desc", "limit": 10, "offset": 20}).await?;
+///  let one:Option<MockTable> = MockTable::select_one_by_map(rb,value!{"id":"1"}).await?;
+///  let count:u64 = MockTable::count_by_map(rb,value!{"id":["1","2","3"]}).await?;
+///  let exists:bool = MockTable::exists_by_map(rb,value!{"id":"1"}).await?;
+///  let page:Page<MockTable> = MockTable::select_page_by_map(rb, &PageRequest::new(1, 10), value!{"order_by": "id desc"}).await?;
 ///
 ///  let result:ExecResult = MockTable::update_by_map(rb, &table, value!{"id":"1"}).await?;
 ///  let result:ExecResult = MockTable::delete_by_map(rb, value!{"id":"1"}).await?;
+///
+///  let one:Option<MockTable> = MockTable::select_by_id(rb, "1").await?;
+///  let tables:Vec<MockTable> = MockTable::select_by_ids(rb, &["1", "2"]).await?;
+///  let result:ExecResult = MockTable::update_by_id(rb, &table).await?;
+///  let result:ExecResult = MockTable::update_batch_by_id(rb, std::slice::from_ref(&table), 100).await?;
+///  let result:ExecResult = MockTable::delete_by_id(rb, "1").await?;
+///  let result:ExecResult = MockTable::delete_by_ids(rb, &["1", "2"]).await?;
+///
+///  let query = rbatis::Query::new().eq("id", "1").order_by_desc("id").limit(10);
+///  let tables:Vec<MockTable> = MockTable::select_by_wrapper(rb, &query).await?;
+///  let count:u64 = MockTable::count_by_wrapper(rb, &query).await?;
+///  let page:Page<MockTable> = MockTable::select_page_by_wrapper(rb, &PageRequest::new(1, 10), &query).await?;
+///  let result:ExecResult = MockTable::update_by_wrapper(rb, &table, &query).await?;
+///  let result:ExecResult = MockTable::delete_by_wrapper(rb, &query).await?;
 ///  Ok(())
 /// }
 ///
@@ -28,10 +73,23 @@
 /// ```
 #[macro_export]
 macro_rules! crud {
+    // the table name, default is the snake name of `$table`
+    (@table_name $table:ty, $table_name:expr) => {{
+        let mut table_name = $table_name.to_string();
+        if table_name.is_empty() {
+            #[$crate::snake_name($table)]
+            fn snake_name() {}
+            table_name = snake_name();
+        }
+        table_name
+    }};
     ($table:ty{}) => {
         $crate::crud!($table {}, "");
     };
-    ($table:ty{},$table_name:expr) => {
+    ($table:ty{}, $($opt:ident = $val:expr),+) => {
+        $crate::crud!($table {}, "", $($opt = $val),+);
+    };
+    ($table:ty{},$table_name:expr $(, $opt:ident = $val:expr)*) => {
         // insert
         impl $table {
             /// batch insert records
@@ -44,7 +102,7 @@ macro_rules! crud {
             ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
                 use $crate::crud_traits::ColumnSet;
                 #[$crate::py_sql(
-                    "`insert into ${table_name} `
+                    "`insert into ${safe table_name} `
                     trim ',':
                      bind columns = tables.column_sets():
                      for idx,table in tables:
@@ -52,7 +110,7 @@ macro_rules! crud {
                          `(`
                          trim ',':
                            for _,v in columns:
-                              ${v},
+                              ${safe v},
                          `) VALUES `
                       (
                       trim ',':
@@ -63,7 +121,7 @@ macro_rules! crud {
                 )]
                 async fn insert_batch(
                     executor: &dyn $crate::executor::Executor,
-                    tables: &[$table],
+                    tables: &rbs::Value,
                     table_name: &str,
                 ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error>
                 {
@@ -74,25 +132,23 @@ macro_rules! crud {
                         "insert can not insert empty array tables!",
                     ));
                 }
-                let mut table_name = $table_name.to_string();
-                if table_name.is_empty() {
-                    #[$crate::snake_name($table)]
-                    fn snake_name() {}
-                    table_name = snake_name();
-                }
+                let table_name = $crate::crud!(@table_name $table, $table_name);
                 let mut result = $crate::rbdc::db::ExecResult {
                     rows_affected: 0,
                     last_insert_id: rbs::Value::Null,
                 };
+                let driver_type = executor.driver_type()?.to_string();
+                let quoted_table_name =
+                    $crate::crud_traits::quote_identifier(&driver_type, &table_name);
+                let options = $crate::crud_traits::CrudOptions::new()$(.$opt($val))*;
                 let ranges =
                     $crate::plugin::Page::<()>::make_ranges(tables.len() as u64, batch_size);
                 for (offset, limit) in ranges {
-                    let exec_result = insert_batch(
-                        executor,
-                        &tables[offset as usize..limit as usize],
-                        table_name.as_str(),
-                    )
-                    .await?;
+                    let mut rows = rbs::value!(&tables[offset as usize..limit as usize]);
+                    options.logic_delete_rows(&mut rows);
+                    $crate::plugin::fill_insert(executor.rb_ref(), &table_name, &mut rows);
+                    $crate::crud_traits::quote_columns(&driver_type, &mut rows);
+                    let exec_result = insert_batch(executor, &rows, &quoted_table_name).await?;
                     result.rows_affected += exec_result.rows_affected;
                     result.last_insert_id = exec_result.last_insert_id;
                 }
@@ -108,13 +164,167 @@ macro_rules! crud {
             ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
                 <$table>::insert_batch(executor, std::slice::from_ref(table), 1).await
             }
+
+            /// batch insert or update(on the conflict of `conflict_columns`) records,
+            /// the non-null columns not in `conflict_columns` are updated.
+            ///
+            /// sql(sqlite/postgres): `INSERT INTO table_name (id, name) VALUES (?, ?), (?, ?) ON CONFLICT (id) DO UPDATE SET name = excluded.name`
+            /// sql(mysql): `INSERT INTO table_name (id, name) VALUES (?, ?), (?, ?) ON DUPLICATE KEY UPDATE name = values(name)`
+            /// sql(mssql): `MERGE INTO table_name AS target USING (VALUES (?, ?), (?, ?)) AS source (id, name) ON target.id = source.id WHEN MATCHED ... WHEN NOT MATCHED ...;`
+            pub async fn upsert_batch(
+                executor: &dyn $crate::executor::Executor,
+                tables: &[$table],
+                conflict_columns: &[&str],
+                batch_size: u64,
+            ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
+                if tables.is_empty() {
+                    return Err($crate::rbdc::Error::from(
+                        "upsert can not upsert empty array tables!",
+                    ));
+                }
+                let fields = $crate::crud_traits::struct_fields::<$table>();
+                for column in conflict_columns {
+                    $crate::crud_traits::check_column(column, fields)?;
+                }
+                let table_name = $crate::crud!(@table_name $table, $table_name);
+                let mut result = $crate::rbdc::db::ExecResult {
+                    rows_affected: 0,
+                    last_insert_id: rbs::Value::Null,
+                };
+                let driver_type = executor.driver_type()?.to_string();
+                let quoted_table_name =
+                    $crate::crud_traits::quote_identifier(&driver_type, &table_name);
+                let conflict_columns: Vec<String> = conflict_columns
+                    .iter()
+                    .map(|v| $crate::crud_traits::quote_identifier(&driver_type, v))
+                    .collect();
+                let conflict_columns: Vec<&str> =
+                    conflict_columns.iter().map(|v| v.as_str()).collect();
+                let options = $crate::crud_traits::CrudOptions::new()$(.$opt($val))*;
+                let ranges =
+                    $crate::plugin::Page::<()>::make_ranges(tables.len() as u64, batch_size);
+                for (offset, limit) in ranges {
+                    let mut rows = rbs::value!(&tables[offset as usize..limit as usize]);
+                    options.logic_delete_rows(&mut rows);
+                    $crate::plugin::fill_insert(executor.rb_ref(), &table_name, &mut rows);
+                    $crate::crud_traits::quote_columns(&driver_type, &mut rows);
+                    let (sql, args) = $crate::crud_traits::upsert_sql(
+                        &driver_type,
+                        &quoted_table_name,
+                        &rows,
+                        &conflict_columns,
+                    )?;
+                    let exec_result = executor.exec(&sql, args).await?;
+                    result.rows_affected += exec_result.rows_affected;
+                    result.last_insert_id = exec_result.last_insert_id;
+                }
+                Ok(result)
+            }
+
+            /// insert or update(on the conflict of `conflict_columns`) a single record
+            ///
+            /// sql: see `upsert_batch`
+            pub async fn upsert(
+                executor: &dyn $crate::executor::Executor,
+                table: &$table,
+                conflict_columns: &[&str],
+            ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
+                <$table>::upsert_batch(executor, std::slice::from_ref(table), conflict_columns, 1)
+                    .await
+            }
+
+            /// batch insert records and return the inserted rows(with the generated ids and default values)
+            ///
+            /// sql(sqlite/postgres): `INSERT INTO table_name (column1, column2, ...) VALUES (?, ?), (?, ?) RETURNING *`
+            /// sql(mssql): `INSERT INTO table_name (column1, column2, ...) OUTPUT INSERTED.* VALUES (?, ?), (?, ?)`
+            /// sql(mysql and others): `INSERT INTO ...` then `SELECT * FROM table_name WHERE id in (?, ?)`,
+            /// the null id is the `last_insert_id` of a single `auto_increment` primary key,
+            /// so these rows are inserted one by one(please run it in a tx if the batch must be atomic).
+            pub async fn insert_batch_returning(
+                executor: &dyn $crate::executor::Executor,
+                tables: &[$table],
+                batch_size: u64,
+            ) -> std::result::Result<Vec<$table>, $crate::rbdc::Error> {
+                if tables.is_empty() {
+                    return Err($crate::rbdc::Error::from(
+                        "insert can not insert empty array tables!",
+                    ));
+                }
+                let table_name = $crate::crud!(@table_name $table, $table_name);
+                let driver_type = executor.driver_type()?.to_string();
+                let quoted_table_name =
+                    $crate::crud_traits::quote_identifier(&driver_type, &table_name);
+                let options = $crate::crud_traits::CrudOptions::new()$(.$opt($val))*;
+                let mut result = Vec::with_capacity(tables.len());
+                let ranges =
+                    $crate::plugin::Page::<()>::make_ranges(tables.len() as u64, batch_size);
+                for (offset, limit) in ranges {
+                    let mut rows = rbs::value!(&tables[offset as usize..limit as usize]);
+                    options.logic_delete_rows(&mut rows);
+                    $crate::plugin::fill_insert(executor.rb_ref(), &table_name, &mut rows);
+                    //the ids are read by the field names of `rows`, not the quoted columns
+                    let mut quoted_rows = rows.clone();
+                    $crate::crud_traits::quote_columns(&driver_type, &mut quoted_rows);
+                    let records = match $crate::crud_traits::insert_returning_sql(
+                        &driver_type,
+                        &quoted_table_name,
+                        &quoted_rows,
+                    )? {
+                        Some((sql, args)) => executor.query(&sql, args).await?,
+                        None => {
+                            let mut ids = vec![];
+                            if options.has_generated_id(&rows) {
+                                //the generated ids of a multi-row insert may be not consecutive
+                                let rows = (&rows).into_iter().zip(&quoted_rows);
+                                for ((_, row), (_, quoted_row)) in rows {
+                                    let (sql, args) = $crate::crud_traits::insert_sql(
+                                        &quoted_table_name,
+                                        &rbs::Value::Array(vec![quoted_row.clone()]),
+                                    )?;
+                                    let last_insert_id = executor.exec(&sql, args).await?.last_insert_id;
+                                    ids.push(options.inserted_id(row, &last_insert_id)?);
+                                }
+                            } else {
+                                let (sql, args) = $crate::crud_traits::insert_sql(
+                                    &quoted_table_name,
+                                    &quoted_rows,
+                                )?;
+                                executor.exec(&sql, args).await?;
+                                for (_, row) in &rows {
+                                    ids.push(options.table_id(row)?);
+                                }
+                            }
+                            let (sql, args) = options
+                                .id_query(&ids)?
+                                .select_sql(&quoted_table_name, &driver_type)?;
+                            let records = executor.query(&sql, args).await?;
+                            options.sort_by_ids(records, &ids)
+                        }
+                    };
+                    let records: Vec<$table> = $crate::decode(records)?;
+                    result.extend(records);
+                }
+                Ok(result)
+            }
+
+            /// insert a single record and return the inserted rows
+            ///
+            /// sql: see `insert_batch_returning`
+            pub async fn insert_returning(
+                executor: &dyn $crate::executor::Executor,
+                table: &$table,
+            ) -> std::result::Result<Vec<$table>, $crate::rbdc::Error> {
+                <$table>::insert_batch_returning(executor, std::slice::from_ref(table), 1).await
+            }
         }
         // select
         impl $table {
             /// select records by condition map.
             /// supports "column" key in condition to select specific columns, e.g. `value!{"col1":"val1", "column": ["col1", "col2"]}`
+            /// supports "group_by", "order_by", "limit", "offset" keys, the columns must be the fields of table,
+            /// e.g. `value!{"status": 1, "order_by": ["create_time desc", "id"], "limit": 10}`
             ///
-            /// sql: `SELECT col1, col2, ... FROM table_name WHERE col1 = ? and col2 in (?, ?, ...)`
+            /// sql: `SELECT col1, col2, ... FROM table_name WHERE col1 = ? and col2 in (?, ?, ...) ORDER BY col1 desc LIMIT 10`
             ///
             /// condition map -> where sql:
             /// - `value!{"col1": "val1"}`                       -> `WHERE col1 = 'val1'`
@@ -124,56 +334,65 @@ macro_rules! crud {
             /// - null values are skipped
             pub async fn select_by_map(
                 executor: &dyn $crate::executor::Executor,
-                mut condition: rbs::Value,
+                condition: rbs::Value,
             ) -> std::result::Result<Vec<$table>, $crate::rbdc::Error> {
+                let records = <$table>::select_value_by_map(executor, condition).await?;
+                $crate::decode(records)
+            }
+
+            /// the same as `select_by_map`, but return the rows `Value`(`[{k:v},...]`)
+            pub async fn select_value_by_map(
+                executor: &dyn $crate::executor::Executor,
+                condition: rbs::Value,
+            ) -> std::result::Result<rbs::Value, $crate::rbdc::Error> {
+                let table_column = $crate::crud_traits::select_columns(
+                    executor.driver_type()?,
+                    &condition["column"],
+                    $crate::crud_traits::struct_fields::<$table>(),
+                )?;
+                <$table>::select_value_by_columns(executor, table_column, condition).await
+            }
+
+            /// the same as `select_value_by_map`, but the `table_column` sql is not checked(the "column" key is ignored),
+            /// it is not public for the `count_by_map`/`exists_by_map` only
+            async fn select_value_by_columns(
+                executor: &dyn $crate::executor::Executor,
+                table_column: String,
+                mut condition: rbs::Value,
+            ) -> std::result::Result<rbs::Value, $crate::rbdc::Error> {
                 use rbatis::crud_traits::ValueOperatorSql;
-                // Extract column specification and remove it from condition
-                let table_column = {
-                    let mut columns = String::new();
-                    let mut clean_map = rbs::value::map::ValueMap::with_capacity(condition.len());
-                    for (k, v) in condition {
-                        match k.as_str() {
-                            Some("column") => {
-                                columns = match v {
-                                    rbs::Value::String(s) => s.clone(),
-                                    rbs::Value::Array(arr) => {
-                                        let cols: Vec<&str> =
-                                            arr.iter().filter_map(|v| v.as_str()).collect();
-                                        if cols.is_empty() {
-                                            "*".to_string()
-                                        } else {
-                                            cols.join(", ")
-                                        }
-                                    }
-                                    _ => "*".to_string(),
-                                };
-                            }
-                            _ => {
-                                clean_map.insert(k.clone(), v.clone());
-                            }
-                        }
-                    }
-                    if columns.is_empty() {
-                        columns = "*".to_string();
+                let driver_type = executor.driver_type()?;
+                match &mut condition {
+                    rbs::Value::Map(m) => {
+                        m.remove(&rbs::Value::from("column"));
                     }
-                    condition = rbs::Value::Map(clean_map);
-                    columns
-                };
+                    _ => condition = rbs::Value::Map(rbs::value::map::ValueMap::new()),
+                }
+                let tail_sql = $crate::crud_traits::select_tail_sql(
+                    &mut condition,
+                    $crate::crud_traits::struct_fields::<$table>(),
+                    driver_type,
+                )?;
+                $crate::crud_traits::CrudOptions::new()$(.$opt($val))*
+                    .logic_delete_condition(&mut condition);
+                $crate::crud_traits::quote_condition(driver_type, &mut condition)?;
 
                 #[$crate::py_sql(
-                    "`select ${table_column} from ${table_name}`
+                    "`select ${safe table_column} from ${safe table_name}`
            trim end=' where ':
              ` where `
              trim ' and ': for key,item in condition:
                           if item == null:
                              continue:
                           if !item.is_array():
-                            ` and ${key.operator_sql()}#{item}`
+                            ` and ${safe key.operator_sql()}#{item}`
                           if item.is_array():
-                            ` and ${key} in (`
+                            ` and ${safe key} in (`
                                trim ',': for _,item_array in item:
                                     #{item_array},
                             `)`
+           if tail_sql != '':
+             ` ${safe tail_sql}`
         "
                 )]
                 async fn select_by_map(
@@ -181,21 +400,157 @@ macro_rules! crud {
                     table_name: String,
                     table_column: &str,
                     condition: &rbs::Value,
-                ) -> std::result::Result<Vec<$table>, $crate::rbdc::Error> {
+                    tail_sql: &str,
+                ) -> std::result::Result<rbs::Value, $crate::rbdc::Error> {
                     for (_, v) in condition {
                         if v.is_array() && v.is_empty() {
-                            return Ok(vec![]);
+                            return Ok(rbs::Value::Array(vec![]));
                         }
                     }
                     impled!()
                 }
-                let mut table_name = $table_name.to_string();
-                if table_name.is_empty() {
-                    #[$crate::snake_name($table)]
-                    fn snake_name() {}
-                    table_name = snake_name();
+                let table_name = $crate::crud!(@table_name $table, $table_name);
+                select_by_map(
+                    executor,
+                    $crate::crud_traits::quote_identifier(driver_type, &table_name),
+                    &table_column,
+                    &condition,
+                    tail_sql.trim_start(),
+                )
+                .await
+            }
+
+            /// count records by condition map, the condition is the same as `select_by_map`
+            /// (the "column", "order_by" keys are ignored, the "group_by", "limit", "offset" keys are not supported)
+            ///
+            /// sql: `SELECT count(1) as count FROM table_name WHERE col1 = ?`
+            pub async fn count_by_map(
+                executor: &dyn $crate::executor::Executor,
+                mut condition: rbs::Value,
+            ) -> std::result::Result<u64, $crate::rbdc::Error> {
+                $crate::crud_traits::reject_keys(&condition, "count_by_map", &["group_by", "limit", "offset"])?;
+                $crate::crud_traits::set_key(&mut condition, "order_by", rbs::Value::Null);
+                let count = <$table>::select_value_by_columns(
+                    executor,
+                    "count(1) as count".to_string(),
+                    condition,
+                )
+                .await?;
+                $crate::decode::<u64>(count)
+            }
+
+            /// is there any record of the condition map? the condition is the same as `select_by_map`
+            /// (the "column", "order_by" keys are ignored, the "group_by", "limit", "offset" keys are not supported)
+            ///
+            /// sql: `SELECT 1 FROM table_name WHERE col1 = ? LIMIT 1`
+            pub async fn exists_by_map(
+                executor: &dyn $crate::executor::Executor,
+                mut condition: rbs::Value,
+            ) -> std::result::Result<bool, $crate::rbdc::Error> {
+                $crate::crud_traits::reject_keys(&condition, "exists_by_map", &["group_by", "limit", "offset"])?;
+                $crate::crud_traits::set_key(&mut condition, "order_by", rbs::Value::Null);
+                $crate::crud_traits::set_key(&mut condition, "limit", rbs::Value::U64(1));
+                let rows =
+                    <$table>::select_value_by_columns(executor, "1".to_string(), condition).await?;
+                Ok(!rows.is_empty())
+            }
+
+            /// select one record by condition map, return error if more than one record matched.
+            /// the condition is the same as `select_by_map`(the "limit", "offset" keys are not supported)
+            ///
+            /// sql: `SELECT * FROM table_name WHERE col1 = ? LIMIT 2`
+            pub async fn select_one_by_map(
+                executor: &dyn $crate::executor::Executor,
+                mut condition: rbs::Value,
+            ) -> std::result::Result<Option<$table>, $crate::rbdc::Error> {
+                $crate::crud_traits::reject_keys(&condition, "select_one_by_map", &["limit", "offset"])?;
+                $crate::crud_traits::set_key(&mut condition, "limit", rbs::Value::U64(2));
+                let mut records: Vec<$table> = <$table>::select_by_map(executor, condition).await?;
+                if records.len() > 1 {
+                    return Err($crate::rbdc::Error::from(
+                        "[rb] select_one_by_map matched more than one record",
+                    ));
+                }
+                Ok(records.pop())
+            }
+
+            /// select a page of records by condition map, it needs the `PageIntercept`(the default intercept of `RBatis`).
+            /// the condition is the same as `select_by_map`, except the "limit" and "offset" keys(the `page_request` decide them)
+            ///
+            /// sql: `SELECT count(1) as count FROM table_name WHERE col1 = ?`
+            /// and `SELECT * FROM table_name WHERE col1 = ? ORDER BY col1 desc LIMIT 0,10`
+            pub async fn select_page_by_map(
+                executor: &dyn $crate::executor::Executor,
+                page_request: &dyn $crate::plugin::IPageRequest,
+                condition: rbs::Value,
+            ) -> std::result::Result<$crate::plugin::Page<$table>, $crate::rbdc::Error> {
+                $crate::crud_traits::reject_keys(&condition, "select_page_by_map", &["limit", "offset"])?;
+                let mut executor = executor;
+                let mut conn = None;
+                if executor.name().eq($crate::executor::Executor::name(executor.rb_ref())) {
+                    conn = Some(executor.rb_ref().acquire().await?);
+                    match &conn {
+                        Some(c) => {
+                            executor = c;
+                        }
+                        None => {}
+                    }
                 }
-                select_by_map(executor, table_name, &table_column, &condition).await
+                let mut page = $crate::plugin::Page::<$table>::new(
+                    page_request.page_no(),
+                    page_request.page_size(),
+                    0,
+                    vec![],
+                );
+                let intercept = executor
+                    .rb_ref()
+                    .get_intercept::<$crate::plugin::intercept_page::PageIntercept>()
+                    .ok_or_else(|| $crate::rbdc::Error::from("PageIntercept not found"))?;
+                if page_request.do_count() {
+                    intercept.count_ids.insert(
+                        executor.id(),
+                        $crate::plugin::PageRequest::new(
+                            page_request.page_no(),
+                            page_request.page_size(),
+                        ),
+                    );
+                    let total_value =
+                        <$table>::select_value_by_map(executor, condition.clone()).await?;
+                    page.total = $crate::decode::<u64>(total_value)?;
+                }
+                intercept.select_ids.insert(
+                    executor.id(),
+                    $crate::plugin::PageRequest::new(page_request.page_no(), page_request.page_size()),
+                );
+                let records_value = <$table>::select_value_by_map(executor, condition).await?;
+                page.records = $crate::decode(records_value)?;
+                Ok(page)
+            }
+
+            /// select a page of records by the `Query` wrapper, the limit and offset of `Query` are decided by the `page_request`.
+            ///
+            /// sql: `SELECT count(1) as count FROM table_name WHERE name = ?`
+            /// and `SELECT * FROM table_name WHERE name = ? ORDER BY id desc LIMIT 10 OFFSET 0`
+            pub async fn select_page_by_wrapper(
+                executor: &dyn $crate::executor::Executor,
+                page_request: &dyn $crate::plugin::IPageRequest,
+                query: &$crate::query::Query,
+            ) -> std::result::Result<$crate::plugin::Page<$table>, $crate::rbdc::Error> {
+                let mut page = $crate::plugin::Page::<$table>::new(
+                    page_request.page_no(),
+                    page_request.page_size(),
+                    0,
+                    vec![],
+                );
+                if page_request.do_count() {
+                    page.total = <$table>::count_by_wrapper(executor, query).await?;
+                }
+                let query = query
+                    .clone()
+                    .limit(page_request.page_size())
+                    .offset(page_request.offset());
+                page.records = <$table>::select_by_wrapper(executor, &query).await?;
+                Ok(page)
             }
         }
         // update
@@ -204,7 +559,8 @@ macro_rules! crud {
             /// supports "column" key in condition to update specific columns, e.g. `value!{"col1":"val1", "column": ["col2", "col3"]}`
             ///
             /// sql: `UPDATE table_name SET col1 = ?, col2 = ?, ... WHERE col1 = ? and col2 in (?, ?, ...)`
-            /// note: skips null fields by default, skips 'id' field always
+            /// note: skips null fields by default, skips the primary key(default 'id') always
+            /// note: with the `version` option, return `version_conflict_error` if the version is changed by others
             ///
             /// condition map -> where sql:
             /// - `value!{"col1": "val1"}`                       -> `WHERE col1 = 'val1'`
@@ -220,7 +576,7 @@ macro_rules! crud {
                 use rbatis::crud_traits::{FilterByColumns, ValueOperatorSql};
 
                 // Extract column list for selective updates - implements GitHub issue #591
-                let set_columns = {
+                let mut set_columns = {
                     let mut columns = rbs::Value::Null;
                     let mut clean_map = rbs::value::map::ValueMap::with_capacity(condition.len());
                     for (k, v) in condition {
@@ -253,21 +609,23 @@ macro_rules! crud {
                     condition = rbs::Value::Map(clean_map);
                     columns
                 };
+                let options = $crate::crud_traits::CrudOptions::new()$(.$opt($val))*;
+                options.logic_delete_condition(&mut condition);
                 #[$crate::py_sql(
-                    "`update ${table_name}
+                    "`update ${safe table_name}
                       if skip_null == false:
-                        set collection='table',skips='id',skip_null=false:
+                        set collection='table',skips=' ',skip_null=false:
                       if skip_null == true:
-                        set collection='table',skips='id':
+                        set collection='table',skips=' ':
                       trim end=' where ':
                        ` where `
                        trim ' and ': for key,item in condition:
                             if item == null:
                                continue:
                             if !item.is_array():
-                              ` and ${key.operator_sql()}#{item}`
+                              ` and ${safe key.operator_sql()}#{item}`
                             if item.is_array():
-                              ` and ${key} in (`
+                              ` and ${safe key} in (`
                                  trim ',': for _,item_array in item:
                                       #{item_array},
                               `)`
@@ -288,28 +646,63 @@ macro_rules! crud {
                     }
                     impled!()
                 }
-                let mut table_name = $table_name.to_string();
-                if table_name.is_empty() {
-                    #[$crate::snake_name($table)]
-                    fn snake_name() {}
-                    table_name = snake_name();
-                }
-                let table_value = rbs::value!(table);
+                let table_name = $crate::crud!(@table_name $table, $table_name);
+                let mut table_value = rbs::value!(table);
+                $crate::plugin::fill_update(
+                    executor.rb_ref(),
+                    &table_name,
+                    &mut table_value,
+                    &mut set_columns,
+                );
+                let has_empty_array = (&condition)
+                    .into_iter()
+                    .any(|(_, v)| v.is_array() && v.is_empty());
+                let version = match has_empty_array {
+                    true => None,
+                    false => options.version_lock(&mut table_value, &mut condition)?,
+                };
+                options.remove_primary_key(&mut table_value);
                 let mut skip_null = true;
                 let table = if set_columns != rbs::Value::Null {
                     skip_null = false;
+                    let mut set_columns = set_columns;
+                    if let (Some(_), Some(column), rbs::Value::Array(columns)) =
+                        (&version, &options.version, &mut set_columns)
+                    {
+                        //the version column is always updated
+                        columns.push(rbs::Value::String(column.clone()));
+                    }
                     table_value.filter_by_columns(&set_columns)
                 } else {
                     table_value
                 };
-                update_by_map_internal(executor, table_name, &table, &condition, skip_null).await
+                let driver_type = executor.driver_type()?;
+                let mut table = table;
+                $crate::crud_traits::quote_columns(driver_type, &mut table);
+                $crate::crud_traits::quote_condition(driver_type, &mut condition)?;
+                let result = update_by_map_internal(
+                    executor,
+                    $crate::crud_traits::quote_identifier(driver_type, &table_name),
+                    &table,
+                    &condition,
+                    skip_null,
+                )
+                .await?;
+                if let Some(version) = version {
+                    if result.rows_affected == 0 {
+                        return Err($crate::version_conflict_error(&table_name, &version));
+                    }
+                }
+                Ok(result)
             }
         }
         // delete
         impl $table {
-            /// delete records by condition map
+            /// delete records by condition map.
+            /// with the `logic_delete` option, it update the logic delete column to 1
             ///
             /// sql: `DELETE FROM table_name WHERE col1 = ? and col2 in (?, ?, ...)`
+            /// or `UPDATE table_name SET delete_flag = 1 WHERE col1 = ? and col2 in (?, ?, ...)`
             ///
             /// condition map -> where sql:
             /// - `value!{"col1": "val1"}`                       -> `WHERE col1 = 'val1'`
@@ -323,16 +716,79 @@ macro_rules! crud {
             ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
                 use rbatis::crud_traits::ValueOperatorSql;
                 #[$crate::py_sql(
-                    "`delete from ${table_name}`
+                    "`update ${safe table_name} set ${safe logic_delete} = 1`
+           trim end=' where ':
+             ` where `
+             trim ' and ': for key,item in condition:
+                          if item == null:
+                             continue:
+                          if !item.is_array():
+                            ` and ${safe key.operator_sql()}#{item}`
+                          if item.is_array():
+                            ` and ${safe key} in (`
+                               trim ',': for _,item_array in item:
+                                    #{item_array},
+                            `)`
+        "
+                )]
+                async fn logic_delete_by_map(
+                    executor: &dyn $crate::executor::Executor,
+                    table_name: String,
+                    logic_delete: &str,
+                    condition: &rbs::Value,
+                ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error>
+                {
+                    for (_, v) in condition {
+                        if v.is_array() && v.is_empty() {
+                            return Ok($crate::rbdc::db::ExecResult::default());
+                        }
+                    }
+                    impled!()
+                }
+                let options = $crate::crud_traits::CrudOptions::new()$(.$opt($val))*;
+                let logic_delete = match &options.logic_delete {
+                    None => return <$table>::delete_by_map_physical(executor, condition).await,
+                    Some(v) => v,
+                };
+                let table_name = $crate::crud!(@table_name $table, $table_name);
+                let driver_type = executor.driver_type()?;
+                let mut condition = condition;
+                $crate::crud_traits::quote_condition(driver_type, &mut condition)?;
+                logic_delete_by_map(
+                    executor,
+                    $crate::crud_traits::quote_identifier(driver_type, &table_name),
+                    &$crate::crud_traits::quote_identifier(driver_type, logic_delete),
+                    &condition,
+                )
+                .await
+            }
+
+            /// delete records by condition map, it always delete the rows(ignore the `logic_delete` option)
+            ///
+            /// sql: `DELETE FROM table_name WHERE col1 = ? and col2 in (?, ?, ...)`
+            ///
+            /// condition map -> where sql:
+            /// - `value!{"col1": "val1"}`                       -> `WHERE col1 = 'val1'`
+            /// - `value!{"col1": 1, "col2": "val2"}`           -> `WHERE col1 = 1 and col2 = 'val2'`
+            /// - `value!{"col1": ["v1", "v2", "v3"]}`          -> `WHERE col1 in ('v1', 'v2', 'v3')`
+            /// - `value!{"col1": "val1", "col2": ["a", "b"]}`  -> `WHERE col1 = 'val1' and col2 in ('a', 'b')`
+            /// - null values are skipped
+            pub async fn delete_by_map_physical(
+                executor: &dyn $crate::executor::Executor,
+                condition: rbs::Value,
+            ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
+                use rbatis::crud_traits::ValueOperatorSql;
+                #[$crate::py_sql(
+                    "`delete from ${safe table_name}`
            trim end=' where ':
              ` where `
              trim ' and ': for key,item in condition:
                           if item == null:
                              continue:
                           if !item.is_array():
-                            ` and ${key.operator_sql()}#{item}`
+                            ` and ${safe key.operator_sql()}#{item}`
                           if item.is_array():
-                            ` and ${key} in (`
+                            ` and ${safe key} in (`
                                trim ',': for _,item_array in item:
                                     #{item_array},
                             `)`
@@ -351,13 +807,254 @@ macro_rules! crud {
                     }
                     impled!()
                 }
-                let mut table_name = $table_name.to_string();
-                if table_name.is_empty() {
-                    #[$crate::snake_name($table)]
-                    fn snake_name() {}
-                    table_name = snake_name();
+                let table_name = $crate::crud!(@table_name $table, $table_name);
+                let driver_type = executor.driver_type()?;
+                let mut condition = condition;
+                $crate::crud_traits::quote_condition(driver_type, &mut condition)?;
+                delete_by_map(
+                    executor,
+                    $crate::crud_traits::quote_identifier(driver_type, &table_name),
+                    &condition,
+                )
+                .await
+            }
+        }
+        // by id
+        impl $table {
+            /// select a record by the primary key(default 'id', see the `primary_key` option).
+            /// the id of composite primary key is a map `value!{"org_id": 1, "user_id": 2}` or a tuple `(1, 2)`
+            ///
+            /// sql: `SELECT * FROM table_name WHERE id = ?`
+            pub async fn select_by_id<V: serde::Serialize>(
+                executor: &dyn $crate::executor::Executor,
+                id: V,
+            ) -> std::result::Result<Option<$table>, $crate::rbdc::Error> {
+                let query = $crate::crud_traits::CrudOptions::new()$(.$opt($val))*
+                    .id_query(&[rbs::value!(id)])?;
+                let records = <$table>::select_by_wrapper(executor, &query).await?;
+                Ok(records.into_iter().next())
+            }
+
+            /// select records by the primary keys
+            ///
+            /// sql: `SELECT * FROM table_name WHERE id in (?, ?)`
+            /// or `SELECT * FROM table_name WHERE ((org_id = ? and user_id = ?) or (org_id = ? and user_id = ?))`
+            pub async fn select_by_ids<V: serde::Serialize>(
+                executor: &dyn $crate::executor::Executor,
+                ids: &[V],
+            ) -> std::result::Result<Vec<$table>, $crate::rbdc::Error> {
+                if ids.is_empty() {
+                    return Ok(vec![]);
                 }
-                delete_by_map(executor, table_name, &condition).await
+                let ids: Vec<rbs::Value> = ids.iter().map(|v| rbs::value!(v)).collect();
+                let query = $crate::crud_traits::CrudOptions::new()$(.$opt($val))*.id_query(&ids)?;
+                <$table>::select_by_wrapper(executor, &query).await
+            }
+
+            /// update a record by the primary key of table, the primary key can not be null
+            /// note: skips null fields, skips the primary key always
+            ///
+            /// sql: `UPDATE table_name SET col1 = ?, col2 = ? WHERE id = ?`
+            pub async fn update_by_id(
+                executor: &dyn $crate::executor::Executor,
+                table: &$table,
+            ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
+                let options = $crate::crud_traits::CrudOptions::new()$(.$opt($val))*;
+                let id = options.table_id(&rbs::value!(table))?;
+                let query = options.id_query(&[id])?;
+                <$table>::update_by_wrapper(executor, table, &query).await
+            }
+
+            /// batch update records by the primary key of tables, the primary key can not be null.
+            /// the null fields of a table are not updated, the primary key is never updated.
+            /// mysql/sqlite use one `CASE WHEN` statement per batch, postgres use `UPDATE ... FROM (VALUES ...)`,
+            /// the other drivers(or the `version` option) run `update_by_id` one by one.
+            /// return the total `rows_affected`, use a transaction to update all or nothing.
+            ///
+            /// sql(mysql/sqlite): `UPDATE table_name SET name = CASE WHEN id = ? THEN ? WHEN id = ? THEN ? ELSE name END WHERE id in (?, ?)`
+            /// sql(postgres): `UPDATE table_name AS t SET name = v.name FROM (VALUES (?, ?), (?, ?)) AS v(id, name) WHERE t.id = v.id`
+            pub async fn update_batch_by_id(
+                executor: &dyn $crate::executor::Executor,
+                tables: &[$table],
+                batch_size: u64,
+            ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
+                let mut result = $crate::rbdc::db::ExecResult {
+                    rows_affected: 0,
+                    last_insert_id: rbs::Value::Null,
+                };
+                if tables.is_empty() {
+                    return Ok(result);
+                }
+                let table_name = $crate::crud!(@table_name $table, $table_name);
+                let options = $crate::crud_traits::CrudOptions::new()$(.$opt($val))*;
+                let driver_type = executor.driver_type()?.to_string();
+                let quoted_table_name =
+                    $crate::crud_traits::quote_identifier(&driver_type, &table_name);
+                let ranges =
+                    $crate::plugin::Page::<()>::make_ranges(tables.len() as u64, batch_size);
+                for (offset, limit) in ranges {
+                    let tables = &tables[offset as usize..limit as usize];
+                    let mut rows = rbs::value!(tables);
+                    if let rbs::Value::Array(rows) = &mut rows {
+                        for row in rows {
+                            $crate::plugin::fill_update(
+                                executor.rb_ref(),
+                                &table_name,
+                                row,
+                                &mut rbs::Value::Null,
+                            );
+                        }
+                    }
+                    match options.update_batch_sql(&driver_type, &quoted_table_name, &rows)? {
+                        Some((sql, args)) => {
+                            result.rows_affected += executor.exec(&sql, args).await?.rows_affected;
+                        }
+                        None => {
+                            for table in tables {
+                                result.rows_affected +=
+                                    <$table>::update_by_id(executor, table).await?.rows_affected;
+                            }
+                        }
+                    }
+                }
+                Ok(result)
+            }
+
+            /// delete a record by the primary key, with the `logic_delete` option it update the logic delete column to 1
+            ///
+            /// sql: `DELETE FROM table_name WHERE id = ?`
+            pub async fn delete_by_id<V: serde::Serialize>(
+                executor: &dyn $crate::executor::Executor,
+                id: V,
+            ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
+                let query = $crate::crud_traits::CrudOptions::new()$(.$opt($val))*
+                    .id_query(&[rbs::value!(id)])?;
+                <$table>::delete_by_wrapper(executor, &query).await
+            }
+
+            /// delete records by the primary keys, with the `logic_delete` option it update the logic delete column to 1
+            ///
+            /// sql: `DELETE FROM table_name WHERE id in (?, ?)`
+            pub async fn delete_by_ids<V: serde::Serialize>(
+                executor: &dyn $crate::executor::Executor,
+                ids: &[V],
+            ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
+                if ids.is_empty() {
+                    return Ok($crate::rbdc::db::ExecResult::default());
+                }
+                let ids: Vec<rbs::Value> = ids.iter().map(|v| rbs::value!(v)).collect();
+                let query = $crate::crud_traits::CrudOptions::new()$(.$opt($val))*.id_query(&ids)?;
+                <$table>::delete_by_wrapper(executor, &query).await
+            }
+        }
+        // wrapper
+        impl $table {
+            /// select records by the `Query` wrapper.
+            ///
+            /// sql: `SELECT * FROM table_name WHERE name = ? and (age > ? or status is null) ORDER BY id desc LIMIT 10`
+            pub async fn select_by_wrapper(
+                executor: &dyn $crate::executor::Executor,
+                query: &$crate::query::Query,
+            ) -> std::result::Result<Vec<$table>, $crate::rbdc::Error> {
+                let table_name = $crate::crud!(@table_name $table, $table_name);
+                let query = $crate::crud_traits::CrudOptions::new()$(.$opt($val))*
+                    .logic_delete_query(query.clone());
+                let driver_type = executor.driver_type()?;
+                let (sql, args) = query.select_sql(
+                    &$crate::crud_traits::quote_identifier(driver_type, &table_name),
+                    driver_type,
+                )?;
+                let records = executor.query(&sql, args).await?;
+                rbs::from_value(records)
+            }
+
+            /// count records by the `Query` wrapper, the order by and limit of `Query` are ignored.
+            ///
+            /// sql: `SELECT count(1) as count FROM table_name WHERE name = ?`
+            pub async fn count_by_wrapper(
+                executor: &dyn $crate::executor::Executor,
+                query: &$crate::query::Query,
+            ) -> std::result::Result<u64, $crate::rbdc::Error> {
+                let table_name = $crate::crud!(@table_name $table, $table_name);
+                let query = $crate::crud_traits::CrudOptions::new()$(.$opt($val))*
+                    .logic_delete_query(query.clone());
+                let driver_type = executor.driver_type()?;
+                let (sql, args) = query.count_sql(
+                    &$crate::crud_traits::quote_identifier(driver_type, &table_name),
+                    driver_type,
+                )?;
+                let count = executor.query(&sql, args).await?;
+                $crate::decode::<u64>(count)
+            }
+
+            /// update records by the `Query` wrapper, the order by and limit of `Query` are ignored.
+            /// note: skips null fields, skips the primary key(default 'id') always
+            /// note: with the `version` option, return `version_conflict_error` if the version is changed by others
+            ///
+            /// sql: `UPDATE table_name SET col1 = ?, col2 = ? WHERE name = ? and age > ?`
+            pub async fn update_by_wrapper(
+                executor: &dyn $crate::executor::Executor,
+                table: &$table,
+                query: &$crate::query::Query,
+            ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
+                let table_name = $crate::crud!(@table_name $table, $table_name);
+                let options = $crate::crud_traits::CrudOptions::new()$(.$opt($val))*;
+                let query = options.logic_delete_query(query.clone());
+                let mut table_value = rbs::value!(table);
+                $crate::plugin::fill_update(
+                    executor.rb_ref(),
+                    &table_name,
+                    &mut table_value,
+                    &mut rbs::Value::Null,
+                );
+                let (query, version) = options.version_lock_query(&mut table_value, query)?;
+                let driver_type = executor.driver_type()?;
+                options.remove_primary_key(&mut table_value);
+                let (sql, args) = query.update_sql(
+                    &$crate::crud_traits::quote_identifier(driver_type, &table_name),
+                    driver_type,
+                    &table_value,
+                    &[],
+                )?;
+                let result = executor.exec(&sql, args).await?;
+                if let Some(version) = version {
+                    if result.rows_affected == 0 {
+                        return Err($crate::version_conflict_error(&table_name, &version));
+                    }
+                }
+                Ok(result)
+            }
+
+            /// delete records by the `Query` wrapper, the order by and limit of `Query` are ignored.
+            /// with the `logic_delete` option, it update the logic delete column to 1
+            ///
+            /// sql: `DELETE FROM table_name WHERE name = ?`
+            /// or `UPDATE table_name SET delete_flag = 1 WHERE name = ?`
+            pub async fn delete_by_wrapper(
+                executor: &dyn $crate::executor::Executor,
+                query: &$crate::query::Query,
+            ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
+                let table_name = $crate::crud!(@table_name $table, $table_name);
+                let options = $crate::crud_traits::CrudOptions::new()$(.$opt($val))*;
+                let driver_type = executor.driver_type()?;
+                let table_name = $crate::crud_traits::quote_identifier(driver_type, &table_name);
+                let (sql, args) = match &options.logic_delete {
+                    None => query.delete_sql(&table_name, driver_type)?,
+                    Some(column) => {
+                        let (where_sql, args) = query.where_sql(driver_type)?;
+                        (
+                            format!(
+                                "update {} set {} = {}{}",
+                                table_name,
+                                $crate::crud_traits::quote_identifier(driver_type, column),
+                                $crate::crud_traits::CrudOptions::LOGIC_DELETED,
+                                where_sql
+                            ),
+                            args,
+                        )
+                    }
+                };
+                executor.exec(&sql, args).await
             }
         }
     };
@@ -383,7 +1080,7 @@ macro_rules! crud {
 ///             `count(1) from table`
 ///         </if>
 ///         <if test="do_count == false">
-///             `* from table limit ${page_no},${page_size}`
+///             `* from table limit ${safe page_no},${safe page_size}`
 ///         </if>
 ///   </select>
 /// ```
@@ -393,14 +1090,14 @@ macro_rules! crud {
 /// //rbatis::htmlsql_select_page!(select_page_data(name: &str) -> MockTable => "example.html");
 /// rbatis::htmlsql_select_page!(select_page_data(name: &str) -> MockTable => r#"
 /// <select id="select_page_data">
-///  `select * from table  where id > 1  limit ${page_no},${page_size} `
+///  `select * from table  where id > 1  limit ${safe page_no},${safe page_size} `
 /// </select>"#);
 ///
 /// rbatis::pysql_select_page!(pysql_select_page(name:&str) -> MockTable =>
 ///     r#"`select * from activity where delete_flag = 0`
 ///         if name != '':
 ///            ` and name=#{name}`
-///       ` limit ${page_no},${page_size}`
+///       ` limit ${safe page_no},${safe page_size}`
 /// "#);
 /// ```
 #[macro_export]
@@ -465,7 +1162,7 @@ macro_rules! htmlsql_select_page {
 ///     r#"`select * from activity where delete_flag = 0`
 ///         if name != '':
 ///            ` and name=#{name}`
-///       ` limit ${page_no},${page_size}`
+///       ` limit ${safe page_no},${safe page_size}`
 /// "#);
 /// ```
 #[macro_export]
@@ -525,7 +1222,7 @@ macro_rules! raw_sql {
 /// ```rust
 /// use rbatis::executor::Executor;
 /// rbatis::pysql!(test_same_id(rb: &dyn Executor, id: &u64)  -> Result<rbs::Value, rbatis::Error> =>
-/// "select * from table where ${id} = 1
+/// "select * from table where ${safe id} = 1
 ///  if id != 0:
 ///    `id = #{id}`"
 /// );
@@ -555,7 +1252,7 @@ macro_rules! pysql {
 /// rbatis::htmlsql!(test_select(rb: &dyn Executor, id: &u64)  -> Result<rbs::Value, rbatis::Error> => r#"
 ///             <mapper>
 ///             <select id="test_same_id">
-///               `select ${id} from my_table`
+///               `select ${safe id} from my_table`
 ///             </select>
 ///             </mapper>"#);
 /// ```
@@ -118,3 +118,1396 @@ impl FilterByColumns for Value {
         }
     }
 }
+
+/// the column names of a table struct, impl by `#[derive(rbatis::Columns)]`
+pub trait Columns {
+    /// the struct of column names, one `&'static str` field per column
+    type Columns: Copy;
+    /// the column names, for example `Activity::COL.name`
+    const COL: Self::Columns;
+    /// all the column names(include the flatten columns)
+    fn column_names() -> Vec<&'static str>;
+}
+
+/// the options of `crud!`, for example `crud!(Activity{}, "activity", logic_delete = "delete_flag")`.
+/// each `key = value` of `crud!` call the method `key(value)`
+#[derive(Clone, Debug, Default)]
+pub struct CrudOptions {
+    /// the logic delete column, `delete_by_map` set it `LOGIC_DELETED`,
+    /// `select_by_map`/`update_by_map` only use the rows of `LOGIC_UNDELETED`
+    pub logic_delete: Option<String>,
+    /// the optimistic lock column, `update_by_map` add `version = ?` to the condition
+    /// and set it to `version + 1`, return `version_conflict_error` if no row updated
+    pub version: Option<String>,
+    /// the primary key columns, empty = `id`.
+    /// the update methods never set them, the `*_by_id` methods find rows by them
+    pub primary_key: Vec<String>,
+}
+
+/// the column names of `crud!` option, for example `"id"` or `["org_id", "user_id"]`
+pub trait IntoColumns {
+    fn into_columns(self) -> Vec<String>;
+}
+
+impl IntoColumns for &str {
+    fn into_columns(self) -> Vec<String> {
+        vec![self.to_string()]
+    }
+}
+
+impl IntoColumns for String {
+    fn into_columns(self) -> Vec<String> {
+        vec![self]
+    }
+}
+
+impl IntoColumns for &[&str] {
+    fn into_columns(self) -> Vec<String> {
+        self.iter().map(|v| v.to_string()).collect()
+    }
+}
+
+impl<const N: usize> IntoColumns for [&str; N] {
+    fn into_columns(self) -> Vec<String> {
+        self.iter().map(|v| v.to_string()).collect()
+    }
+}
+
+impl IntoColumns for Vec<&str> {
+    fn into_columns(self) -> Vec<String> {
+        self.iter().map(|v| v.to_string()).collect()
+    }
+}
+
+impl CrudOptions {
+    /// the value of logic delete column on deleted rows
+    pub const LOGIC_DELETED: i32 = 1;
+    /// the value of logic delete column on undeleted rows
+    pub const LOGIC_UNDELETED: i32 = 0;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn logic_delete(mut self, column: &str) -> Self {
+        self.logic_delete = Some(column.to_string());
+        self
+    }
+
+    pub fn version(mut self, column: &str) -> Self {
+        self.version = Some(column.to_string());
+        self
+    }
+
+    /// the primary key, for example `primary_key("id")` or `primary_key(["org_id", "user_id"])`
+    pub fn primary_key<K: IntoColumns>(mut self, columns: K) -> Self {
+        self.primary_key = columns.into_columns();
+        self
+    }
+
+    /// the primary key columns, default `["id"]`
+    pub fn primary_keys(&self) -> Vec<&str> {
+        if self.primary_key.is_empty() {
+            vec!["id"]
+        } else {
+            self.primary_key.iter().map(|v| v.as_str()).collect()
+        }
+    }
+
+    /// remove the primary key columns of the table map, they are never updated
+    pub fn remove_primary_key(&self, table: &mut Value) {
+        let keys = self.primary_keys();
+        if let Value::Map(m) = table {
+            //keep the order of columns
+            let mut new_map = rbs::value::map::ValueMap::with_capacity(m.len());
+            for (k, v) in &*m {
+                if !k.as_str().map(|k| keys.contains(&k)).unwrap_or(false) {
+                    new_map.insert(k.clone(), v.clone());
+                }
+            }
+            *m = new_map;
+        }
+    }
+
+    /// the id of the table map, the value of primary key, or the map of the composite primary key
+    pub fn table_id(&self, table: &Value) -> Result<Value, crate::Error> {
+        let keys = self.primary_keys();
+        let mut id = rbs::value::map::ValueMap::new();
+        for key in &keys {
+            let v = &table[*key];
+            if *v == Value::Null {
+                return Err(crate::Error::from(format!(
+                    "[rb] the primary key '{}' of table is null",
+                    key
+                )));
+            }
+            id.insert(Value::String(key.to_string()), v.clone());
+        }
+        if keys.len() == 1 {
+            return Ok(id.remove(&Value::String(keys[0].to_string())));
+        }
+        Ok(Value::Map(id))
+    }
+
+    /// the values of a composite id, the id is a map(`{"org_id": 1, "user_id": 2}`)
+    /// or an array/tuple in the order of primary key(`(1, 2)`)
+    fn id_values(&self, keys: &[&str], id: &Value) -> Result<Vec<Value>, crate::Error> {
+        let values: Vec<Value> = match id {
+            Value::Map(m) => keys.iter().map(|k| m[*k].clone()).collect(),
+            Value::Array(arr) if arr.len() == keys.len() => arr.clone(),
+            _ => vec![],
+        };
+        if values.len() != keys.len() || values.contains(&Value::Null) {
+            return Err(crate::Error::from(format!(
+                "[rb] the id {} not match the primary key {:?}",
+                id, keys
+            )));
+        }
+        Ok(values)
+    }
+
+    /// the `Query` of the ids, `id = ?`, `id in (?, ?)`, or `(org_id = ? and user_id = ?) or (...)` of composite primary key
+    pub fn id_query(&self, ids: &[Value]) -> Result<crate::query::Query, crate::Error> {
+        let keys = self.primary_keys();
+        for key in &keys {
+            check_identifier(key)?;
+        }
+        let query = crate::query::Query::new();
+        if keys.len() == 1 {
+            let key = keys[0];
+            if ids.contains(&Value::Null) {
+                return Err(crate::Error::from(format!(
+                    "[rb] the id of primary key '{}' is null",
+                    key
+                )));
+            }
+            return Ok(match ids {
+                [id] => query.eq(key, id),
+                _ => query.is_in(key, ids.to_vec()),
+            });
+        }
+        let mut groups = vec![];
+        for id in ids {
+            groups.push(self.id_values(&keys, id)?);
+        }
+        let eq_all = |mut q: crate::query::Query, values: Vec<Value>| {
+            for (key, v) in keys.iter().zip(values) {
+                q = q.eq(key, v);
+            }
+            q
+        };
+        if groups.len() == 1 {
+            return Ok(eq_all(query, groups.remove(0)));
+        }
+        if groups.is_empty() {
+            return Ok(query.is_in(keys[0], Vec::<Value>::new()));
+        }
+        Ok(query.and(|mut q| {
+            for values in groups {
+                q = q.or(|g| eq_all(g, values));
+            }
+            q
+        }))
+    }
+
+    /// add `version = ?` to the condition map and set the version of table to `version + 1`.
+    /// return the old version, None = no lock(no version option, or the version of table is null)
+    pub fn version_lock(
+        &self,
+        table: &mut Value,
+        condition: &mut Value,
+    ) -> Result<Option<Value>, crate::Error> {
+        let column = match &self.version {
+            None => return Ok(None),
+            Some(column) => column,
+        };
+        let version = match table {
+            Value::Map(m) => m[column.as_str()].clone(),
+            _ => Value::Null,
+        };
+        let next = match &version {
+            Value::Null => return Ok(None),
+            Value::I32(v) => Value::I32(v + 1),
+            Value::I64(v) => Value::I64(v + 1),
+            Value::U32(v) => Value::U32(v + 1),
+            Value::U64(v) => Value::U64(v + 1),
+            _ => {
+                return Err(crate::Error::from(format!(
+                    "[rb] version column '{}' must be integer, but it is {}",
+                    column, version
+                )))
+            }
+        };
+        if let Value::Map(m) = table {
+            m.insert(Value::String(column.clone()), next);
+        }
+        if *condition == Value::Null {
+            *condition = Value::Map(rbs::value::map::ValueMap::new());
+        }
+        if let Value::Map(m) = condition {
+            m.insert(Value::String(column.clone()), version.clone());
+        }
+        Ok(Some(version))
+    }
+
+    /// add `logic_delete = LOGIC_UNDELETED` to the condition map, if the column not in it.
+    pub fn logic_delete_condition(&self, condition: &mut Value) {
+        let column = match &self.logic_delete {
+            None => return,
+            Some(column) => column,
+        };
+        if *condition == Value::Null {
+            *condition = Value::Map(rbs::value::map::ValueMap::new());
+        }
+        if let Value::Map(m) = condition {
+            let exists = m.into_iter().any(|(k, _)| {
+                k.as_str()
+                    .map(|k| k == column || k.starts_with(&format!("{} ", column)))
+                    .unwrap_or(false)
+            });
+            if !exists {
+                m.insert(
+                    Value::String(column.clone()),
+                    Value::I32(Self::LOGIC_UNDELETED),
+                );
+            }
+        }
+    }
+
+    /// add `logic_delete = LOGIC_UNDELETED` to the `Query`, if the column not in it.
+    pub fn logic_delete_query(&self, query: crate::query::Query) -> crate::query::Query {
+        match &self.logic_delete {
+            Some(column) if !query.has_column(column) => {
+                query.group().eq(column, Self::LOGIC_UNDELETED)
+            }
+            _ => query,
+        }
+    }
+
+    /// the same as `version_lock()`, but add `version = ?` to the `Query`
+    pub fn version_lock_query(
+        &self,
+        table: &mut Value,
+        query: crate::query::Query,
+    ) -> Result<(crate::query::Query, Option<Value>), crate::Error> {
+        let mut condition = Value::Null;
+        let version = self.version_lock(table, &mut condition)?;
+        match (&version, &self.version) {
+            (Some(v), Some(column)) => Ok((query.group().eq(column, v), version)),
+            _ => Ok((query, version)),
+        }
+    }
+
+    /// the batch update sql of the rows(the array of table maps), find the rows by the primary key.
+    /// return None if the driver(or the `version` option) need update rows one by one.
+    /// * mysql/sqlite: `update t set name = case when id = ? then ? when id = ? then ? else name end where id in (?, ?)`
+    /// * postgres: `update t as t set name = v.name from (values (?, ?), (?, ?)) as v(id, name) where t.id = v.id`
+    ///
+    /// the null column of a row is not updated. the columns(the keys of rows) must be identifiers, they are quoted by the driver.
+    pub fn update_batch_sql(
+        &self,
+        driver_type: &str,
+        table_name: &str,
+        rows: &Value,
+    ) -> Result<Option<(String, Vec<Value>)>, crate::Error> {
+        let pg = matches!(driver_type, "postgres" | "pg");
+        if self.version.is_some() || !(pg || matches!(driver_type, "mysql" | "sqlite")) {
+            return Ok(None);
+        }
+        let keys = self.primary_keys();
+        let mut ids = vec![];
+        for (_, row) in rows {
+            ids.push(self.table_id(row)?);
+        }
+        let columns: Vec<String> = rows
+            .column_sets()
+            .into_iter()
+            .filter_map(|(_, v)| v.as_str().map(|v| v.to_string()))
+            .filter(|v| !keys.contains(&v.as_str()))
+            .collect();
+        if columns.is_empty() {
+            return Err(crate::Error::from(format!(
+                "[rb] update table '{}' has no column to set",
+                table_name
+            )));
+        }
+        let quote = |column: &str| -> Result<String, crate::Error> {
+            check_identifier(column)?;
+            Ok(quote_identifier(driver_type, column))
+        };
+        let mut args = vec![];
+        if pg {
+            let mut sets = vec![];
+            for column in &columns {
+                let has_null = rows
+                    .into_iter()
+                    .any(|(_, row)| row[column.as_str()] == Value::Null);
+                let c = quote(column)?;
+                if has_null {
+                    sets.push(format!("{} = coalesce(v.{}, t.{})", c, c, c));
+                } else {
+                    sets.push(format!("{} = v.{}", c, c));
+                }
+            }
+            let mut values = vec![];
+            let all_columns: Vec<&str> = keys
+                .iter()
+                .copied()
+                .chain(columns.iter().map(|v| v.as_str()))
+                .collect();
+            for (_, row) in rows {
+                for column in &all_columns {
+                    args.push(row[*column].clone());
+                }
+                values.push(format!("({})", vec!["?"; all_columns.len()].join(", ")));
+            }
+            let mut wheres = vec![];
+            for k in &keys {
+                let k = quote(k)?;
+                wheres.push(format!("t.{} = v.{}", k, k));
+            }
+            if let Some(column) = &self.logic_delete {
+                wheres.push(format!("t.{} = {}", quote(column)?, Self::LOGIC_UNDELETED));
+            }
+            let mut quoted_columns = vec![];
+            for column in &all_columns {
+                quoted_columns.push(quote(column)?);
+            }
+            let sql = format!(
+                "update {} as t set {} from (values {}) as v({}) where {}",
+                table_name,
+                sets.join(", "),
+                values.join(", "),
+                quoted_columns.join(", "),
+                wheres.join(" and ")
+            );
+            return Ok(Some((sql, args)));
+        }
+        let mut id_match = vec![];
+        for k in &keys {
+            id_match.push(format!("{} = ?", quote(k)?));
+        }
+        let id_match = id_match.join(" and ");
+        let mut sets = vec![];
+        for column in &columns {
+            let c = quote(column)?;
+            let mut set = format!("{} = case", c);
+            for ((_, row), id) in rows.into_iter().zip(&ids) {
+                let v = &row[column.as_str()];
+                if *v == Value::Null {
+                    continue;
+                }
+                set.push_str(&format!(" when {} then ?", id_match));
+                match id {
+                    Value::Map(m) => {
+                        for k in &keys {
+                            args.push(m[*k].clone());
+                        }
+                    }
+                    _ => args.push(id.clone()),
+                }
+                args.push(v.clone());
+            }
+            set.push_str(&format!(" else {} end", c));
+            sets.push(set);
+        }
+        let query = self.logic_delete_query(self.id_query(&ids)?);
+        let (where_sql, where_args) = query.where_sql(driver_type)?;
+        args.extend(where_args);
+        let sql = format!("update {} set {}{}", table_name, sets.join(", "), where_sql);
+        Ok(Some((sql, args)))
+    }
+
+    /// is the id of the insert rows generated by the database? (the null id of a single primary key)
+    ///
+    /// the ids are read by the field names, so `rows` must not be quoted by `quote_columns`.
+    pub fn has_generated_id(&self, rows: &Value) -> bool {
+        let keys = self.primary_keys();
+        keys.len() == 1 && rows.into_iter().any(|(_, row)| row[keys[0]] == Value::Null)
+    }
+
+    /// the id of the inserted row, to select it again if the driver not support `insert_returning_sql`.
+    ///
+    /// the null id of a single primary key is the generated id `last_insert_id`,
+    /// so the rows of generated ids must be inserted one by one.
+    pub fn inserted_id(&self, row: &Value, last_insert_id: &Value) -> Result<Value, crate::Error> {
+        let keys = self.primary_keys();
+        if keys.len() == 1 && row[keys[0]] == Value::Null {
+            return match last_insert_id.as_u64() {
+                Some(id) => Ok(Value::U64(id)),
+                _ => Err(crate::Error::from(format!(
+                    "[rb] the driver not return the generated id of primary key '{}'",
+                    keys[0]
+                ))),
+            };
+        }
+        self.table_id(row)
+    }
+
+    /// sort the selected rows in the order of `ids`
+    pub fn sort_by_ids(&self, rows: Value, ids: &[Value]) -> Value {
+        let keys: Vec<String> = ids.iter().map(|v| v.to_string()).collect();
+        let mut rows: Vec<(usize, Value)> = rows
+            .into_iter()
+            .map(|(_, row)| {
+                let index = self
+                    .table_id(&row)
+                    .ok()
+                    .and_then(|id| keys.iter().position(|k| *k == id.to_string()))
+                    .unwrap_or(keys.len());
+                (index, row)
+            })
+            .collect();
+        rows.sort_by_key(|(index, _)| *index);
+        Value::Array(rows.into_iter().map(|(_, row)| row).collect())
+    }
+
+    /// set the null logic delete column of the insert rows to `LOGIC_UNDELETED`
+    pub fn logic_delete_rows(&self, rows: &mut Value) {
+        let column = match &self.logic_delete {
+            None => return,
+            Some(column) => column,
+        };
+        if let Value::Array(rows) = rows {
+            for row in rows {
+                if let Value::Map(m) = row {
+                    if m[column.as_str()] == Value::Null {
+                        m.insert(
+                            Value::String(column.clone()),
+                            Value::I32(Self::LOGIC_UNDELETED),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// the deserializer to take the field names of `#[derive(Deserialize)]` struct
+struct FieldsDeserializer<'a> {
+    fields: &'a mut Option<&'static [&'static str]>,
+}
+
+impl<'de> serde::Deserializer<'de> for FieldsDeserializer<'_> {
+    type Error = rbs::Error;
+
+    fn deserialize_any<V: serde::de::Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+        Err(rbs::Error::from("not struct"))
+    }
+
+    fn deserialize_struct<V: serde::de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.fields = Some(fields);
+        Err(rbs::Error::from("fields"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+/// the field names of the struct(`#[serde(rename)]` applied),
+/// None = unknown, for example the struct have `#[serde(flatten)]` field
+pub fn struct_fields<T: serde::de::DeserializeOwned>() -> Option<&'static [&'static str]> {
+    let mut fields = None;
+    let _ = T::deserialize(FieldsDeserializer {
+        fields: &mut fields,
+    });
+    fields
+}
+
+/// check the column is a field of the struct, or a plain identifier if the fields are unknown
+pub fn check_column(column: &str, fields: Option<&[&str]>) -> Result<(), crate::Error> {
+    let ok = match fields {
+        Some(fields) => fields.contains(&column),
+        None => is_identifier(column),
+    };
+    if ok {
+        Ok(())
+    } else {
+        Err(crate::Error::from(format!(
+            "[rb] invalid column '{}', it is not a field of the table",
+            column
+        )))
+    }
+}
+
+/// is it a plain identifier, `[A-Za-z_][A-Za-z0-9_]*`
+pub fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// return error if the name is not `is_identifier()`
+fn check_identifier(name: &str) -> Result<(), crate::Error> {
+    if is_identifier(name) {
+        Ok(())
+    } else {
+        Err(crate::Error::from(format!(
+            "[rb] invalid column '{}', it must be an identifier",
+            name
+        )))
+    }
+}
+
+/// the select columns of the `select_by_map` "column" key, `"name"`, `"id, name"` or `["id", "name"]`.
+/// the columns are checked by `check_column()` and quoted by the driver, empty = `*`
+pub fn select_columns(
+    driver_type: &str,
+    column: &Value,
+    fields: Option<&[&str]>,
+) -> Result<String, crate::Error> {
+    let columns: Vec<&str> = match column {
+        Value::String(s) => s.split(',').map(|v| v.trim()).collect(),
+        Value::Array(arr) => arr.iter().filter_map(|v| v.as_str()).collect(),
+        _ => vec![],
+    };
+    let mut quoted = vec![];
+    for column in columns {
+        if column == "*" {
+            quoted.push(column.to_string());
+            continue;
+        }
+        check_column(column, fields)?;
+        quoted.push(quote_identifier(driver_type, column));
+    }
+    if quoted.is_empty() {
+        return Ok("*".to_string());
+    }
+    Ok(quoted.join(", "))
+}
+
+/// the sql keywords(the reserved words of mysql/postgres/mssql and the keywords of sqlite) which may be used as a column or table name,
+/// sorted for `binary_search`
+pub const RESERVED_WORDS: [&str; 416] = [
+    "abort",
+    "accessible",
+    "action",
+    "add",
+    "after",
+    "all",
+    "alter",
+    "always",
+    "analyse",
+    "analyze",
+    "and",
+    "any",
+    "array",
+    "as",
+    "asc",
+    "asensitive",
+    "asymmetric",
+    "attach",
+    "authorization",
+    "autoincrement",
+    "backup",
+    "before",
+    "begin",
+    "between",
+    "bigint",
+    "binary",
+    "blob",
+    "both",
+    "break",
+    "browse",
+    "bulk",
+    "by",
+    "call",
+    "cascade",
+    "case",
+    "cast",
+    "change",
+    "char",
+    "character",
+    "check",
+    "checkpoint",
+    "close",
+    "clustered",
+    "coalesce",
+    "collate",
+    "collation",
+    "column",
+    "commit",
+    "compute",
+    "concurrently",
+    "condition",
+    "conflict",
+    "constraint",
+    "contains",
+    "containstable",
+    "continue",
+    "convert",
+    "create",
+    "cross",
+    "cube",
+    "cume_dist",
+    "current",
+    "current_catalog",
+    "current_date",
+    "current_role",
+    "current_schema",
+    "current_time",
+    "current_timestamp",
+    "current_user",
+    "cursor",
+    "database",
+    "databases",
+    "day_hour",
+    "day_microsecond",
+    "day_minute",
+    "day_second",
+    "dbcc",
+    "deallocate",
+    "dec",
+    "decimal",
+    "declare",
+    "default",
+    "deferrable",
+    "deferred",
+    "delayed",
+    "delete",
+    "dense_rank",
+    "deny",
+    "desc",
+    "describe",
+    "detach",
+    "deterministic",
+    "disk",
+    "distinct",
+    "distinctrow",
+    "distributed",
+    "div",
+    "do",
+    "double",
+    "drop",
+    "dual",
+    "dump",
+    "each",
+    "else",
+    "elseif",
+    "empty",
+    "enclosed",
+    "end",
+    "errlvl",
+    "escape",
+    "escaped",
+    "except",
+    "exclude",
+    "exclusive",
+    "exec",
+    "execute",
+    "exists",
+    "exit",
+    "explain",
+    "external",
+    "fail",
+    "false",
+    "fetch",
+    "file",
+    "fillfactor",
+    "filter",
+    "first",
+    "first_value",
+    "float",
+    "float4",
+    "float8",
+    "following",
+    "for",
+    "force",
+    "foreign",
+    "freetext",
+    "freetexttable",
+    "freeze",
+    "from",
+    "full",
+    "fulltext",
+    "function",
+    "generated",
+    "get",
+    "glob",
+    "goto",
+    "grant",
+    "group",
+    "grouping",
+    "groups",
+    "having",
+    "high_priority",
+    "holdlock",
+    "hour_microsecond",
+    "hour_minute",
+    "hour_second",
+    "identity",
+    "identity_insert",
+    "identitycol",
+    "if",
+    "ignore",
+    "ilike",
+    "immediate",
+    "in",
+    "index",
+    "indexed",
+    "infile",
+    "initially",
+    "inner",
+    "inout",
+    "insensitive",
+    "insert",
+    "instead",
+    "int",
+    "int1",
+    "int2",
+    "int3",
+    "int4",
+    "int8",
+    "integer",
+    "intersect",
+    "interval",
+    "into",
+    "io_after_gtids",
+    "io_before_gtids",
+    "is",
+    "isnull",
+    "iterate",
+    "join",
+    "json_table",
+    "key",
+    "keys",
+    "kill",
+    "lag",
+    "last",
+    "last_value",
+    "lateral",
+    "lead",
+    "leading",
+    "leave",
+    "left",
+    "like",
+    "limit",
+    "linear",
+    "lineno",
+    "lines",
+    "load",
+    "localtime",
+    "localtimestamp",
+    "lock",
+    "long",
+    "longblob",
+    "longtext",
+    "loop",
+    "low_priority",
+    "master_bind",
+    "master_ssl_verify_server_cert",
+    "match",
+    "materialized",
+    "maxvalue",
+    "mediumblob",
+    "mediumint",
+    "mediumtext",
+    "merge",
+    "middleint",
+    "minute_microsecond",
+    "minute_second",
+    "mod",
+    "modifies",
+    "national",
+    "natural",
+    "no",
+    "no_write_to_binlog",
+    "nocheck",
+    "nonclustered",
+    "not",
+    "nothing",
+    "notnull",
+    "nth_value",
+    "ntile",
+    "null",
+    "nullif",
+    "nulls",
+    "numeric",
+    "of",
+    "off",
+    "offset",
+    "offsets",
+    "on",
+    "only",
+    "open",
+    "opendatasource",
+    "openquery",
+    "openrowset",
+    "openxml",
+    "optimize",
+    "optimizer_costs",
+    "option",
+    "optionally",
+    "or",
+    "order",
+    "others",
+    "out",
+    "outer",
+    "outfile",
+    "over",
+    "overlaps",
+    "partition",
+    "percent",
+    "percent_rank",
+    "pivot",
+    "placing",
+    "plan",
+    "pragma",
+    "preceding",
+    "precision",
+    "primary",
+    "print",
+    "proc",
+    "procedure",
+    "public",
+    "purge",
+    "query",
+    "raise",
+    "raiserror",
+    "range",
+    "rank",
+    "read",
+    "read_write",
+    "reads",
+    "readtext",
+    "real",
+    "reconfigure",
+    "recursive",
+    "references",
+    "regexp",
+    "reindex",
+    "release",
+    "rename",
+    "repeat",
+    "replace",
+    "replication",
+    "require",
+    "resignal",
+    "restore",
+    "restrict",
+    "return",
+    "returning",
+    "revert",
+    "revoke",
+    "right",
+    "rlike",
+    "rollback",
+    "row",
+    "row_number",
+    "rowcount",
+    "rowguidcol",
+    "rows",
+    "rule",
+    "save",
+    "savepoint",
+    "schema",
+    "schemas",
+    "second_microsecond",
+    "securityaudit",
+    "select",
+    "semantickeyphrase",
+    "semanticsimilaritydetailstable",
+    "semanticsimilaritytable",
+    "sensitive",
+    "separator",
+    "session_user",
+    "set",
+    "setuser",
+    "show",
+    "shutdown",
+    "signal",
+    "similar",
+    "smallint",
+    "some",
+    "spatial",
+    "specific",
+    "sql",
+    "sql_big_result",
+    "sql_calc_found_rows",
+    "sql_small_result",
+    "sqlexception",
+    "sqlstate",
+    "sqlwarning",
+    "ssl",
+    "starting",
+    "statistics",
+    "stored",
+    "straight_join",
+    "symmetric",
+    "system",
+    "system_user",
+    "table",
+    "tablesample",
+    "temp",
+    "temporary",
+    "terminated",
+    "textsize",
+    "then",
+    "ties",
+    "tinyblob",
+    "tinyint",
+    "tinytext",
+    "to",
+    "top",
+    "trailing",
+    "tran",
+    "transaction",
+    "trigger",
+    "true",
+    "truncate",
+    "try_convert",
+    "tsequal",
+    "unbounded",
+    "undo",
+    "union",
+    "unique",
+    "unlock",
+    "unpivot",
+    "unsigned",
+    "update",
+    "updatetext",
+    "usage",
+    "use",
+    "user",
+    "using",
+    "utc_date",
+    "utc_time",
+    "utc_timestamp",
+    "vacuum",
+    "values",
+    "varbinary",
+    "varchar",
+    "varcharacter",
+    "variadic",
+    "varying",
+    "verbose",
+    "view",
+    "virtual",
+    "waitfor",
+    "when",
+    "where",
+    "while",
+    "window",
+    "with",
+    "without",
+    "write",
+    "writetext",
+    "xor",
+    "year_month",
+    "zerofill",
+];
+
+/// quote the identifier by the driver if it is a reserved word,
+/// `order` -> `` `order` ``(mysql), `"order"`(postgres/sqlite), `[order]`(mssql).
+///
+/// the name of schema(`schema.table`) quote every part, the other names(for example `count(1) as count`) are not changed.
+pub fn quote_identifier(driver_type: &str, name: &str) -> String {
+    if name.contains('.') {
+        return name
+            .split('.')
+            .map(|v| quote_identifier(driver_type, v))
+            .collect::<Vec<String>>()
+            .join(".");
+    }
+    if !is_identifier(name)
+        || RESERVED_WORDS
+            .binary_search(&name.to_lowercase().as_str())
+            .is_err()
+    {
+        return name.to_string();
+    }
+    match driver_type {
+        "mysql" => format!("`{}`", name),
+        "mssql" => format!("[{}]", name),
+        _ => format!("\"{}\"", name),
+    }
+}
+
+/// quote the column keys of the table map, or of every table map in the array
+pub fn quote_columns(driver_type: &str, table: &mut Value) {
+    match table {
+        Value::Map(m) => {
+            if !m.into_iter().any(|(k, _)| {
+                k.as_str()
+                    .map(|k| quote_identifier(driver_type, k) != k)
+                    .unwrap_or(false)
+            }) {
+                return;
+            }
+            let mut new_map = rbs::value::map::ValueMap::with_capacity(m.len());
+            for (k, v) in &*m {
+                match k.as_str() {
+                    Some(k) => {
+                        new_map.insert(Value::String(quote_identifier(driver_type, k)), v.clone())
+                    }
+                    None => new_map.insert(k.clone(), v.clone()),
+                };
+            }
+            *m = new_map;
+        }
+        Value::Array(arr) => {
+            for v in arr {
+                quote_columns(driver_type, v);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// the operators of the condition key `column operator`, for example `value!{"age >": 18}`
+pub const CONDITION_OPERATORS: [&str; 11] = [
+    "=",
+    "!=",
+    "<>",
+    ">",
+    ">=",
+    "<",
+    "<=",
+    "like",
+    "not like",
+    "ilike",
+    "not ilike",
+];
+
+/// check and quote the keys of the condition map,
+/// the key must be a column(`name`, `t.name`) or a column followed by a `CONDITION_OPERATORS`(`age >=`).
+/// the array value(`column in (..)`) only support the column key.
+pub fn quote_condition(driver_type: &str, condition: &mut Value) -> Result<(), crate::Error> {
+    let m = match condition {
+        Value::Map(m) => m,
+        _ => return Ok(()),
+    };
+    let mut new_map = rbs::value::map::ValueMap::with_capacity(m.len());
+    for (k, v) in &*m {
+        let key = k.as_str().unwrap_or_default().trim();
+        let (column, operator) = match key.split_once(char::is_whitespace) {
+            Some((column, operator)) => (
+                column,
+                operator
+                    .split_whitespace()
+                    .collect::<Vec<&str>>()
+                    .join(" ")
+                    .to_lowercase(),
+            ),
+            None => (key, String::new()),
+        };
+        let valid_column = column.split('.').all(is_identifier);
+        let valid_operator = operator.is_empty()
+            || (!v.is_array() && CONDITION_OPERATORS.contains(&operator.as_str()));
+        if !valid_column || !valid_operator {
+            return Err(crate::Error::from(format!(
+                "[rb] invalid condition key {}, it must be a column or 'column operator'(operator: {})",
+                k,
+                CONDITION_OPERATORS.join(" ")
+            )));
+        }
+        let column = quote_identifier(driver_type, column);
+        let key = if operator.is_empty() {
+            column
+        } else {
+            format!("{} {} ", column, operator)
+        };
+        new_map.insert(Value::String(key), v.clone());
+    }
+    *m = new_map;
+    Ok(())
+}
+
+/// the reserved keys of `select_by_map` condition
+pub const SELECT_RESERVED_KEYS: [&str; 4] = ["group_by", "order_by", "limit", "offset"];
+
+/// return error if the condition map have one of the `keys`, for example `select_page_by_map` not support `limit`
+pub fn reject_keys(condition: &Value, method: &str, keys: &[&str]) -> Result<(), crate::Error> {
+    for key in keys {
+        if condition[*key] != Value::Null {
+            return Err(crate::Error::from(format!(
+                "[rb] {} not support the '{}' key",
+                method, key
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// insert the key into the condition map, the null condition is made an empty map
+pub fn set_key(condition: &mut Value, key: &str, v: Value) {
+    if *condition == Value::Null {
+        *condition = Value::Map(rbs::value::map::ValueMap::new());
+    }
+    if let Value::Map(m) = condition {
+        m.insert(Value::String(key.to_string()), v);
+    }
+}
+
+/// take the `group_by`/`order_by`/`limit`/`offset` keys out of the condition map,
+/// return the sql after where, for example ` group by status order by id desc limit 10`.
+/// the columns are checked by `check_column()`.
+/// * `"group_by": "status"` or `["status", "name"]`
+/// * `"order_by": "id desc"` or `["create_time desc", "id asc"]`
+/// * `"limit": 10`, `"offset": 20`
+pub fn select_tail_sql(
+    condition: &mut Value,
+    fields: Option<&[&str]>,
+    driver_type: &str,
+) -> Result<String, crate::Error> {
+    let m = match condition {
+        Value::Map(m) => m,
+        _ => return Ok(String::new()),
+    };
+    let mut values = vec![];
+    for key in SELECT_RESERVED_KEYS {
+        values.push(m.remove(&Value::String(key.to_string())));
+    }
+    let items = |v: Value| -> Vec<String> {
+        match v {
+            Value::String(s) => vec![s],
+            Value::Array(arr) => arr
+                .into_iter()
+                .filter_map(|v| v.as_str().map(|v| v.to_string()))
+                .collect(),
+            _ => vec![],
+        }
+    };
+    let number = |key: &str, v: Value| -> Result<Option<u64>, crate::Error> {
+        match v {
+            Value::Null => Ok(None),
+            Value::I32(v) if v >= 0 => Ok(Some(v as u64)),
+            Value::I64(v) if v >= 0 => Ok(Some(v as u64)),
+            Value::U32(v) => Ok(Some(v as u64)),
+            Value::U64(v) => Ok(Some(v)),
+            _ => Err(crate::Error::from(format!(
+                "[rb] '{}' must be a positive integer, but it is {}",
+                key, v
+            ))),
+        }
+    };
+    let offset = number("offset", values.pop().unwrap_or_default())?;
+    let limit = number("limit", values.pop().unwrap_or_default())?;
+    let order_by = items(values.pop().unwrap_or_default());
+    let group_by = items(values.pop().unwrap_or_default());
+
+    let mut sql = String::new();
+    if !group_by.is_empty() {
+        for column in &group_by {
+            check_column(column, fields)?;
+        }
+        let group_by: Vec<String> = group_by
+            .iter()
+            .map(|v| quote_identifier(driver_type, v))
+            .collect();
+        sql.push_str(" group by ");
+        sql.push_str(&group_by.join(", "));
+    }
+    let mut query = crate::query::Query::new();
+    for item in &order_by {
+        let mut words = item.split_whitespace();
+        let column = words.next().unwrap_or_default();
+        check_column(column, fields)?;
+        let desc = match words.next().map(|v| v.to_lowercase()).as_deref() {
+            None | Some("asc") => false,
+            Some("desc") => true,
+            Some(_) => {
+                return Err(crate::Error::from(format!(
+                    "[rb] invalid order_by '{}', it must be 'column asc' or 'column desc'",
+                    item
+                )))
+            }
+        };
+        if words.next().is_some() {
+            return Err(crate::Error::from(format!(
+                "[rb] invalid order_by '{}', it must be 'column asc' or 'column desc'",
+                item
+            )));
+        }
+        query = if desc {
+            query.order_by_desc(column)
+        } else {
+            query.order_by_asc(column)
+        };
+    }
+    if let Some(limit) = limit {
+        query = query.limit(limit);
+    }
+    if let Some(offset) = offset {
+        query = query.offset(offset);
+    }
+    sql.push_str(&query.order_limit_sql(driver_type)?);
+    Ok(sql)
+}
+
+/// the non-null columns of the rows, the values sql `(?, ?), (?, ?)` and the args
+fn insert_values(rows: &Value) -> (Vec<String>, String, Vec<Value>) {
+    let columns: Vec<String> = rows
+        .column_sets()
+        .into_iter()
+        .filter_map(|(_, v)| v.as_str().map(|v| v.to_string()))
+        .collect();
+    let mut args = vec![];
+    let mut values = vec![];
+    for (_, row) in rows {
+        for column in &columns {
+            args.push(row[column.as_str()].clone());
+        }
+        values.push(format!("({})", vec!["?"; columns.len()].join(", ")));
+    }
+    (columns, values.join(", "), args)
+}
+
+/// `insert into t (id, name) values (?, ?), (?, ?)`, `rows` is the array of table maps
+pub fn insert_sql(table_name: &str, rows: &Value) -> Result<(String, Vec<Value>), crate::Error> {
+    let (columns, values_sql, args) = insert_values(rows);
+    if columns.is_empty() {
+        return Err(crate::Error::from("[rb] insert can not insert empty rows"));
+    }
+    let sql = format!(
+        "insert into {} ({}) values {}",
+        table_name,
+        columns.join(", "),
+        values_sql
+    );
+    Ok((sql, args))
+}
+
+/// the insert sql return the inserted rows, `None` if the driver not support it(for example mysql).
+/// * sqlite/postgres: `insert into t (..) values (..) returning *`
+/// * mssql: `insert into t (..) output inserted.* values (..)`
+pub fn insert_returning_sql(
+    driver_type: &str,
+    table_name: &str,
+    rows: &Value,
+) -> Result<Option<(String, Vec<Value>)>, crate::Error> {
+    let (columns, values_sql, args) = insert_values(rows);
+    if columns.is_empty() {
+        return Err(crate::Error::from("[rb] insert can not insert empty rows"));
+    }
+    let column_sql = columns.join(", ");
+    let sql = match driver_type {
+        "sqlite" | "postgres" | "pg" => format!(
+            "insert into {} ({}) values {} returning *",
+            table_name, column_sql, values_sql
+        ),
+        "mssql" => format!(
+            "insert into {} ({}) output inserted.* values {}",
+            table_name, column_sql, values_sql
+        ),
+        _ => return Ok(None),
+    };
+    Ok(Some((sql, args)))
+}
+
+/// the upsert sql of the driver, `rows` is the array of table maps,
+/// the columns not in `conflict_columns` are updated on conflict.
+/// * sqlite/postgres: `insert into t (..) values (..) on conflict (id) do update set name = excluded.name`
+/// * mysql: `insert into t (..) values (..) on duplicate key update name = values(name)`
+/// * mssql: `merge into t as target using (values (..)) as source (..) on target.id = source.id when matched then update .. when not matched then insert ..;`
+pub fn upsert_sql(
+    driver_type: &str,
+    table_name: &str,
+    rows: &Value,
+    conflict_columns: &[&str],
+) -> Result<(String, Vec<Value>), crate::Error> {
+    let (columns, values_sql, args) = insert_values(rows);
+    if columns.is_empty() {
+        return Err(crate::Error::from("[rb] upsert can not upsert empty rows"));
+    }
+    if conflict_columns.is_empty() {
+        return Err(crate::Error::from("[rb] upsert need the conflict columns"));
+    }
+    for column in conflict_columns {
+        if !columns.iter().any(|v| v == column) {
+            return Err(crate::Error::from(format!(
+                "[rb] upsert conflict column '{}' is not a non-null column of the table",
+                column
+            )));
+        }
+    }
+    let updates: Vec<&String> = columns
+        .iter()
+        .filter(|v| !conflict_columns.contains(&v.as_str()))
+        .collect();
+    let column_sql = columns.join(", ");
+    let sql = match driver_type {
+        "sqlite" | "postgres" | "pg" => {
+            let action = if updates.is_empty() {
+                "do nothing".to_string()
+            } else {
+                let sets: Vec<String> = updates
+                    .iter()
+                    .map(|v| format!("{} = excluded.{}", v, v))
+                    .collect();
+                format!("do update set {}", sets.join(", "))
+            };
+            format!(
+                "insert into {} ({}) values {} on conflict ({}) {}",
+                table_name,
+                column_sql,
+                values_sql,
+                conflict_columns.join(", "),
+                action
+            )
+        }
+        "mysql" => {
+            let sets: Vec<String> = if updates.is_empty() {
+                //no column to update, keep the row
+                vec![format!("{} = {}", conflict_columns[0], conflict_columns[0])]
+            } else {
+                updates
+                    .iter()
+                    .map(|v| format!("{} = values({})", v, v))
+                    .collect()
+            };
+            format!(
+                "insert into {} ({}) values {} on duplicate key update {}",
+                table_name,
+                column_sql,
+                values_sql,
+                sets.join(", ")
+            )
+        }
+        "mssql" => {
+            let on: Vec<String> = conflict_columns
+                .iter()
+                .map(|v| format!("target.{} = source.{}", v, v))
+                .collect();
+            let mut sql = format!(
+                "merge into {} as target using (values {}) as source ({}) on {}",
+                table_name,
+                values_sql,
+                column_sql,
+                on.join(" and ")
+            );
+            if !updates.is_empty() {
+                let sets: Vec<String> = updates
+                    .iter()
+                    .map(|v| format!("target.{} = source.{}", v, v))
+                    .collect();
+                sql.push_str(&format!(
+                    " when matched then update set {}",
+                    sets.join(", ")
+                ));
+            }
+            let sources: Vec<String> = columns.iter().map(|v| format!("source.{}", v)).collect();
+            sql.push_str(&format!(
+                " when not matched then insert ({}) values ({});",
+                column_sql,
+                sources.join(", ")
+            ));
+            sql
+        }
+        _ => {
+            return Err(crate::Error::from(format!(
+                "[rb] upsert not support the driver '{}'",
+                driver_type
+            )))
+        }
+    };
+    Ok((sql, args))
+}
@@ -0,0 +1,78 @@
+use crate::rbatis::RBatis;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// the name of primary datasource, it is the pool of `RBatis::init()`
+pub const PRIMARY_DS: &str = "primary";
+
+/// the hint comment to force the sql run on the primary datasource,
+/// for example `/*rb:primary*/ select * from activity`
+pub const PRIMARY_HINT: &str = "/*rb:primary*/";
+
+/// the kind of the statement to route
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RouteKind {
+    /// `Executor::exec()`, for example insert/update/delete
+    Exec,
+    /// `Executor::query()`, for example select
+    Query,
+}
+
+/// choose the datasource of each exec/query run on `RBatis`.
+///
+/// the router is not called:
+/// * on `RBatis::use_ds()`, it always use the named datasource.
+/// * on `RBatis::acquire()` and transactions, they always use the primary datasource.
+/// * when only the primary datasource is registered.
+pub trait DataSourceRouter: Debug + Send + Sync {
+    /// return the datasource name registered by `RBatis::init_ds()`, None = primary
+    fn route(&self, rb: &RBatis, kind: RouteKind, sql: &str) -> Option<String>;
+}
+
+/// the default `DataSourceRouter`.
+///
+/// writes use the primary, reads(`select` without lock) round-robin
+/// on the datasources whose name start with `replica`.
+/// other datasources(for example "analytics") only be used by `RBatis::use_ds()`.
+#[derive(Debug, Default)]
+pub struct DefaultRouter {
+    index: AtomicUsize,
+}
+
+impl DefaultRouter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// is the datasource a replica?
+    pub fn is_replica(name: &str) -> bool {
+        name.starts_with("replica")
+    }
+
+    /// is the sql a read without lock and `PRIMARY_HINT`?
+    pub fn is_read(sql: &str) -> bool {
+        if sql.contains(PRIMARY_HINT) {
+            return false;
+        }
+        let sql = sql.trim_start().to_lowercase();
+        sql.starts_with("select")
+            && !sql.contains(" for update")
+            && !sql.contains(" for share")
+            && !sql.contains(" lock in share mode")
+    }
+}
+
+impl DataSourceRouter for DefaultRouter {
+    fn route(&self, rb: &RBatis, kind: RouteKind, sql: &str) -> Option<String> {
+        if kind != RouteKind::Query || !Self::is_read(sql) {
+            return None;
+        }
+        let mut replicas = rb.ds_names();
+        replicas.retain(|name| Self::is_replica(name));
+        if replicas.is_empty() {
+            return None;
+        }
+        let index = self.index.fetch_add(1, Ordering::Relaxed) % replicas.len();
+        Some(replicas.swap_remove(index))
+    }
+}
@@ -1,3 +1,32 @@
 pub type Result<T> = std::result::Result<T, Error>;
 
 pub type Error = rbdc::Error;
+
+/// the message prefix of statement timeout error
+pub const TIMEOUT_ERROR: &str = "[rb] statement timeout";
+
+/// create a statement timeout error
+pub fn timeout_error(timeout: std::time::Duration) -> Error {
+    Error::from(format!("{} after {:?}", TIMEOUT_ERROR, timeout))
+}
+
+/// is the error returned by statement timeout? see `RBatis::set_exec_timeout()`
+pub fn is_timeout(e: &Error) -> bool {
+    e.to_string().starts_with(TIMEOUT_ERROR)
+}
+
+/// the message prefix of optimistic lock conflict error
+pub const VERSION_CONFLICT_ERROR: &str = "[rb] version conflict";
+
+/// create an optimistic lock conflict error
+pub fn version_conflict_error(table: &str, version: &rbs::Value) -> Error {
+    Error::from(format!(
+        "{} on table '{}', the version {} is changed by others",
+        VERSION_CONFLICT_ERROR, table, version
+    ))
+}
+
+/// is the error returned by optimistic lock conflict? see the `version` option of `crud!`
+pub fn is_version_conflict(e: &Error) -> bool {
+    e.to_string().starts_with(VERSION_CONFLICT_ERROR)
+}
@@ -1,18 +1,21 @@
 use crate::decode::decode;
 use crate::intercept::{self, ResultType};
 use crate::rbatis::RBatis;
-use crate::Error;
+use crate::{timeout_error, Error, RouteKind, TxOptions};
 use dark_std::sync::SyncVec;
-use futures::Future;
+use futures::stream::BoxStream;
+use futures::{Future, StreamExt};
 use futures_core::future::BoxFuture;
-use rbdc::db::{Connection, ExecResult};
+use rbdc::db::{Connection, ExecResult, Row};
+use rbdc::pool::Pool;
 use rbdc::rt::tokio::sync::Mutex;
 use rbs::Value;
 use serde::de::DeserializeOwned;
 use std::any::Any;
 use std::fmt::{Debug, Formatter};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 /// the RBatis Executor. this trait impl with structs = RBatis,RBatisConnExecutor,RBatisTxExecutor,RBatisTxExecutorGuard
 pub trait Executor: RBatisRef + Send + Sync {
@@ -22,6 +25,21 @@ pub trait Executor: RBatisRef + Send + Sync {
         std::any::type_name::<Self>()
     }
 
+    /// is the executor run in a transaction? for example `RBatisTxExecutor`
+    fn is_tx(&self) -> bool {
+        false
+    }
+
+    /// the connection held by the executor, None = each call acquire a connection from the pool(for example `RBatis`)
+    fn connection(&self) -> Option<&Arc<Mutex<Box<dyn Connection>>>> {
+        None
+    }
+
+    /// the timeout of each exec/query
+    fn exec_timeout(&self) -> Option<Duration> {
+        self.rb_ref().get_exec_timeout()
+    }
+
     fn exec(&self, sql: &str, args: Vec<Value>) -> BoxFuture<'_, Result<ExecResult, Error>>;
     fn query(&self, sql: &str, args: Vec<Value>) -> BoxFuture<'_, Result<Value, Error>>;
 }
@@ -46,6 +64,8 @@ pub struct RBatisConnExecutor {
     pub rb: RBatis,
     pub conn: Arc<Mutex<Box<dyn Connection>>>,
     pub intercepts: Arc<SyncVec<Arc<dyn crate::intercept::Intercept>>>,
+    /// the timeout of exec/query, None = use `RBatis::get_exec_timeout()`
+    pub timeout: Option<Duration>,
 }
 
 impl RBatisConnExecutor {
@@ -55,12 +75,53 @@ impl RBatisConnExecutor {
             conn: Arc::new(Mutex::new(conn)),
             rb: rb.clone(),
             intercepts: rb.intercepts.clone(),
+            timeout: None,
         }
     }
 
+    /// return an executor on the same connection, each exec/query of it is bounded by `timeout`.
+    ///
+    /// if the timeout elapsed, the call return an error checked by `rbatis::is_timeout()`,
+    /// and the connection is closed and discarded. the executor is not usable after that,
+    /// all calls on it (and the executors sharing the connection) will fail, please acquire a new one.
+    /// for example:
+    /// ```rust
+    ///  use std::time::Duration;
+    ///  use rbatis::{Error, RBatis};
+    ///
+    ///  async fn test_timeout(rb: &RBatis) -> Result<(), Error> {
+    ///     let conn = rb.acquire().await?;
+    ///     conn.with_timeout(Duration::from_secs(5))
+    ///         .exec("update activity set name = 'a'", vec![])
+    ///         .await?;
+    ///     Ok(())
+    ///  }
+    /// ```
+    pub fn with_timeout(&self, timeout: Duration) -> Self {
+        let mut executor = self.clone();
+        executor.timeout = Some(timeout);
+        executor
+    }
+
+    /// the timeout of exec/query, `self.timeout` first, otherwise `RBatis::get_exec_timeout()`
+    pub fn get_timeout(&self) -> Option<Duration> {
+        self.timeout.or_else(|| self.rb.get_exec_timeout())
+    }
+
     pub fn take_connection(self) -> Option<Box<dyn Connection>> {
         Arc::into_inner(self.conn).map(Mutex::into_inner)
     }
+
+    /// run the query on the datasource chosen by `Intercept::route()`,
+    /// fall back to the connection of this executor if it is not routed or fails
+    async fn route_query(&self, sql: &str, args: Vec<Value>) -> Result<Value, Error> {
+        let timeout = self.get_timeout();
+        if let Some(v) = route_query(self, &self.intercepts, timeout, sql, &args).await {
+            return Ok(v);
+        }
+        let mut conn = self.conn.lock().await;
+        conn_timeout(&mut conn, timeout, |c| c.exec_decode(sql, args)).await
+    }
 }
 
 impl Debug for RBatisConnExecutor {
@@ -90,7 +151,9 @@ impl RBatisConnExecutor {
     {
         // Fast path: no interceptors - skip all overhead
         if self.intercepts.is_empty() {
-            let result = self.conn.lock().await.exec_decode(sql, args).await;
+            let mut conn = self.conn.lock().await;
+            let result =
+                conn_timeout(&mut conn, self.get_timeout(), |c| c.exec_decode(sql, args)).await;
             return result.and_then(|v| decode(v));
         }
         let mut sql = sql.to_string();
@@ -108,10 +171,8 @@ impl RBatisConnExecutor {
         {
             return before_result.and_then(|v| decode(v));
         }
-        let mut conn = self.conn.lock().await;
         let mut args_after = args.clone();
-        let mut result = conn.exec_decode(&sql, args).await;
-        drop(conn);
+        let mut result = self.route_query(&sql, args).await;
         if intercept::apply_after(
             &self.intercepts,
             id,
@@ -126,6 +187,37 @@ impl RBatisConnExecutor {
         }
         result.and_then(|v| decode(v))
     }
+
+    /// query rows as a stream, each row is a `Value::Map`.
+    ///
+    /// the rows are read by a background task holding this connection,
+    /// drop the stream will stop reading. interceptors `before()` run before the query,
+    /// `after_stream()` run when the stream ends.
+    /// the `get_timeout()` bounds the query start and the wait of each row.
+    pub async fn query_stream(&self, sql: &str, args: Vec<Value>) -> Result<RowStream, Error> {
+        query_stream(
+            self.clone(),
+            self.id,
+            self.conn.clone(),
+            self.intercepts.clone(),
+            self.get_timeout(),
+            sql,
+            args,
+        )
+        .await
+    }
+
+    /// query rows as a stream and decode each row to `T`
+    pub async fn fetch_stream<T>(
+        &self,
+        sql: &str,
+        args: Vec<Value>,
+    ) -> Result<BoxStream<'static, Result<T, Error>>, Error>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        Ok(decode_stream(self.query_stream(sql, args).await?))
+    }
 }
 
 impl Executor for RBatisConnExecutor {
@@ -134,6 +226,14 @@ impl Executor for RBatisConnExecutor {
         self.id
     }
 
+    fn connection(&self) -> Option<&Arc<Mutex<Box<dyn Connection>>>> {
+        Some(&self.conn)
+    }
+
+    fn exec_timeout(&self) -> Option<Duration> {
+        self.get_timeout()
+    }
+
     fn exec(&self, sql: &str, mut args: Vec<Value>) -> BoxFuture<'_, Result<ExecResult, Error>> {
         let mut sql = sql.to_string();
         Box::pin(async move {
@@ -152,7 +252,10 @@ impl Executor for RBatisConnExecutor {
                 return before_result;
             }
             let mut args_after = args.clone();
-            let mut result = self.conn.lock().await.exec(&sql, args).await;
+            let mut conn = self.conn.lock().await;
+            let mut result =
+                conn_timeout(&mut conn, self.get_timeout(), |c| c.exec(&sql, args)).await;
+            drop(conn);
             if intercept::apply_after(
                 &self.intercepts,
                 id,
@@ -186,9 +289,8 @@ impl Executor for RBatisConnExecutor {
             {
                 return before_result;
             }
-            let mut conn = self.conn.lock().await;
             let mut args_after = args.clone();
-            let mut result = conn.exec_decode(&sql, args).await;
+            let mut result = self.route_query(&sql, args).await;
             if intercept::apply_after(
                 &self.intercepts,
                 id,
@@ -214,27 +316,95 @@ impl RBatisRef for RBatisConnExecutor {
 
 impl RBatisConnExecutor {
     pub fn begin(self) -> BoxFuture<'static, Result<RBatisTxExecutor, Error>> {
+        self.begin_option(TxOptions::default())
+    }
+
+    /// begin transaction with isolation level and access mode
+    /// for example:
+    /// ```rust
+    /// use rbatis::{Error, IsolationLevel, RBatis, TxOptions};
+    ///
+    /// async fn test_begin_option(rb: &RBatis) -> Result<(), Error> {
+    ///     let conn = rb.acquire().await?;
+    ///     let tx = conn
+    ///         .begin_option(TxOptions::new().isolation_level(IsolationLevel::Serializable))
+    ///         .await?;
+    ///     tx.commit().await
+    /// }
+    /// ```
+    pub fn begin_option(
+        self,
+        options: TxOptions,
+    ) -> BoxFuture<'static, Result<RBatisTxExecutor, Error>> {
         Box::pin(async move {
             let task_id = self.rb.task_id_generator.generate();
             let id = self.id;
             let rb = self.rb.clone();
+            let driver_type = rb.driver_type()?.to_string();
+            options.check(&driver_type)?;
             let conn = self.take_connection();
             let mut conn = conn.ok_or_else(|| Error::from("[rb] failed to take connection: connection Arc is still shared (this may happen if the executor was cloned)"))?;
-            conn.begin().await?;
+            if let Some(sql) = options.set_sql(&driver_type) {
+                conn.exec(&sql, vec![]).await?;
+            }
+            match options.begin_sql(&driver_type) {
+                Some(sql) => {
+                    conn.exec(&sql, vec![]).await?;
+                }
+                None => {
+                    conn.begin().await?;
+                }
+            }
+            if let Some(sql) = options.after_begin_sql(&driver_type) {
+                if let Err(e) = conn.exec(&sql, vec![]).await {
+                    let _ = conn.rollback().await;
+                    return Err(e);
+                }
+            }
             let conn_executor = RBatisConnExecutor::new(id, conn, rb);
-            Ok(RBatisTxExecutor::new(task_id, conn_executor))
+            let mut tx = RBatisTxExecutor::new(task_id, conn_executor);
+            tx.reset_sql = options.reset_sql(&driver_type);
+            if let Err(e) = intercept::apply_begin(&tx.conn_executor.intercepts, task_id, &tx).await
+            {
+                if let Err(rollback_err) = tx.conn_executor.conn.lock().await.rollback().await {
+                    log::error!("[rb] [{}] rollback fail: {}", task_id, rollback_err);
+                }
+                return Err(e);
+            }
+            Ok(tx)
         })
     }
 
+    /// rollback the connection, the `Intercept::on_rollback()` is only called by `RBatisTxExecutor`
     pub fn rollback(&self) -> BoxFuture<'_, Result<(), Error>> {
         Box::pin(async { self.conn.lock().await.rollback().await })
     }
 
+    /// commit the connection, the `Intercept::on_commit()` is only called by `RBatisTxExecutor`
     pub fn commit(&self) -> BoxFuture<'_, Result<(), Error>> {
         Box::pin(async { self.conn.lock().await.commit().await })
     }
 }
 
+/// callback registered by `RBatisTxExecutor::after_commit()` or `after_rollback()`
+pub type TxCallback = Box<dyn FnOnce() -> BoxFuture<'static, ()> + Send>;
+
+/// the callbacks of a transaction, shared by the outer tx and all its nested tx
+#[derive(Default)]
+pub struct TxCallbacks {
+    pub after_commit: Vec<TxCallback>,
+    pub after_rollback: Vec<TxCallback>,
+}
+
+impl Debug for TxCallbacks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TxCallbacks")
+            .field("after_commit", &self.after_commit.len())
+            .field("after_rollback", &self.after_rollback.len())
+            .finish()
+    }
+}
+
 /// `RBatisTxExecutor` is a type that represents an executor for transactional operations in RBatis.
 ///
 /// # Type Description
@@ -250,6 +420,17 @@ pub struct RBatisTxExecutor {
     /// if tx call .commit() or .rollback() done = true.
     /// if tx not call .commit() or .rollback() done = false
     done: Arc<AtomicBool>,
+    /// savepoint name, only set on a nested tx created by `begin_nested()`
+    savepoint: Option<String>,
+    /// savepoint sequence, shared by the outer tx and all its nested tx
+    savepoint_seq: Arc<AtomicU64>,
+    /// after commit/rollback callbacks, shared by the outer tx and all its nested tx
+    callbacks: Arc<std::sync::Mutex<TxCallbacks>>,
+    /// the after_commit callbacks len when the nested tx begin,
+    /// rollback the nested tx will drop callbacks registered after it
+    callbacks_mark: usize,
+    /// the sql run after the outer tx commit/rollback, reset the session scoped `TxOptions`
+    reset_sql: Option<String>,
 }
 
 impl Debug for RBatisTxExecutor {
@@ -258,6 +439,7 @@ impl Debug for RBatisTxExecutor {
             .field("tx_id", &self.tx_id)
             .field("conn_executor", &self.conn_executor)
             .field("done", &self.done)
+            .field("savepoint", &self.savepoint)
             .finish()
     }
 }
@@ -268,6 +450,11 @@ impl RBatisTxExecutor {
             tx_id,
             conn_executor,
             done: Arc::new(AtomicBool::new(false)),
+            savepoint: None,
+            savepoint_seq: Arc::new(AtomicU64::new(0)),
+            callbacks: Arc::new(std::sync::Mutex::new(TxCallbacks::default())),
+            callbacks_mark: 0,
+            reset_sql: None,
         }
     }
 
@@ -297,9 +484,157 @@ impl RBatisTxExecutor {
         })
     }
 
+    /// begin a nested tx on the same connection by create a `SAVEPOINT`.
+    ///
+    /// the nested tx `commit()` will `RELEASE SAVEPOINT`, `rollback()` will `ROLLBACK TO SAVEPOINT`,
+    /// the outer tx `done` flag is not changed by the nested tx.
+    /// for example:
+    /// ```rust
+    ///  use rbatis::executor::RBatisTxExecutor;
+    ///  use rbatis::Error;
+    ///
+    ///  async fn test_nested(tx: &RBatisTxExecutor) -> Result<(), Error> {
+    ///     let nested = tx.begin_nested().await?;
+    ///     if let Err(e) = nested.exec("update activity set name = 'a'", vec![]).await {
+    ///         nested.rollback().await?;
+    ///         return Err(e);
+    ///     }
+    ///     nested.commit().await?;
+    ///     Ok(())
+    ///  }
+    /// ```
+    pub fn begin_nested(&self) -> BoxFuture<'_, Result<RBatisTxExecutor, Error>> {
+        Box::pin(async move {
+            let seq = self.savepoint_seq.fetch_add(1, Ordering::SeqCst) + 1;
+            let savepoint = format!("rb_sp_{}", seq);
+            let driver_type = self.driver_type()?;
+            let sql = if driver_type == "mssql" {
+                format!("save transaction {}", savepoint)
+            } else {
+                format!("savepoint {}", savepoint)
+            };
+            self.conn_executor
+                .conn
+                .lock()
+                .await
+                .exec(&sql, vec![])
+                .await?;
+            Ok(RBatisTxExecutor {
+                tx_id: self.tx_id,
+                conn_executor: self.conn_executor.clone(),
+                done: Arc::new(AtomicBool::new(false)),
+                savepoint: Some(savepoint),
+                savepoint_seq: self.savepoint_seq.clone(),
+                callbacks: self.callbacks.clone(),
+                callbacks_mark: self.callbacks_lock().after_commit.len(),
+                reset_sql: None,
+            })
+        })
+    }
+
+    /// the savepoint name of nested tx, outer tx is None
+    pub fn savepoint(&self) -> Option<&str> {
+        self.savepoint.as_deref()
+    }
+
+    /// is this tx created by `begin_nested()`?
+    pub fn is_nested(&self) -> bool {
+        self.savepoint.is_some()
+    }
+
+    /// register a callback run after the outer tx commit success.
+    ///
+    /// callbacks run in register order, and are dropped if the outer tx rollback.
+    /// callbacks registered in a nested tx are dropped if the nested tx rollback.
+    /// for example:
+    /// ```rust
+    ///  use rbatis::executor::RBatisTxExecutor;
+    ///  use rbatis::Error;
+    ///
+    ///  async fn test_tx(tx: RBatisTxExecutor) -> Result<(), Error> {
+    ///     tx.exec("update activity set name = 'a'", vec![]).await?;
+    ///     tx.after_commit(|| async move {
+    ///         println!("publish event");
+    ///     });
+    ///     tx.commit().await?;
+    ///     Ok(())
+    ///  }
+    /// ```
+    pub fn after_commit<F, Fut>(&self, callback: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.callbacks_lock()
+            .after_commit
+            .push(Box::new(move || Box::pin(callback())));
+    }
+
+    /// register a callback run after the outer tx rollback success.
+    ///
+    /// callbacks run in register order, and are dropped if the outer tx commit.
+    pub fn after_rollback<F, Fut>(&self, callback: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.callbacks_lock()
+            .after_rollback
+            .push(Box::new(move || Box::pin(callback())));
+    }
+
+    /// run the `reset_sql` after the outer tx commit/rollback, the tx is done so the error is only logged
+    async fn reset_session(&self) {
+        if let Some(sql) = &self.reset_sql {
+            if let Err(e) = self.conn_executor.conn.lock().await.exec(sql, vec![]).await {
+                log::error!(
+                    "[rb] [{}] reset transaction options fail: {}",
+                    self.tx_id,
+                    e
+                );
+            }
+        }
+    }
+
+    fn callbacks_lock(&self) -> std::sync::MutexGuard<'_, TxCallbacks> {
+        self.callbacks.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
     pub fn rollback(&self) -> BoxFuture<'_, Result<(), Error>> {
         Box::pin(async {
-            self.conn_executor.conn.lock().await.rollback().await?;
+            match &self.savepoint {
+                Some(savepoint) => {
+                    let sql = if self.driver_type()? == "mssql" {
+                        format!("rollback transaction {}", savepoint)
+                    } else {
+                        format!("rollback to savepoint {}", savepoint)
+                    };
+                    self.conn_executor
+                        .conn
+                        .lock()
+                        .await
+                        .exec(&sql, vec![])
+                        .await?;
+                    self.callbacks_lock()
+                        .after_commit
+                        .truncate(self.callbacks_mark);
+                }
+                None => {
+                    self.conn_executor.conn.lock().await.rollback().await?;
+                    self.done.store(true, Ordering::Relaxed);
+                    let callbacks = std::mem::take(&mut *self.callbacks_lock());
+                    self.reset_session().await;
+                    if let Err(e) =
+                        intercept::apply_rollback(&self.conn_executor.intercepts, self.tx_id, self)
+                            .await
+                    {
+                        log::error!("[rb] [{}] on_rollback fail: {}", self.tx_id, e);
+                    }
+                    for callback in callbacks.after_rollback {
+                        callback().await;
+                    }
+                }
+            }
             self.done.store(true, Ordering::Relaxed);
             Ok(())
         })
@@ -307,12 +642,78 @@ impl RBatisTxExecutor {
 
     pub fn commit(&self) -> BoxFuture<'_, Result<(), Error>> {
         Box::pin(async {
-            self.conn_executor.conn.lock().await.commit().await?;
+            match &self.savepoint {
+                Some(savepoint) => {
+                    //mssql not support release savepoint, it will be released by the outer tx
+                    if self.driver_type()? != "mssql" {
+                        let sql = format!("release savepoint {}", savepoint);
+                        self.conn_executor
+                            .conn
+                            .lock()
+                            .await
+                            .exec(&sql, vec![])
+                            .await?;
+                    }
+                }
+                None => {
+                    self.conn_executor.conn.lock().await.commit().await?;
+                    self.done.store(true, Ordering::Relaxed);
+                    self.reset_session().await;
+                    let callbacks = std::mem::take(&mut *self.callbacks_lock());
+                    if let Err(e) =
+                        intercept::apply_commit(&self.conn_executor.intercepts, self.tx_id, self)
+                            .await
+                    {
+                        log::error!("[rb] [{}] on_commit fail: {}", self.tx_id, e);
+                    }
+                    for callback in callbacks.after_commit {
+                        callback().await;
+                    }
+                }
+            }
             self.done.store(true, Ordering::Relaxed);
             Ok(())
         })
     }
 
+    /// query rows as a stream, same as `RBatisConnExecutor::query_stream()`.
+    /// the connection is locked until the stream ends or is dropped.
+    pub async fn query_stream(&self, sql: &str, args: Vec<Value>) -> Result<RowStream, Error> {
+        query_stream(
+            self.clone(),
+            self.tx_id,
+            self.conn_executor.conn.clone(),
+            self.conn_executor.intercepts.clone(),
+            self.conn_executor.get_timeout(),
+            sql,
+            args,
+        )
+        .await
+    }
+
+    /// query rows as a stream and decode each row to `T`
+    pub async fn fetch_stream<T>(
+        &self,
+        sql: &str,
+        args: Vec<Value>,
+    ) -> Result<BoxStream<'static, Result<T, Error>>, Error>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        Ok(decode_stream(self.query_stream(sql, args).await?))
+    }
+
+    /// return a tx on the same connection, each exec/query of it is bounded by `timeout`,
+    /// same as `RBatisConnExecutor::with_timeout()`.
+    ///
+    /// if the timeout elapsed, the connection is closed and the database rollback the tx,
+    /// the tx is not usable after that (commit/rollback return an error), please begin a new tx.
+    pub fn with_timeout(&self, timeout: Duration) -> Self {
+        let mut tx = self.clone();
+        tx.conn_executor.timeout = Some(timeout);
+        tx
+    }
+
     /// tx is done?
     pub fn done(&self) -> bool {
         self.done.load(Ordering::Relaxed)
@@ -355,6 +756,18 @@ impl Executor for RBatisTxExecutor {
         self.tx_id
     }
 
+    fn is_tx(&self) -> bool {
+        true
+    }
+
+    fn connection(&self) -> Option<&Arc<Mutex<Box<dyn Connection>>>> {
+        Some(&self.conn_executor.conn)
+    }
+
+    fn exec_timeout(&self) -> Option<Duration> {
+        self.conn_executor.get_timeout()
+    }
+
     fn exec(&self, sql: &str, mut args: Vec<Value>) -> BoxFuture<'_, Result<ExecResult, Error>> {
         let mut sql = sql.to_string();
         Box::pin(async move {
@@ -374,7 +787,12 @@ impl Executor for RBatisTxExecutor {
                 return before_result;
             }
             let mut args_after = args.clone();
-            let mut result = self.conn_executor.conn.lock().await.exec(&sql, args).await;
+            let mut conn = self.conn_executor.conn.lock().await;
+            let mut result = conn_timeout(&mut conn, self.conn_executor.get_timeout(), |c| {
+                c.exec(&sql, args)
+            })
+            .await;
+            drop(conn);
             if intercept::apply_after(
                 intercepts,
                 id,
@@ -411,7 +829,10 @@ impl Executor for RBatisTxExecutor {
             }
             let mut conn = self.conn_executor.conn.lock().await;
             let mut args_after = args.clone();
-            let mut result = conn.exec_decode(&sql, args).await;
+            let mut result = conn_timeout(&mut conn, self.conn_executor.get_timeout(), |c| {
+                c.exec_decode(&sql, args)
+            })
+            .await;
             if intercept::apply_after(
                 intercepts,
                 id,
@@ -508,6 +929,18 @@ impl Executor for RBatisTxExecutorGuard {
         self.tx.id()
     }
 
+    fn is_tx(&self) -> bool {
+        self.tx.is_tx()
+    }
+
+    fn connection(&self) -> Option<&Arc<Mutex<Box<dyn Connection>>>> {
+        self.tx.connection()
+    }
+
+    fn exec_timeout(&self) -> Option<Duration> {
+        self.tx.exec_timeout()
+    }
+
     fn exec(&self, sql: &str, args: Vec<Value>) -> BoxFuture<'_, Result<ExecResult, Error>> {
         let sql = sql.to_string();
         Box::pin(async move { self.tx.exec(&sql, args).await })
@@ -522,13 +955,13 @@ impl Executor for RBatisTxExecutorGuard {
 impl RBatis {
     /// exec sql
     pub async fn exec(&self, sql: &str, args: Vec<Value>) -> Result<ExecResult, Error> {
-        let conn = self.acquire().await?;
+        let conn = self.acquire_route(RouteKind::Exec, sql).await?;
         conn.exec(sql, args).await
     }
 
     /// query raw Value
     pub async fn query(&self, sql: &str, args: Vec<Value>) -> Result<Value, Error> {
-        let conn = self.acquire().await?;
+        let conn = self.acquire_route(RouteKind::Query, sql).await?;
         let v = conn.query(sql, args).await?;
         Ok(v)
     }
@@ -550,12 +983,12 @@ impl RBatis {
     {
         // Fast path: no interceptors - skip all overhead
         if self.intercepts.is_empty() {
-            let pool = self
-                .pool
-                .get()
-                .ok_or_else(|| Error::from("[rb] rbatis pool not inited!"))?;
+            let pool = self.get_route_pool(RouteKind::Query, sql)?;
             let mut conn = pool.get().await?;
-            let result = conn.exec_decode(sql, args).await;
+            let result = conn_timeout(&mut conn, self.get_exec_timeout(), |c| {
+                c.exec_decode(sql, args)
+            })
+            .await;
             return result.and_then(|v| decode(v));
         }
 
@@ -573,13 +1006,16 @@ impl RBatis {
         {
             return before_result.and_then(|v| decode(v));
         }
-        let pool = self
-            .pool
-            .get()
-            .ok_or_else(|| Error::from("[rb] rbatis pool not inited!"))?;
-        let mut conn = pool.get().await?;
         let mut args_after = args.clone();
-        let mut result = conn.exec_decode(&sql, args).await;
+        let timeout = self.get_exec_timeout();
+        let mut result = match route_query(self, &self.intercepts, timeout, &sql, &args).await {
+            Some(v) => Ok(v),
+            None => {
+                let pool = self.get_route_pool(RouteKind::Query, &sql)?;
+                let mut conn = pool.get().await?;
+                conn_timeout(&mut conn, timeout, |c| c.exec_decode(&sql, args)).await
+            }
+        };
         if intercept::apply_after(
             &self.intercepts,
             0,
@@ -594,6 +1030,44 @@ impl RBatis {
         }
         result.and_then(|v| decode(v))
     }
+
+    /// query rows as a stream on a new connection, each row is a `Value::Map`.
+    /// the connection return to the pool when the stream ends or is dropped.
+    /// for example:
+    /// ```rust
+    ///  use futures::StreamExt;
+    ///  use rbatis::{Error, RBatis};
+    ///
+    ///  #[derive(serde::Deserialize)]
+    ///  struct Activity {
+    ///     id: Option<String>,
+    ///  }
+    ///
+    ///  async fn export(rb: &RBatis) -> Result<(), Error> {
+    ///     let mut stream = rb.fetch_stream::<Activity>("select * from activity", vec![]).await?;
+    ///     while let Some(row) = stream.next().await {
+    ///         let row = row?;
+    ///     }
+    ///     Ok(())
+    ///  }
+    /// ```
+    pub async fn query_stream(&self, sql: &str, args: Vec<Value>) -> Result<RowStream, Error> {
+        let conn = self.acquire_route(RouteKind::Query, sql).await?;
+        conn.query_stream(sql, args).await
+    }
+
+    /// query rows as a stream on a new connection and decode each row to `T`
+    pub async fn fetch_stream<T>(
+        &self,
+        sql: &str,
+        args: Vec<Value>,
+    ) -> Result<BoxStream<'static, Result<T, Error>>, Error>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        let conn = self.acquire_route(RouteKind::Query, sql).await?;
+        conn.fetch_stream(sql, args).await
+    }
 }
 
 impl Executor for RBatis {
@@ -604,7 +1078,7 @@ impl Executor for RBatis {
     fn exec(&self, sql: &str, args: Vec<Value>) -> BoxFuture<'_, Result<ExecResult, Error>> {
         let sql = sql.to_string();
         Box::pin(async move {
-            let conn = self.acquire().await?;
+            let conn = self.acquire_route(RouteKind::Exec, &sql).await?;
             conn.exec(&sql, args).await
         })
     }
@@ -612,8 +1086,277 @@ impl Executor for RBatis {
     fn query(&self, sql: &str, args: Vec<Value>) -> BoxFuture<'_, Result<Value, Error>> {
         let sql = sql.to_string();
         Box::pin(async move {
-            let conn = self.acquire().await?;
+            let conn = self.acquire_route(RouteKind::Query, &sql).await?;
             conn.query(&sql, args).await
         })
     }
 }
+
+/// a stream of rows, each row is a `Value::Map`
+pub type RowStream = BoxStream<'static, Result<Value, Error>>;
+
+/// the rows buffer of `query_stream()`, the reading task wait when the buffer is full
+const QUERY_STREAM_BUFFER: usize = 64;
+
+async fn query_stream<E>(
+    executor: E,
+    id: i64,
+    conn: Arc<Mutex<Box<dyn Connection>>>,
+    intercepts: Arc<SyncVec<Arc<dyn crate::intercept::Intercept>>>,
+    timeout: Option<Duration>,
+    sql: &str,
+    mut args: Vec<Value>,
+) -> Result<RowStream, Error>
+where
+    E: Executor + 'static,
+{
+    let mut sql = sql.to_string();
+    let mut before_result = Err(Error::from(""));
+    if intercept::apply_before(
+        &intercepts,
+        id,
+        &executor,
+        &mut sql,
+        &mut args,
+        ResultType::Query(&mut before_result),
+    )
+    .await?
+    {
+        let rows = match before_result? {
+            Value::Array(rows) => rows,
+            Value::Null => vec![],
+            v => vec![v],
+        };
+        return Ok(futures::stream::iter(rows.into_iter().map(Ok)).boxed());
+    }
+    let (sender, receiver) = rbdc::rt::tokio::sync::mpsc::channel(QUERY_STREAM_BUFFER);
+    rbdc::rt::spawn(async move {
+        let mut result = Ok(0);
+        {
+            let mut conn = conn.lock().await;
+            let mut elapsed = None;
+            match timeout_opt(timeout, conn.exec_rows(&sql, args.clone())).await {
+                Ok(Ok(mut rows)) => loop {
+                    match timeout_opt(timeout, rows.next()).await {
+                        Ok(Some(Ok(row))) => {
+                            //the stream is dropped
+                            if sender.send(Ok(row_to_value(row))).await.is_err() {
+                                break;
+                            }
+                            if let Ok(count) = &mut result {
+                                *count += 1;
+                            }
+                        }
+                        Ok(Some(Err(e))) => {
+                            result = Err(e);
+                            break;
+                        }
+                        Ok(None) => break,
+                        Err(timeout) => {
+                            elapsed = Some(timeout);
+                            break;
+                        }
+                    }
+                },
+                Ok(Err(e)) => {
+                    result = Err(e);
+                }
+                Err(timeout) => {
+                    elapsed = Some(timeout);
+                }
+            };
+            if let Some(timeout) = elapsed {
+                discard_conn(&mut conn);
+                result = Err(timeout_error(timeout));
+            }
+        }
+        let hook_result =
+            intercept::apply_after_stream(&intercepts, id, &executor, &sql, &args, &result).await;
+        if let Err(e) = result {
+            let _ = sender.send(Err(e)).await;
+        }
+        if let Err(e) = hook_result {
+            let _ = sender.send(Err(e)).await;
+        }
+    });
+    Ok(
+        futures::stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|v| (v, receiver))
+        })
+        .boxed(),
+    )
+}
+
+fn row_to_value(mut row: Box<dyn Row>) -> Value {
+    let md = row.meta_data();
+    let col_len = md.column_len();
+    let mut m = rbs::value::map::ValueMap::with_capacity(col_len);
+    for i in 0..col_len {
+        m.insert(
+            Value::String(md.column_name(i)),
+            row.get(i).unwrap_or(Value::Null),
+        );
+    }
+    Value::Map(m)
+}
+
+fn decode_stream<T>(stream: RowStream) -> BoxStream<'static, Result<T, Error>>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    stream.map(|v| v.and_then(rbs::from_value::<T>)).boxed()
+}
+
+/// exec the sql without the intercepts, bounded by the executor timeout.
+/// it is used by the intercept to run more sql in its `before()`, for example the fan-out of sharding.
+///
+/// `ds` = None run on the connection of the executor(or a connection of its pool),
+/// otherwise run on a connection of the named datasource.
+pub async fn exec_raw(
+    executor: &dyn Executor,
+    ds: Option<&str>,
+    sql: &str,
+    args: Vec<Value>,
+) -> Result<ExecResult, Error> {
+    let timeout = executor.exec_timeout();
+    if let (None, Some(conn)) = (ds, executor.connection()) {
+        let mut conn = conn.lock().await;
+        return conn_timeout(&mut conn, timeout, |c| c.exec(sql, args)).await;
+    }
+    let mut conn = raw_pool(executor, ds)?.get().await?;
+    conn_timeout(&mut conn, timeout, |c| c.exec(sql, args)).await
+}
+
+/// query the sql without the intercepts, same as `exec_raw()`
+pub async fn query_raw(
+    executor: &dyn Executor,
+    ds: Option<&str>,
+    sql: &str,
+    args: Vec<Value>,
+) -> Result<Value, Error> {
+    let timeout = executor.exec_timeout();
+    if let (None, Some(conn)) = (ds, executor.connection()) {
+        let mut conn = conn.lock().await;
+        return conn_timeout(&mut conn, timeout, |c| c.exec_decode(sql, args)).await;
+    }
+    let mut conn = raw_pool(executor, ds)?.get().await?;
+    conn_timeout(&mut conn, timeout, |c| c.exec_decode(sql, args)).await
+}
+
+fn raw_pool<'a>(executor: &'a dyn Executor, ds: Option<&str>) -> Result<&'a dyn Pool, Error> {
+    match ds {
+        Some(ds) => executor.rb_ref().get_ds_pool(ds),
+        None => executor.rb_ref().get_pool(),
+    }
+}
+
+/// run the query on the datasource chosen by `Intercept::route()` and bounded by `timeout`,
+/// None = not routed or the datasource fails, the caller run it on its own connection
+async fn route_query(
+    executor: &dyn Executor,
+    intercepts: &SyncVec<Arc<dyn crate::intercept::Intercept>>,
+    timeout: Option<Duration>,
+    sql: &str,
+    args: &[Value],
+) -> Option<Value> {
+    let id = executor.id();
+    let ds = intercept::apply_route(intercepts, id, executor, sql)?;
+    let result = async {
+        let pool = executor.rb_ref().get_ds_pool(&ds)?;
+        let mut conn = pool.get().await?;
+        conn_timeout(&mut conn, timeout, |c| c.exec_decode(sql, args.to_vec())).await
+    }
+    .await;
+    match result {
+        Ok(v) => Some(v),
+        Err(e) => {
+            log::warn!(
+                "[rb] [{}] datasource '{}' fail, fall back to the primary: {}",
+                id,
+                ds,
+                e
+            );
+            None
+        }
+    }
+}
+
+/// run `f` with the optional `timeout`, Err(timeout) = the timeout elapsed and `f` is cancelled
+async fn timeout_opt<T>(
+    timeout: Option<Duration>,
+    f: impl Future<Output = T>,
+) -> Result<T, Duration> {
+    match timeout {
+        None => Ok(f.await),
+        Some(timeout) => rbdc::rt::timeout(timeout, f).await.map_err(|_| timeout),
+    }
+}
+
+/// run `f` on the connection, if the `timeout` elapsed, cancel it and discard the connection
+async fn conn_timeout<T, F>(
+    conn: &mut Box<dyn Connection>,
+    timeout: Option<Duration>,
+    f: F,
+) -> Result<T, Error>
+where
+    F: for<'c> FnOnce(&'c mut Box<dyn Connection>) -> BoxFuture<'c, Result<T, Error>>,
+{
+    match timeout_opt(timeout, f(conn)).await {
+        Ok(v) => v,
+        Err(timeout) => {
+            discard_conn(conn);
+            Err(timeout_error(timeout))
+        }
+    }
+}
+
+/// the connection is in an unknown state after the cancelled call, close it and never use it again.
+///
+/// the `DefaultPool` drops the closed connection from the pool instead of returning it to the idle queue,
+/// other pools (set by `init_pool()`) drop it when the `check()`(ping) fails on the next `get()`.
+fn discard_conn(conn: &mut Box<dyn Connection>) {
+    let mut conn = std::mem::replace(conn, Box::new(DiscardedConnection {}));
+    rbdc::rt::spawn(async move {
+        if let Err(e) = conn.close().await {
+            log::warn!("[rb] close the timeout connection fail: {}", e);
+        }
+    });
+}
+
+/// the connection placeholder after statement timeout
+#[derive(Debug)]
+struct DiscardedConnection {}
+
+impl DiscardedConnection {
+    fn error() -> Error {
+        Error::from(
+            "[rb] connection is discarded after statement timeout, please acquire a new connection",
+        )
+    }
+}
+
+impl Connection for DiscardedConnection {
+    fn exec_rows(
+        &mut self,
+        _sql: &str,
+        _params: Vec<Value>,
+    ) -> BoxFuture<'_, Result<BoxStream<'_, Result<Box<dyn Row>, Error>>, Error>> {
+        Box::pin(async { Err(Self::error()) })
+    }
+
+    fn exec(
+        &mut self,
+        _sql: &str,
+        _params: Vec<Value>,
+    ) -> BoxFuture<'_, Result<ExecResult, Error>> {
+        Box::pin(async { Err(Self::error()) })
+    }
+
+    fn ping(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+        Box::pin(async { Err(Self::error()) })
+    }
+
+    fn close(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+        Box::pin(async { Ok(()) })
+    }
+}
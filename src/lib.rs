@@ -5,7 +5,7 @@ pub extern crate rbatis_codegen;
 extern crate rbatis_macro_driver;
 pub extern crate rbdc;
 
-pub use rbatis_macro_driver::{html_sql, py_sql, snake_name, sql};
+pub use rbatis_macro_driver::{html_sql, py_sql, snake_name, sql, Columns};
 
 pub mod plugin;
 
@@ -18,12 +18,20 @@ pub mod crud;
 #[macro_use]
 pub mod error;
 pub mod crud_traits;
+pub mod datasource;
 pub mod decode;
+pub mod pool;
+pub mod query;
+pub mod transaction;
 
 pub use async_trait::async_trait;
+pub use crud_traits::Columns;
+pub use datasource::*;
 pub use decode::*;
 pub use error::*;
 pub use executor::*;
 pub use plugin::*;
+pub use pool::DefaultPool;
+pub use query::*;
 pub use rbatis::*;
-pub use rbdc_pool_fast::FastPool as DefaultPool;
+pub use transaction::*;
@@ -0,0 +1,176 @@
+use crate::RBatis;
+use rbdc::DateTime;
+use rbs::Value;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+/// fill the columns of the rows of `crud!` insert and update, see `RBatis::set_fill_handler()`
+pub trait FillHandler: Debug + Send + Sync {
+    /// fill the row of insert
+    fn insert_fill(&self, table: &str, row: &mut Value);
+    /// fill the row of update
+    fn update_fill(&self, table: &str, row: &mut Value);
+}
+
+/// the value provider of a fill column, return `Value::Null` to skip the fill
+pub type FillProvider = Arc<dyn Fn() -> Value + Send + Sync>;
+
+/// the fill config of a column
+#[derive(Clone)]
+pub struct FillColumn {
+    pub column: String,
+    pub on_insert: bool,
+    pub on_update: bool,
+    pub provider: FillProvider,
+}
+
+impl Debug for FillColumn {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FillColumn")
+            .field("column", &self.column)
+            .field("on_insert", &self.on_insert)
+            .field("on_update", &self.on_update)
+            .finish()
+    }
+}
+
+/// the default `FillHandler`, set the configured columns when they are null.
+/// the columns not in the table struct are not filled.
+///
+/// how to use?
+/// ```rust
+/// use rbatis::RBatis;
+/// use rbatis::plugin::DefaultFillHandler;
+/// use rbs::Value;
+///
+/// let rb = RBatis::new();
+/// rb.set_fill_handler(
+///     DefaultFillHandler::new()
+///         .timestamps()
+///         .on_insert("created_by", || Value::from("admin"))
+///         .on_insert_update("updated_by", || Value::from("admin")),
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct DefaultFillHandler {
+    pub columns: Vec<FillColumn>,
+}
+
+impl DefaultFillHandler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn column<F>(mut self, column: &str, on_insert: bool, on_update: bool, provider: F) -> Self
+    where
+        F: Fn() -> Value + Send + Sync + 'static,
+    {
+        self.columns.push(FillColumn {
+            column: column.to_string(),
+            on_insert,
+            on_update,
+            provider: Arc::new(provider),
+        });
+        self
+    }
+
+    /// fill the column on insert
+    pub fn on_insert<F>(self, column: &str, provider: F) -> Self
+    where
+        F: Fn() -> Value + Send + Sync + 'static,
+    {
+        self.column(column, true, false, provider)
+    }
+
+    /// fill the column on update
+    pub fn on_update<F>(self, column: &str, provider: F) -> Self
+    where
+        F: Fn() -> Value + Send + Sync + 'static,
+    {
+        self.column(column, false, true, provider)
+    }
+
+    /// fill the column on insert and update
+    pub fn on_insert_update<F>(self, column: &str, provider: F) -> Self
+    where
+        F: Fn() -> Value + Send + Sync + 'static,
+    {
+        self.column(column, true, true, provider)
+    }
+
+    /// fill `create_time` on insert, `update_time` on insert and update by `DateTime::now()`
+    pub fn timestamps(self) -> Self {
+        self.on_insert("create_time", fill_now)
+            .on_insert_update("update_time", fill_now)
+    }
+
+    fn fill(&self, row: &mut Value, update: bool) {
+        let m = match row {
+            Value::Map(m) => m,
+            _ => return,
+        };
+        for c in &self.columns {
+            if (update && !c.on_update) || (!update && !c.on_insert) {
+                continue;
+            }
+            if let Some(v) = m.get_mut(&Value::String(c.column.clone())) {
+                if *v == Value::Null {
+                    *v = (c.provider)();
+                }
+            }
+        }
+    }
+}
+
+impl FillHandler for DefaultFillHandler {
+    fn insert_fill(&self, _table: &str, row: &mut Value) {
+        self.fill(row, false)
+    }
+
+    fn update_fill(&self, _table: &str, row: &mut Value) {
+        self.fill(row, true)
+    }
+}
+
+/// the `DateTime::now()` value
+pub fn fill_now() -> Value {
+    rbs::value!(DateTime::now())
+}
+
+/// fill the insert rows by the `FillHandler` of rb
+pub fn fill_insert(rb: &RBatis, table: &str, rows: &mut Value) {
+    let handler = match rb.get_fill_handler() {
+        None => return,
+        Some(v) => v,
+    };
+    if let Value::Array(rows) = rows {
+        for row in rows {
+            handler.insert_fill(table, row);
+        }
+    }
+}
+
+/// fill the update row by the `FillHandler` of rb,
+/// the filled columns are added to `columns`(if it is an array of column names)
+pub fn fill_update(rb: &RBatis, table: &str, row: &mut Value, columns: &mut Value) {
+    let handler = match rb.get_fill_handler() {
+        None => return,
+        Some(v) => v,
+    };
+    let nulls: Vec<Value> = match &*row {
+        Value::Map(m) => m
+            .into_iter()
+            .filter(|(_, v)| **v == Value::Null)
+            .map(|(k, _)| k.clone())
+            .collect(),
+        _ => return,
+    };
+    handler.update_fill(table, row);
+    if let (Value::Array(columns), Value::Map(m)) = (columns, &*row) {
+        for k in nulls {
+            if m.get(&k) != &Value::Null && !columns.contains(&k) {
+                columns.push(k);
+            }
+        }
+    }
+}
@@ -165,4 +165,27 @@ impl Intercept for LogInterceptor {
         }
         Ok(Action::Next)
     }
+
+    async fn after_stream(
+        &self,
+        task_id: i64,
+        _rb: &dyn Executor,
+        _sql: &str,
+        _args: &[Value],
+        result: &Result<u64, Error>,
+    ) -> Result<(), Error> {
+        if self.get_level_filter() == LevelFilter::Off {
+            return Ok(());
+        }
+        let level = self.to_level().unwrap_or_else(|| Level::Debug);
+        match result {
+            Ok(rows) => {
+                log!(level, "[rb] [{}] <= stream end,rows={}", task_id, rows);
+            }
+            Err(e) => {
+                log!(level, "[rb] [{}] <= {}", task_id, e);
+            }
+        }
+        Ok(())
+    }
 }
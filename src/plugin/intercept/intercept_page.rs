@@ -7,7 +7,7 @@ use rbdc::db::ExecResult;
 use rbs::Value;
 use std::sync::Arc;
 
-/// make count sql remove `limit`
+/// make count sql remove `limit`(the sql of `group by` count the groups by `select count(1) as count from (..) as t`)
 /// make select sql append limit ${page_no},${page_size}
 /// notice:
 /// ```log
@@ -78,8 +78,9 @@ impl Intercept for PageIntercept {
             if sql.trim_start().starts_with("select ") && sql.contains(" from ") {
                 let start = sql.find("select ").unwrap_or(0) + "select ".len();
                 let end = sql.find(" from ").unwrap_or(0);
-                let v = &sql[start..end];
-                *sql = sql.replace(v, "count(1) as count").to_string();
+                if start <= end {
+                    sql.replace_range(start..end, "count(1) as count");
+                }
                 if let Some(idx) = sql.rfind(" limit ") {
                     *sql = sql[..idx].to_string();
                 }
@@ -94,6 +95,10 @@ impl Intercept for PageIntercept {
                     }
                     *sql = sql[..idx].to_string();
                 }
+                //the group by return a row of each group, count the groups
+                if sql.contains(" group by ") {
+                    *sql = format!("select count(1) as count from ({}) as t", sql.trim());
+                }
             }
         }
         if self.select_ids.contains_key(&executor.id()) {
@@ -0,0 +1,87 @@
+use crate::executor::Executor;
+use crate::intercept::Intercept;
+use crate::DefaultRouter;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// send `select` outside transactions to the replica datasources registered by `RBatis::init_ds()`.
+///
+/// * choose replica by round-robin, or weighted round-robin by `new_weighted()`
+/// * fall back to the primary if the replica fails
+/// * the sql contains `PRIMARY_HINT`(`/*rb:primary*/`), `for update`, or run in a tx use the primary
+/// * the executor of `RBatis::use_ds()` is not split
+///
+/// the replica is chosen by `Intercept::route()` after all `before()`,
+/// so the query run with the timeout and the `after()` of all intercepts, the order is not matter.
+/// how to use?
+/// ```rust
+/// use std::sync::Arc;
+/// use rbatis::RBatis;
+/// use rbatis::intercept_read_write::ReadWriteSplitIntercept;
+/// use rbdc_sqlite::SqliteDriver;
+///
+/// let rb = RBatis::new();
+/// rb.init(SqliteDriver {}, "sqlite://target/sqlite.db").unwrap();
+/// rb.init_ds("replica-1", SqliteDriver {}, "sqlite://target/sqlite.db").unwrap();
+/// rb.init_ds("replica-2", SqliteDriver {}, "sqlite://target/sqlite.db").unwrap();
+/// rb.intercepts.push(Arc::new(ReadWriteSplitIntercept::new(vec!["replica-1", "replica-2"])));
+/// ```
+#[derive(Debug)]
+pub struct ReadWriteSplitIntercept {
+    /// (datasource name, weight)
+    pub replicas: Vec<(String, u32)>,
+    index: AtomicUsize,
+}
+
+impl ReadWriteSplitIntercept {
+    /// round-robin on replicas
+    pub fn new<S: ToString>(replicas: Vec<S>) -> Self {
+        Self::new_weighted(replicas.into_iter().map(|v| (v, 1)).collect())
+    }
+
+    /// weighted round-robin on replicas, the replica of weight 0 is not used
+    pub fn new_weighted<S: ToString>(replicas: Vec<(S, u32)>) -> Self {
+        Self {
+            replicas: replicas
+                .into_iter()
+                .map(|(name, weight)| (name.to_string(), weight))
+                .collect(),
+            index: AtomicUsize::new(0),
+        }
+    }
+
+    /// choose the next replica, None = no replica
+    pub fn next_replica(&self) -> Option<&str> {
+        let total: u64 = self.replicas.iter().map(|(_, w)| *w as u64).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut n = self.index.fetch_add(1, Ordering::Relaxed) as u64 % total;
+        for (name, weight) in &self.replicas {
+            if n < *weight as u64 {
+                return Some(name);
+            }
+            n -= *weight as u64;
+        }
+        None
+    }
+
+    /// can the sql run on replica?
+    pub fn is_split(&self, rb: &dyn Executor, sql: &str) -> bool {
+        if rb.is_tx() {
+            return false;
+        }
+        if rb.rb_ref().ds_name.is_some() {
+            return false;
+        }
+        DefaultRouter::is_read(sql)
+    }
+}
+
+impl Intercept for ReadWriteSplitIntercept {
+    fn route(&self, _task_id: i64, rb: &dyn Executor, sql: &str) -> Option<String> {
+        if !self.is_split(rb, sql) {
+            return None;
+        }
+        self.next_replica().map(|v| v.to_string())
+    }
+}
@@ -0,0 +1,683 @@
+use crate::executor::{exec_raw, query_raw, Executor};
+use crate::intercept::{Intercept, ResultType};
+use crate::{Action, Error, PRIMARY_DS};
+use async_trait::async_trait;
+use rbdc::db::ExecResult;
+use rbs::value::map::ValueMap;
+use rbs::Value;
+
+/// the algorithm to compute the shard index of sharding key
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShardingAlgorithm {
+    /// shard = key % n, the key must be integer
+    Mod(u64),
+    /// shard = the count of bounds <= key, the key must be integer.
+    /// for example `Range(vec![1000, 2000])`: `key < 1000` = 0, `1000 <= key < 2000` = 1, `key >= 2000` = 2
+    Range(Vec<i64>),
+    /// shard = fnv1a(key) % n
+    Hash(u64),
+}
+
+impl ShardingAlgorithm {
+    /// the count of shards
+    pub fn shard_count(&self) -> usize {
+        match self {
+            ShardingAlgorithm::Mod(n) => *n as usize,
+            ShardingAlgorithm::Range(bounds) => bounds.len() + 1,
+            ShardingAlgorithm::Hash(n) => *n as usize,
+        }
+    }
+
+    /// the shard index of key
+    pub fn shard(&self, key: &Value) -> Result<usize, Error> {
+        match self {
+            ShardingAlgorithm::Mod(n) => {
+                if *n == 0 {
+                    return Err(Error::from("[rb] sharding Mod(0) is invalid"));
+                }
+                Ok(key_i64(key)?.rem_euclid(*n as i64) as usize)
+            }
+            ShardingAlgorithm::Range(bounds) => {
+                let key = key_i64(key)?;
+                Ok(bounds.iter().filter(|b| **b <= key).count())
+            }
+            ShardingAlgorithm::Hash(n) => {
+                if *n == 0 {
+                    return Err(Error::from("[rb] sharding Hash(0) is invalid"));
+                }
+                Ok((fnv1a(key_string(key).as_bytes()) % n) as usize)
+            }
+        }
+    }
+}
+
+fn key_i64(key: &Value) -> Result<i64, Error> {
+    match key {
+        Value::I32(v) => Ok(*v as i64),
+        Value::I64(v) => Ok(*v),
+        Value::U32(v) => Ok(*v as i64),
+        Value::U64(v) => Ok(*v as i64),
+        Value::String(v) => v
+            .parse()
+            .map_err(|_| Error::from(format!("[rb] sharding key '{}' is not integer", v))),
+        Value::Ext(_, v) => key_i64(v),
+        _ => Err(Error::from(format!(
+            "[rb] sharding key '{}' is not integer",
+            key
+        ))),
+    }
+}
+
+fn key_string(key: &Value) -> String {
+    match key {
+        Value::String(v) => v.clone(),
+        Value::Ext(_, v) => key_string(v),
+        _ => key.to_string(),
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in bytes {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// the default physical table name, for example `orders_03`
+pub fn default_shard_table_name(table: &str, shard: usize) -> String {
+    format!("{}_{:02}", table, shard)
+}
+
+/// the sharding rule of one logic table
+#[derive(Clone, Debug)]
+pub struct ShardingRule {
+    /// the logic table name in sql, for example `orders`
+    pub table: String,
+    /// the sharding key column, for example `user_id`
+    pub key: String,
+    pub algorithm: ShardingAlgorithm,
+    /// the datasources of shards, the shard `i` use `datasources[i % len]`,
+    /// empty = all shards use the datasource of executor
+    pub datasources: Vec<String>,
+    /// make the physical table name, default is `default_shard_table_name`
+    pub table_name: fn(&str, usize) -> String,
+}
+
+impl ShardingRule {
+    pub fn new(table: &str, key: &str, algorithm: ShardingAlgorithm) -> Self {
+        Self {
+            table: table.to_string(),
+            key: key.to_string(),
+            algorithm,
+            datasources: vec![],
+            table_name: default_shard_table_name,
+        }
+    }
+
+    pub fn datasources<S: ToString>(mut self, datasources: Vec<S>) -> Self {
+        self.datasources = datasources.into_iter().map(|v| v.to_string()).collect();
+        self
+    }
+
+    pub fn table_name(mut self, f: fn(&str, usize) -> String) -> Self {
+        self.table_name = f;
+        self
+    }
+
+    /// the physical table name of shard
+    pub fn physical_table(&self, shard: usize) -> String {
+        (self.table_name)(&self.table, shard)
+    }
+
+    /// the datasource name of shard, None = the datasource of executor
+    pub fn datasource(&self, shard: usize) -> Option<&str> {
+        if self.datasources.is_empty() {
+            return None;
+        }
+        Some(&self.datasources[shard % self.datasources.len()])
+    }
+
+    /// is the logic table in sql?
+    pub fn is_match(&self, sql: &str) -> bool {
+        !table_positions(sql, &self.table).is_empty()
+    }
+
+    /// replace the logic table to the physical table of shard
+    pub fn rewrite_sql(&self, sql: &str, shard: usize) -> String {
+        let positions = table_positions(sql, &self.table);
+        let physical = self.physical_table(shard);
+        let mut new_sql = String::with_capacity(sql.len() + positions.len() * 4);
+        let mut last = 0;
+        for p in positions {
+            new_sql.push_str(&sql[last..p]);
+            new_sql.push_str(&physical);
+            last = p + self.table.len();
+        }
+        new_sql.push_str(&sql[last..]);
+        new_sql
+    }
+
+    /// the shards of sql, None = the sharding key not found, the sql need run on all shards
+    pub fn shards(&self, sql: &str, args: &[Value]) -> Result<Option<Vec<usize>>, Error> {
+        let keys = if is_insert(sql) {
+            Some(insert_key_values(sql, args, &self.key)?)
+        } else {
+            where_key_values(sql, args, &self.key)
+        };
+        let keys = match keys {
+            None => return Ok(None),
+            Some(keys) => keys,
+        };
+        let mut shards = Vec::with_capacity(keys.len());
+        for key in &keys {
+            let shard = self.algorithm.shard(key)?;
+            if !shards.contains(&shard) {
+                shards.push(shard);
+            }
+        }
+        shards.sort();
+        Ok(Some(shards))
+    }
+}
+
+pub(crate) fn is_ident_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_'
+}
+
+/// the positions of `word` in `sql` at identifier boundary
+pub(crate) fn find_words(sql: &str, word: &str) -> Vec<usize> {
+    let bytes = sql.as_bytes();
+    let mut positions = vec![];
+    if word.is_empty() {
+        return positions;
+    }
+    let mut from = 0;
+    while let Some(i) = sql[from..].find(word) {
+        let start = from + i;
+        let end = start + word.len();
+        let before_ok = start == 0 || !is_ident_char(bytes[start - 1]);
+        let after_ok = end == bytes.len() || !is_ident_char(bytes[end]);
+        if before_ok && after_ok {
+            positions.push(start);
+        }
+        from = end;
+    }
+    positions
+}
+
+/// the sql with the bytes in the string literals `'..'` replaced by space, the positions are not changed
+pub(crate) fn mask_literals(sql: &str) -> String {
+    let mut quote = false;
+    let bytes: Vec<u8> = sql
+        .bytes()
+        .map(|c| {
+            if c == b'\'' {
+                quote = !quote;
+                c
+            } else if quote {
+                b' '
+            } else {
+                c
+            }
+        })
+        .collect();
+    String::from_utf8(bytes).unwrap_or_default()
+}
+
+/// the positions of the table reference in sql: the name after `from`/`join`/`into`/`update`,
+/// in the `from a, b` list, or the qualifier of `table.column`.
+/// the same word in the string literal or as a column name is not matched.
+pub(crate) fn table_positions(sql: &str, table: &str) -> Vec<usize> {
+    let table = table.to_ascii_lowercase();
+    table_words(sql)
+        .into_iter()
+        .filter(|(_, word, _)| *word == table)
+        .map(|(p, _, _)| p)
+        .collect()
+}
+
+/// the (position, lower case word, is qualifier) of the table references in sql:
+/// the word after `from`/`join`/`into`/`update`, in the `from a, b` list,
+/// or the qualifier of `table.column`(and the schema of `schema.table`).
+/// the `from` in function(for example `extract(year from t)`), `on duplicate key update`
+/// and `for update` are skipped.
+pub(crate) fn table_words(sql: &str) -> Vec<(usize, String, bool)> {
+    let lower = mask_literals(sql).to_ascii_lowercase();
+    let bytes = lower.as_bytes();
+    let mut words = vec![];
+    //the next word is a table reference
+    let mut table_ref = false;
+    //the (is sub query, in from) of the brackets, is sub query = None until the first word
+    let mut levels: Vec<(Option<bool>, bool)> = vec![(Some(true), false)];
+    let mut last_word = "";
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        if !is_ident_char(c) {
+            match c {
+                b',' => table_ref = levels.last().is_some_and(|v| v.1),
+                //quoted identifier or schema
+                b'"' | b'`' | b'[' | b']' | b'.' => {}
+                b'(' => {
+                    levels.push((None, false));
+                    table_ref = false;
+                }
+                b')' => {
+                    if levels.len() > 1 {
+                        levels.pop();
+                    }
+                    table_ref = false;
+                }
+                c if c.is_ascii_whitespace() => {}
+                _ => table_ref = false,
+            }
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < bytes.len() && is_ident_char(bytes[i]) {
+            i += 1;
+        }
+        let word = &lower[start..i];
+        let level = levels.last_mut().unwrap();
+        if level.0.is_none() {
+            level.0 = Some(word == "select" || word == "with");
+        }
+        let mut next = i;
+        while next < bytes.len() && matches!(bytes[next], b'"' | b'`' | b']') {
+            next += 1;
+        }
+        let qualifier = bytes.get(next) == Some(&b'.');
+        if table_ref || qualifier {
+            words.push((start, word.to_string(), qualifier));
+        }
+        match word {
+            "from" if level.0 == Some(false) => table_ref = false,
+            "update" if last_word == "key" || last_word == "for" => table_ref = false,
+            "from" | "join" | "into" | "update" => {
+                level.1 = true;
+                table_ref = true;
+            }
+            "where" | "on" | "set" | "values" | "select" | "group" | "order" | "having"
+            | "limit" | "union" | "using" | "returning" => {
+                level.1 = false;
+                table_ref = false;
+            }
+            //keep `table_ref` on the schema of `schema.table`
+            _ => table_ref = table_ref && qualifier,
+        }
+        last_word = word;
+    }
+    words
+}
+
+/// the positions of `?` outside quotes
+pub(crate) fn placeholder_positions(sql: &str) -> Vec<usize> {
+    let mut positions = vec![];
+    let mut quote: Option<u8> = None;
+    for (i, c) in sql.bytes().enumerate() {
+        match quote {
+            Some(q) => {
+                if c == q {
+                    quote = None;
+                }
+            }
+            None => match c {
+                b'\'' | b'"' | b'`' => quote = Some(c),
+                b'?' => positions.push(i),
+                _ => {}
+            },
+        }
+    }
+    positions
+}
+
+fn is_insert(sql: &str) -> bool {
+    sql.trim_start().to_ascii_lowercase().starts_with("insert")
+}
+
+pub(crate) fn skip_whitespace(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    i
+}
+
+/// parse the operand at `i`: `?`, `'string'` or number. return (value, end)
+fn parse_operand(
+    sql: &str,
+    i: usize,
+    args: &[Value],
+    placeholders: &[usize],
+) -> Option<(Value, usize)> {
+    let bytes = sql.as_bytes();
+    let c = *bytes.get(i)?;
+    if c == b'?' {
+        let index = placeholders.iter().position(|p| *p == i)?;
+        return Some((args.get(index)?.clone(), i + 1));
+    }
+    if c == b'\'' {
+        let end = sql[i + 1..].find('\'')? + i + 1;
+        return Some((Value::String(sql[i + 1..end].to_string()), end + 1));
+    }
+    let mut end = i;
+    if c == b'-' {
+        end += 1;
+    }
+    while end < bytes.len() && bytes[end].is_ascii_digit() {
+        end += 1;
+    }
+    let v: i64 = sql[i..end].parse().ok()?;
+    Some((Value::I64(v), end))
+}
+
+/// find the top level `and` condition `key = ?` and `key in (?, ?)` after `where`.
+/// None = the key not found, the where have `or`, or the key is in the parentheses,
+/// after `not`, or used by other operator
+fn where_key_values(sql: &str, args: &[Value], key: &str) -> Option<Vec<Value>> {
+    let lower = mask_literals(sql).to_ascii_lowercase();
+    let start = *find_words(&lower, "where").first()?;
+    let mut end = sql.len();
+    for word in ["order", "group", "having", "limit"] {
+        if let Some(p) = find_words(&lower[start..], word).first() {
+            end = end.min(start + p);
+        }
+    }
+    if !find_words(&lower[start..end], "or").is_empty() {
+        return None;
+    }
+    let placeholders = placeholder_positions(sql);
+    let bytes = sql.as_bytes();
+    let mut values = vec![];
+    for p in find_words(&lower[start..end], &key.to_ascii_lowercase()) {
+        let depth = lower[start..start + p]
+            .bytes()
+            .fold(0i32, |depth, c| match c {
+                b'(' => depth + 1,
+                b')' => depth - 1,
+                _ => depth,
+            });
+        if depth != 0 || lower[start..start + p].trim_end().ends_with(" not") {
+            return None;
+        }
+        let mut i = start + p + key.len();
+        //quoted column
+        while i < end && matches!(bytes[i], b'"' | b'`' | b']') {
+            i += 1;
+        }
+        i = skip_whitespace(bytes, i);
+        if bytes.get(i) == Some(&b'=') {
+            i = skip_whitespace(bytes, i + 1);
+            let (v, _) = parse_operand(sql, i, args, &placeholders)?;
+            values.push(v);
+        } else if lower[i..].starts_with("in") && !is_ident_char(*bytes.get(i + 2)?) {
+            i = skip_whitespace(bytes, i + 2);
+            if bytes.get(i) != Some(&b'(') {
+                return None;
+            }
+            i += 1;
+            loop {
+                i = skip_whitespace(bytes, i);
+                let (v, next) = parse_operand(sql, i, args, &placeholders)?;
+                values.push(v);
+                i = skip_whitespace(bytes, next);
+                match bytes.get(i) {
+                    Some(b',') => i += 1,
+                    Some(b')') => break,
+                    _ => return None,
+                }
+            }
+        } else {
+            return None;
+        }
+    }
+    if values.is_empty() {
+        None
+    } else {
+        Some(values)
+    }
+}
+
+/// find the key values of each row in `insert into table (columns) values (...),(...)`
+fn insert_key_values(sql: &str, args: &[Value], key: &str) -> Result<Vec<Value>, Error> {
+    let not_found = || {
+        Error::from(format!(
+            "[rb] sharding key '{}' not found in insert sql",
+            key
+        ))
+    };
+    let lower = sql.to_ascii_lowercase();
+    let bytes = sql.as_bytes();
+    let columns_start = sql.find('(').ok_or_else(not_found)?;
+    let columns_end = sql[columns_start..].find(')').ok_or_else(not_found)? + columns_start;
+    let key_index = sql[columns_start + 1..columns_end]
+        .split(',')
+        .map(|c| {
+            c.trim()
+                .trim_matches(|c| matches!(c, '"' | '`' | '[' | ']'))
+        })
+        .position(|c| c.eq_ignore_ascii_case(key))
+        .ok_or_else(not_found)?;
+    let values_start = *find_words(&lower[columns_end..], "values")
+        .first()
+        .ok_or_else(not_found)?
+        + columns_end
+        + "values".len();
+    let placeholders = placeholder_positions(sql);
+    let mut values = vec![];
+    let mut i = values_start;
+    loop {
+        i = skip_whitespace(bytes, i);
+        if bytes.get(i) != Some(&b'(') {
+            break;
+        }
+        i += 1;
+        let mut column = 0;
+        loop {
+            i = skip_whitespace(bytes, i);
+            if column == key_index {
+                let (v, _) = parse_operand(sql, i, args, &placeholders).ok_or_else(not_found)?;
+                values.push(v);
+            }
+            //skip the value
+            let mut depth = 0;
+            let mut quote = false;
+            while i < bytes.len() {
+                let c = bytes[i];
+                if quote {
+                    if c == b'\'' {
+                        quote = false;
+                    }
+                } else if c == b'\'' {
+                    quote = true;
+                } else if c == b'(' {
+                    depth += 1;
+                } else if c == b')' {
+                    if depth == 0 {
+                        break;
+                    }
+                    depth -= 1;
+                } else if c == b',' && depth == 0 {
+                    break;
+                }
+                i += 1;
+            }
+            match bytes.get(i) {
+                Some(b',') => {
+                    i += 1;
+                    column += 1;
+                }
+                Some(b')') => {
+                    i += 1;
+                    break;
+                }
+                _ => return Err(not_found()),
+            }
+        }
+        i = skip_whitespace(bytes, i);
+        if bytes.get(i) == Some(&b',') {
+            i += 1;
+        } else {
+            break;
+        }
+    }
+    if values.is_empty() {
+        return Err(not_found());
+    }
+    Ok(values)
+}
+
+/// merge the query results of shards.
+/// `select count(` with one row and one column of each shard will be sum, others will be append
+fn merge_query(sql: &str, results: Vec<Value>) -> Value {
+    let is_count = sql
+        .trim_start()
+        .to_ascii_lowercase()
+        .starts_with("select count(");
+    if is_count {
+        let mut column = None;
+        let mut sum = 0i64;
+        let mut ok = true;
+        for result in &results {
+            match result {
+                Value::Array(rows) if rows.len() == 1 => match &rows[0] {
+                    Value::Map(m) if m.len() == 1 => {
+                        let (k, v) = m.into_iter().next().unwrap();
+                        match key_i64(v) {
+                            Ok(v) => {
+                                sum += v;
+                                column = Some(k.clone());
+                            }
+                            Err(_) => ok = false,
+                        }
+                    }
+                    _ => ok = false,
+                },
+                _ => ok = false,
+            }
+        }
+        if let (true, Some(column)) = (ok, column) {
+            let mut m = ValueMap::with_capacity(1);
+            m.insert(column, Value::I64(sum));
+            return Value::Array(vec![Value::Map(m)]);
+        }
+    }
+    let mut rows = vec![];
+    for result in results {
+        match result {
+            Value::Array(arr) => rows.extend(arr),
+            Value::Null => {}
+            v => rows.push(v),
+        }
+    }
+    Value::Array(rows)
+}
+
+/// split the logic table into physical tables(and datasources) by sharding key.
+///
+/// * the sql with sharding key(`key = ?`, `key in (?, ?)`, or insert column) run on the shard of key
+/// * the sql without sharding key run on all shards, the results are merged:
+///   query rows are appended(`select count(*)` is sum), exec `rows_affected` is sum.
+///   `order by`/`limit` is applied on each shard, not on the merged rows.
+/// * the `where` with `or`, or the key in the parentheses, run on all shards
+/// * insert rows must be in the same shard
+/// * in a tx, the shards must use the datasource of tx
+///
+/// the sql of shards run on the connection directly(not run the intercepts again) after the fan-out,
+/// so please push it after the intercepts changing the sql, for example `TenantIntercept`.
+///
+/// how to use?
+/// ```rust
+/// use std::sync::Arc;
+/// use rbatis::RBatis;
+/// use rbatis::intercept_sharding::{ShardingAlgorithm, ShardingIntercept, ShardingRule};
+///
+/// let rb = RBatis::new();
+/// rb.intercepts.push(Arc::new(ShardingIntercept::new().rule(
+///     ShardingRule::new("orders", "user_id", ShardingAlgorithm::Mod(4))
+///         .datasources(vec!["primary", "ds_1"]),
+/// )));
+/// ```
+#[derive(Debug, Default)]
+pub struct ShardingIntercept {
+    pub rules: Vec<ShardingRule>,
+}
+
+impl ShardingIntercept {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn rule(mut self, rule: ShardingRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+}
+
+#[async_trait]
+impl Intercept for ShardingIntercept {
+    async fn before(
+        &self,
+        _task_id: i64,
+        rb: &dyn Executor,
+        sql: &mut String,
+        args: &mut Vec<Value>,
+        result: ResultType<&mut Result<ExecResult, Error>, &mut Result<Value, Error>>,
+    ) -> Result<Action, Error> {
+        let rule = match self.rules.iter().find(|r| r.is_match(sql)) {
+            None => return Ok(Action::Next),
+            Some(rule) => rule,
+        };
+        let shards = match rule.shards(sql, args)? {
+            Some(shards) => shards,
+            None => (0..rule.algorithm.shard_count()).collect(),
+        };
+        if is_insert(sql) && shards.len() > 1 {
+            return Err(Error::from(format!(
+                "[rb] sharding insert rows of '{}' must be in the same shard",
+                rule.table
+            )));
+        }
+        let current_ds = rb.rb_ref().ds_name.as_deref().unwrap_or(PRIMARY_DS);
+        let in_tx = rb.is_tx();
+        let is_current = |shard: usize| match rule.datasource(shard) {
+            None => true,
+            Some(ds) => ds == current_ds,
+        };
+        if in_tx && !shards.iter().all(|s| is_current(*s)) {
+            return Err(Error::from(format!(
+                "[rb] sharding in tx can not use the datasource other than '{}'",
+                current_ds
+            )));
+        }
+        if shards.len() == 1 && is_current(shards[0]) {
+            *sql = rule.rewrite_sql(sql, shards[0]);
+            return Ok(Action::Next);
+        }
+        //the sql is already intercepted, run it on the connection directly
+        let shard_ds = |shard: usize| rule.datasource(shard).filter(|ds| *ds != current_ds);
+        match result {
+            ResultType::Exec(result) => {
+                let mut merged = ExecResult::default();
+                for shard in shards {
+                    let shard_sql = rule.rewrite_sql(sql, shard);
+                    let r = exec_raw(rb, shard_ds(shard), &shard_sql, args.clone()).await?;
+                    merged.rows_affected += r.rows_affected;
+                    merged.last_insert_id = r.last_insert_id;
+                }
+                *result = Ok(merged);
+            }
+            ResultType::Query(result) => {
+                let mut results = vec![];
+                for shard in shards {
+                    let shard_sql = rule.rewrite_sql(sql, shard);
+                    results.push(query_raw(rb, shard_ds(shard), &shard_sql, args.clone()).await?);
+                }
+                *result = Ok(merge_query(sql, results));
+            }
+        }
+        Ok(Action::Return)
+    }
+}
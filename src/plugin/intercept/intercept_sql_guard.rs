@@ -0,0 +1,124 @@
+use crate::executor::Executor;
+use crate::intercept::{Intercept, ResultType};
+use crate::{Action, Error};
+use async_trait::async_trait;
+use rbdc::db::ExecResult;
+use rbs::Value;
+
+/// check the final sql for the sign of sql injection, the values of `${}` are formatted into the sql text
+/// but the values of `#{}` are bind args(`?`), so a safe sql should not have:
+///
+/// * string literal, for example `where name = 'a' or '1' = '1'`
+/// * stacked statements, for example `select * from t; drop table t`
+/// * comment, for example `where id = 1 -- and tenant_id = 2`
+///
+/// how to use?
+/// ```rust
+/// use std::sync::Arc;
+/// use rbatis::RBatis;
+/// use rbatis::intercept_sql_guard::SqlInjectionGuardIntercept;
+///
+/// let rb = RBatis::new();
+/// rb.intercepts.insert(0, Arc::new(SqlInjectionGuardIntercept::new()));
+/// // or only log the warning
+/// // rb.intercepts.insert(0, Arc::new(SqlInjectionGuardIntercept::new().warn_only(true)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SqlInjectionGuardIntercept {
+    /// allow the string literal of sql, default false
+    pub allow_string_literal: bool,
+    /// log the warning and run the sql, default false(return error)
+    pub warn_only: bool,
+}
+
+impl SqlInjectionGuardIntercept {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn allow_string_literal(mut self, allow: bool) -> Self {
+        self.allow_string_literal = allow;
+        self
+    }
+
+    pub fn warn_only(mut self, warn_only: bool) -> Self {
+        self.warn_only = warn_only;
+        self
+    }
+
+    /// the reason if the sql is flagged, None = pass. the quotes and comments are by `driver_type`:
+    ///
+    /// * mysql: `'..'` and `".."` are string literals(escaped by `\`), `#` is comment
+    /// * the others: `'..'` is string literal, `".."` is identifier, `[..]` is identifier of mssql
+    /// * `` `..` `` is identifier
+    pub fn check(&self, driver_type: &str, sql: &str) -> Option<&'static str> {
+        let mysql = driver_type == "mysql";
+        let bracket = driver_type == "mssql";
+        let bytes = sql.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            let c = bytes[i];
+            match c {
+                b'\'' | b'"' if c == b'\'' || mysql => {
+                    if !self.allow_string_literal {
+                        return Some("string literal");
+                    }
+                    i = skip_quoted(bytes, i, c, mysql);
+                }
+                b'"' | b'`' => i = skip_quoted(bytes, i, c, false),
+                b'[' if bracket => i = skip_quoted(bytes, i, b']', false),
+                b'-' if bytes.get(i + 1) == Some(&b'-') => return Some("comment"),
+                b'/' if bytes.get(i + 1) == Some(&b'*') => return Some("comment"),
+                b'#' if mysql => return Some("comment"),
+                b';' => {
+                    let rest = &sql[i + 1..];
+                    if rest.chars().any(|c| !c.is_whitespace() && c != ';') {
+                        return Some("stacked statements");
+                    }
+                    return None;
+                }
+                _ => i += 1,
+            }
+        }
+        None
+    }
+}
+
+/// skip the quoted text at `i` which end by `end`,
+/// the escape is the double `end`(and `\` if `backslash`). return the index after it
+fn skip_quoted(bytes: &[u8], mut i: usize, end: u8, backslash: bool) -> usize {
+    i += 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if backslash => i += 2,
+            c if c == end && bytes.get(i + 1) == Some(&end) => i += 2,
+            c if c == end => return i + 1,
+            _ => i += 1,
+        }
+    }
+    i
+}
+
+#[async_trait]
+impl Intercept for SqlInjectionGuardIntercept {
+    async fn before(
+        &self,
+        _task_id: i64,
+        rb: &dyn Executor,
+        sql: &mut String,
+        _args: &mut Vec<Value>,
+        _result: ResultType<&mut Result<ExecResult, Error>, &mut Result<Value, Error>>,
+    ) -> Result<Action, Error> {
+        if let Some(reason) = self.check(rb.driver_type().unwrap_or_default(), sql) {
+            if self.warn_only {
+                log::warn!("[rb] sql injection guard: the sql have {}: {}", reason, sql);
+            } else {
+                return Err(Error::from(format!(
+                    "[rb] sql injection guard: the sql have {}: {}",
+                    reason, sql
+                )));
+            }
+        }
+        Ok(Action::Next)
+    }
+}
@@ -0,0 +1,843 @@
+use crate::executor::Executor;
+use crate::intercept::{Intercept, ResultType};
+use crate::intercept_sharding::{
+    find_words, is_ident_char, mask_literals, placeholder_positions, skip_whitespace, table_words,
+};
+use crate::{Action, Error};
+use async_trait::async_trait;
+use rbdc::db::ExecResult;
+use rbs::Value;
+use std::future::Future;
+
+#[derive(Clone, Debug)]
+enum TenantContext {
+    Tenant(Value),
+    Bypass,
+}
+
+rbdc::rt::tokio::task_local! {
+    static TENANT: TenantContext;
+}
+
+/// run the future with the tenant, the sql of `TenantIntercept` tables in it are filtered by the tenant.
+///
+/// the context is task local, the task spawned in the future need call `with_tenant()` again
+pub async fn with_tenant<T: Into<Value>, F: Future>(tenant_id: T, f: F) -> F::Output {
+    TENANT
+        .scope(TenantContext::Tenant(tenant_id.into()), f)
+        .await
+}
+
+/// run the future without tenant filter, for example the admin jobs
+pub async fn bypass_tenant<F: Future>(f: F) -> F::Output {
+    TENANT.scope(TenantContext::Bypass, f).await
+}
+
+/// the tenant of current task, None = not set or bypass
+pub fn current_tenant() -> Option<Value> {
+    TENANT
+        .try_with(|c| match c {
+            TenantContext::Tenant(v) => Some(v.clone()),
+            TenantContext::Bypass => None,
+        })
+        .ok()
+        .flatten()
+}
+
+/// is current task run in `bypass_tenant()`?
+pub fn is_bypass_tenant() -> bool {
+    TENANT
+        .try_with(|c| matches!(c, TenantContext::Bypass))
+        .unwrap_or(false)
+}
+
+/// the words can not be table alias
+const KEYWORDS: [&str; 20] = [
+    "where",
+    "set",
+    "join",
+    "left",
+    "right",
+    "inner",
+    "outer",
+    "cross",
+    "full",
+    "natural",
+    "on",
+    "using",
+    "order",
+    "group",
+    "having",
+    "limit",
+    "offset",
+    "for",
+    "returning",
+    "union",
+];
+
+/// the words end the where conditions
+const END_WORDS: [&str; 8] = [
+    "order",
+    "group",
+    "having",
+    "limit",
+    "offset",
+    "for",
+    "returning",
+    "union",
+];
+
+/// the join types before `join`
+const JOIN_WORDS: [&str; 7] = [
+    "natural", "left", "right", "full", "outer", "inner", "cross",
+];
+
+/// mask[i] = the byte i is outside quotes and brackets
+fn top_level_mask(sql: &str) -> Vec<bool> {
+    let mut mask = Vec::with_capacity(sql.len());
+    let mut quote: Option<u8> = None;
+    let mut depth = 0;
+    for c in sql.bytes() {
+        match quote {
+            Some(q) => {
+                mask.push(false);
+                if c == q {
+                    quote = None;
+                }
+            }
+            None => {
+                match c {
+                    b'\'' | b'"' | b'`' => quote = Some(c),
+                    b'(' => depth += 1,
+                    b')' => depth -= 1,
+                    _ => {}
+                }
+                mask.push(depth == 0 && quote.is_none() && c != b')');
+            }
+        }
+    }
+    mask
+}
+
+fn top_level_words(lower: &str, mask: &[bool], word: &str) -> Vec<usize> {
+    find_words(lower, word)
+        .into_iter()
+        .filter(|p| mask[*p])
+        .collect()
+}
+
+fn unquote(name: &str) -> &str {
+    name.trim_matches(|c| matches!(c, '"' | '`' | '[' | ']'))
+}
+
+/// the text of tenant value, so `1i32`, `1i64` and the literal `'1'` are the same tenant
+fn tenant_text(v: &Value) -> String {
+    match v {
+        Value::String(s) => s.clone(),
+        _ => v.to_string(),
+    }
+}
+
+#[derive(Debug)]
+struct TableRef {
+    /// the table name in sql, for example `"public"."orders"`
+    raw: String,
+    /// the table name without schema and quotes, for example `orders`
+    name: String,
+    alias: Option<String>,
+    start: usize,
+    end: usize,
+}
+
+impl TableRef {
+    /// the qualifier of tenant column, `qualify` = qualify it by the table name if no alias
+    fn qualifier(&self, qualify: bool) -> String {
+        match &self.alias {
+            Some(alias) => format!("{}.", alias),
+            None if qualify => format!("{}.", self.raw),
+            None => String::new(),
+        }
+    }
+}
+
+/// read the word at `i`, return (word, end)
+fn read_word(sql: &str, i: usize) -> (&str, usize) {
+    let bytes = sql.as_bytes();
+    let s = skip_whitespace(bytes, i);
+    let mut e = s;
+    while e < bytes.len() && is_ident_char(bytes[e]) {
+        e += 1;
+    }
+    (&sql[s..e], e)
+}
+
+/// read the table(and alias) at `i`
+fn read_table(sql: &str, i: usize) -> Option<TableRef> {
+    let bytes = sql.as_bytes();
+    let start = skip_whitespace(bytes, i);
+    let mut end = start;
+    while end < bytes.len() && !bytes[end].is_ascii_whitespace() && !b"(,;".contains(&bytes[end]) {
+        end += 1;
+    }
+    if end == start {
+        return None;
+    }
+    let raw = &sql[start..end];
+    let name = unquote(raw.rsplit('.').next().unwrap_or(raw)).to_string();
+    let (mut word, mut word_end) = read_word(sql, end);
+    if word.eq_ignore_ascii_case("as") {
+        (word, word_end) = read_word(sql, word_end);
+    }
+    let mut alias = None;
+    if !word.is_empty() && !KEYWORDS.contains(&word.to_ascii_lowercase().as_str()) {
+        alias = Some(word.to_string());
+        end = word_end;
+    }
+    Some(TableRef {
+        raw: raw.to_string(),
+        name,
+        alias,
+        start,
+        end,
+    })
+}
+
+/// the table joined to the main table, by `join` or in the `from a, b` list
+struct JoinedTable {
+    table: TableRef,
+    /// `left join`
+    left: bool,
+    /// `right join` or `full join`
+    outer: bool,
+    /// the (start, end) of the `on` conditions
+    on: Option<(usize, usize)>,
+}
+
+/// the end of the `on` conditions at `from`: the next join, `where`, `set` or the end words
+fn on_end(sql: &str, lower: &str, mask: &[bool], from: usize) -> usize {
+    let mut end = sql.trim_end().trim_end_matches(';').trim_end().len();
+    for word in JOIN_WORDS
+        .iter()
+        .chain(&["join", "where", "set"])
+        .chain(END_WORDS.iter())
+    {
+        let is_function = |p: usize| {
+            matches!(*word, "left" | "right")
+                && lower
+                    .as_bytes()
+                    .get(skip_whitespace(lower.as_bytes(), p + word.len()))
+                    == Some(&b'(')
+        };
+        if let Some(p) = top_level_words(lower, mask, word)
+            .into_iter()
+            .find(|p| *p >= from && !is_function(*p))
+        {
+            end = end.min(p);
+        }
+    }
+    from + sql[from..end.max(from)].trim_end().len()
+}
+
+/// the tables joined to the main table(ends at `i`), the tables in sub query are not included
+fn joined_tables(sql: &str, lower: &str, mask: &[bool], mut i: usize) -> Vec<JoinedTable> {
+    let bytes = sql.as_bytes();
+    let mut result = vec![];
+    loop {
+        let p = skip_whitespace(bytes, i);
+        let mut kinds = vec![];
+        let table = if bytes.get(p) == Some(&b',') {
+            read_table(sql, p + 1)
+        } else {
+            let mut q = p;
+            loop {
+                let (word, end) = read_word(lower, q);
+                if !JOIN_WORDS.contains(&word) {
+                    break;
+                }
+                kinds.push(word);
+                q = end;
+            }
+            match read_word(lower, q) {
+                ("join", end) => read_table(sql, end),
+                _ => None,
+            }
+        };
+        let table = match table {
+            Some(table) => table,
+            None => break,
+        };
+        i = table.end;
+        let mut on = None;
+        match read_word(lower, table.end) {
+            ("on", end) => {
+                i = on_end(sql, lower, mask, end);
+                on = Some((end, i));
+            }
+            ("using", end) => {
+                if let Some((_, close)) = tuples(sql, end).first() {
+                    i = close + 1;
+                }
+            }
+            _ => {}
+        }
+        result.push(JoinedTable {
+            table,
+            left: kinds.contains(&"left"),
+            outer: kinds.contains(&"right") || kinds.contains(&"full"),
+            on,
+        });
+    }
+    result
+}
+
+/// the (open, close) positions of tuples `(..),(..)` from `i`
+fn tuples(sql: &str, mut i: usize) -> Vec<(usize, usize)> {
+    let bytes = sql.as_bytes();
+    let mut result = vec![];
+    loop {
+        i = skip_whitespace(bytes, i);
+        if bytes.get(i) != Some(&b'(') {
+            break;
+        }
+        let open = i;
+        let mut depth = 0;
+        let mut quote = false;
+        while i < bytes.len() {
+            let c = bytes[i];
+            if quote {
+                if c == b'\'' {
+                    quote = false;
+                }
+            } else if c == b'\'' {
+                quote = true;
+            } else if c == b'(' {
+                depth += 1;
+            } else if c == b')' {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            i += 1;
+        }
+        if i >= bytes.len() {
+            break;
+        }
+        result.push((open, i));
+        i = skip_whitespace(bytes, i + 1);
+        if bytes.get(i) == Some(&b',') {
+            i += 1;
+        } else {
+            break;
+        }
+    }
+    result
+}
+
+/// the (start, end) of elements in the tuple `(a, b, c)`
+fn tuple_elements(sql: &str, open: usize, close: usize) -> Vec<(usize, usize)> {
+    let bytes = sql.as_bytes();
+    let mut result = vec![];
+    let mut depth = 0;
+    let mut quote = false;
+    let mut start = open + 1;
+    for (i, c) in bytes.iter().copied().enumerate().take(close).skip(open + 1) {
+        if quote {
+            if c == b'\'' {
+                quote = false;
+            }
+        } else if c == b'\'' {
+            quote = true;
+        } else if c == b'(' {
+            depth += 1;
+        } else if c == b')' {
+            depth -= 1;
+        } else if c == b',' && depth == 0 {
+            result.push((start, i));
+            start = i + 1;
+        }
+    }
+    result.push((start, close));
+    result
+}
+
+/// filter the rows of tables by the tenant of `with_tenant()`.
+///
+/// * `select`/`update`/`delete` add the condition `tenant_id = ?` to the where of the main table
+///   and the joined tables, or to the `on` of the left joined tables
+/// * `insert` add the column `tenant_id`, or fill it if the value is null
+/// * `insert` values and `update .. set tenant_id = ?` of the other tenant return error, null is filled
+/// * `bypass_tenant()` skip the filter, for example the admin jobs
+/// * if the tenant is not set, the sql on tenant tables return error(unless `required(false)`)
+/// * the tenant tables can not be filtered return error: the main table can not be resolved
+///   (for example `from (select ..) t`), in the sub query/union/`insert .. select`, or in the
+///   right/full join
+///
+/// please insert it at the front of intercepts, so the log show the filtered sql.
+/// how to use?
+/// ```rust
+/// use std::sync::Arc;
+/// use rbatis::RBatis;
+/// use rbatis::intercept_tenant::{with_tenant, TenantIntercept};
+///
+/// async fn run(rb: &RBatis) {
+///     rb.intercepts.insert(
+///         0,
+///         Arc::new(TenantIntercept::new().ignore_tables(vec!["sys_config"])),
+///     );
+///     with_tenant(1, async {
+///         // select * from orders where tenant_id = ?
+///         rb.query("select * from orders", vec![]).await
+///     })
+///     .await;
+/// }
+/// ```
+#[derive(Debug)]
+pub struct TenantIntercept {
+    /// the tenant column, default `tenant_id`
+    pub column: String,
+    /// the tenant tables, empty = all tables
+    pub tables: Vec<String>,
+    /// the tables without tenant
+    pub ignore_tables: Vec<String>,
+    /// return error if the tenant is not set, default true
+    pub required: bool,
+}
+
+impl Default for TenantIntercept {
+    fn default() -> Self {
+        Self {
+            column: "tenant_id".to_string(),
+            tables: vec![],
+            ignore_tables: vec![],
+            required: true,
+        }
+    }
+}
+
+impl TenantIntercept {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn column(mut self, column: &str) -> Self {
+        self.column = column.to_string();
+        self
+    }
+
+    /// opt in tables, the other tables are not filtered
+    pub fn tables<S: ToString>(mut self, tables: Vec<S>) -> Self {
+        self.tables = tables.into_iter().map(|v| v.to_string()).collect();
+        self
+    }
+
+    /// opt out tables
+    pub fn ignore_tables<S: ToString>(mut self, tables: Vec<S>) -> Self {
+        self.ignore_tables = tables.into_iter().map(|v| v.to_string()).collect();
+        self
+    }
+
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// is the table filtered by tenant?
+    pub fn is_tenant_table(&self, table: &str) -> bool {
+        if self
+            .ignore_tables
+            .iter()
+            .any(|t| t.eq_ignore_ascii_case(table))
+        {
+            return false;
+        }
+        self.tables.is_empty() || self.tables.iter().any(|t| t.eq_ignore_ascii_case(table))
+    }
+
+    /// is the sql on tables but the main table can not be resolved?
+    /// for example `select * from (select * from orders) t`
+    fn is_unresolved(&self, lower: &str) -> bool {
+        let trimmed = lower.trim_start();
+        if !["select", "insert", "update", "delete", "with"]
+            .iter()
+            .any(|v| trimmed.starts_with(v))
+        {
+            return false;
+        }
+        let masked = mask_literals(lower);
+        if !["from", "into", "update"]
+            .iter()
+            .any(|v| !find_words(&masked, v).is_empty())
+        {
+            return false;
+        }
+        self.tables.is_empty()
+            || self
+                .tables
+                .iter()
+                .any(|t| !find_words(&masked, &t.to_ascii_lowercase()).is_empty())
+    }
+
+    /// the main table of sql, None = not select/update/delete/insert
+    fn main_table(sql: &str, lower: &str, mask: &[bool]) -> Option<(TableRef, bool)> {
+        let trimmed = lower.trim_start();
+        let start = lower.len() - trimmed.len();
+        if trimmed.starts_with("insert") {
+            let into = *top_level_words(lower, mask, "into").first()?;
+            return Some((read_table(sql, into + 4)?, true));
+        }
+        if trimmed.starts_with("update") {
+            return Some((read_table(sql, start + 6)?, false));
+        }
+        if trimmed.starts_with("select") || trimmed.starts_with("delete") {
+            let from = *top_level_words(lower, mask, "from").first()?;
+            return Some((read_table(sql, from + 4)?, false));
+        }
+        None
+    }
+
+    /// return error if a tenant table is referenced outside the main and joined `tables`,
+    /// for example in the sub query, union or the select of `insert .. select`
+    fn check_unfiltered(&self, sql: &str, tables: &[&TableRef]) -> Result<(), Error> {
+        for (p, word, qualifier) in table_words(sql) {
+            if qualifier
+                || KEYWORDS.contains(&word.as_str())
+                || matches!(word.as_str(), "select" | "values" | "lateral")
+                || !self.is_tenant_table(&word)
+                || tables
+                    .iter()
+                    .any(|t| p >= t.start && p < t.start + t.raw.len())
+            {
+                continue;
+            }
+            return Err(Error::from(format!(
+                "[rb] tenant can not filter the table '{}' of sub query/union/insert select, please run it in bypass_tenant(): {}",
+                word, sql
+            )));
+        }
+        Ok(())
+    }
+
+    /// add `column = ?` of the `tables` to the where of main `table`, return (sql, the index of args)
+    fn add_condition(
+        &self,
+        sql: &str,
+        lower: &str,
+        mask: &[bool],
+        table: &TableRef,
+        tables: &[(&TableRef, String)],
+    ) -> (String, usize) {
+        let condition = tables
+            .iter()
+            .map(|(_, qualifier)| format!("{}{} = ?", qualifier, self.column))
+            .collect::<Vec<_>>()
+            .join(" and ");
+        let where_pos = top_level_words(lower, mask, "where")
+            .into_iter()
+            .find(|p| *p >= table.end);
+        let from = where_pos.unwrap_or(table.end);
+        let mut end = sql.trim_end().trim_end_matches(';').trim_end().len();
+        for word in END_WORDS {
+            if let Some(p) = top_level_words(lower, mask, word)
+                .into_iter()
+                .find(|p| *p >= from)
+            {
+                end = end.min(p);
+            }
+        }
+        let placeholders = placeholder_positions(sql);
+        let tail = sql[end..].trim_start();
+        let sep = if tail.is_empty() { "" } else { " " };
+        match where_pos {
+            Some(p) => {
+                let head_end = p + "where".len();
+                let new_sql = format!(
+                    "{} {} and ({}){}{}",
+                    &sql[..head_end],
+                    condition,
+                    sql[head_end..end].trim(),
+                    sep,
+                    tail
+                );
+                let index = placeholders.iter().filter(|v| **v < head_end).count();
+                (new_sql, index)
+            }
+            None => {
+                let new_sql = format!(
+                    "{} where {}{}{}",
+                    sql[..end].trim_end(),
+                    condition,
+                    sep,
+                    tail
+                );
+                let index = placeholders.iter().filter(|v| **v < end).count();
+                (new_sql, index)
+            }
+        }
+    }
+
+    /// add `column = ?` to the `on` conditions at `(start, end)` of the left join `table`,
+    /// return (sql, the index of arg)
+    fn add_on_condition(
+        &self,
+        sql: &str,
+        (s, e): (usize, usize),
+        table: &TableRef,
+    ) -> (String, usize) {
+        let new_sql = format!(
+            "{} {}{} = ? and ({}){}",
+            &sql[..s],
+            table.qualifier(true),
+            self.column,
+            sql[s..e].trim(),
+            &sql[e..]
+        );
+        let index = placeholder_positions(sql)
+            .iter()
+            .filter(|p| **p < s)
+            .count();
+        (new_sql, index)
+    }
+
+    /// check the value of tenant column(the element of insert or `set` of update) at `(start, end)`:
+    /// fill the null arg by the tenant, the other tenant return error
+    fn check_value(
+        &self,
+        sql: &str,
+        (s, e): (usize, usize),
+        placeholders: &[usize],
+        args: &mut [Value],
+        table: &TableRef,
+        tenant: &Value,
+    ) -> Result<(), Error> {
+        let s = skip_whitespace(sql.as_bytes(), s);
+        let text = sql[s..e].trim();
+        let value = if text == "?" {
+            match placeholders.iter().position(|p| *p == s) {
+                Some(i) if i < args.len() => {
+                    if args[i] == Value::Null {
+                        args[i] = tenant.clone();
+                        return Ok(());
+                    }
+                    tenant_text(&args[i])
+                }
+                _ => text.to_string(),
+            }
+        } else {
+            text.trim_matches('\'').to_string()
+        };
+        if value != tenant_text(tenant) {
+            return Err(Error::from(format!(
+                "[rb] tenant column '{}' of table '{}' is set to the other tenant: {}",
+                self.column, table.name, value
+            )));
+        }
+        Ok(())
+    }
+
+    /// check the tenant column in the `set` of update
+    fn check_update_set(
+        &self,
+        sql: &str,
+        lower: &str,
+        mask: &[bool],
+        args: &mut [Value],
+        table: &TableRef,
+        tenant: &Value,
+    ) -> Result<(), Error> {
+        let set = match top_level_words(lower, mask, "set")
+            .into_iter()
+            .find(|p| *p >= table.end)
+        {
+            Some(p) => p + "set".len(),
+            None => return Ok(()),
+        };
+        let mut end = sql.len();
+        for word in END_WORDS.iter().chain(&["where"]) {
+            if let Some(p) = top_level_words(lower, mask, word)
+                .into_iter()
+                .find(|p| *p >= set)
+            {
+                end = end.min(p);
+            }
+        }
+        let placeholders = placeholder_positions(sql);
+        let mut start = set;
+        for i in set..=end {
+            if i < end && !(mask[i] && sql.as_bytes()[i] == b',') {
+                continue;
+            }
+            let assign = &sql[start..i];
+            if let Some(eq) = assign.find('=') {
+                let column = assign[..eq].trim();
+                let column = unquote(column.rsplit('.').next().unwrap_or(column));
+                if column.eq_ignore_ascii_case(&self.column) {
+                    self.check_value(sql, (start + eq + 1, i), &placeholders, args, table, tenant)?;
+                }
+            }
+            start = i + 1;
+        }
+        Ok(())
+    }
+
+    /// add the column to insert, or fill the null value of column
+    fn fill_insert(
+        &self,
+        sql: &mut String,
+        args: &mut Vec<Value>,
+        table: &TableRef,
+        tenant: Value,
+    ) -> Result<(), Error> {
+        let no_columns = || {
+            Error::from(format!(
+                "[rb] tenant insert into '{}' must have the column list",
+                table.name
+            ))
+        };
+        let columns = *tuples(sql, table.end).first().ok_or_else(no_columns)?;
+        let lower = sql.to_ascii_lowercase();
+        let values = *find_words(&lower[columns.1..], "values")
+            .first()
+            .ok_or_else(no_columns)?
+            + columns.1
+            + "values".len();
+        let rows = tuples(sql, values);
+        let placeholders = placeholder_positions(sql);
+        let column_index = tuple_elements(sql, columns.0, columns.1)
+            .into_iter()
+            .position(|(s, e)| unquote(sql[s..e].trim()).eq_ignore_ascii_case(&self.column));
+        match column_index {
+            Some(index) => {
+                for (open, close) in rows {
+                    let element = tuple_elements(sql, open, close)[index];
+                    self.check_value(sql, element, &placeholders, args, table, &tenant)?;
+                }
+            }
+            None => {
+                let mut new_sql = String::with_capacity(sql.len() + 16 + rows.len() * 2);
+                new_sql.push_str(&sql[..columns.1]);
+                new_sql.push(',');
+                new_sql.push_str(&self.column);
+                let mut last = columns.1;
+                for (inserted, (_, close)) in rows.into_iter().enumerate() {
+                    new_sql.push_str(&sql[last..close]);
+                    new_sql.push_str(",?");
+                    last = close;
+                    let index = placeholders.iter().filter(|p| **p < close).count() + inserted;
+                    args.insert(index, tenant.clone());
+                }
+                new_sql.push_str(&sql[last..]);
+                *sql = new_sql;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Intercept for TenantIntercept {
+    async fn before(
+        &self,
+        _task_id: i64,
+        _rb: &dyn Executor,
+        sql: &mut String,
+        args: &mut Vec<Value>,
+        _result: ResultType<&mut Result<ExecResult, Error>, &mut Result<Value, Error>>,
+    ) -> Result<Action, Error> {
+        if is_bypass_tenant() {
+            return Ok(Action::Next);
+        }
+        let tenant = current_tenant();
+        if tenant.is_none() && !self.required {
+            return Ok(Action::Next);
+        }
+        let lower = sql.to_ascii_lowercase();
+        let mask = top_level_mask(sql);
+        let (table, is_insert) = match Self::main_table(sql, &lower, &mask) {
+            Some(v) => v,
+            None => {
+                if self.is_unresolved(&lower) {
+                    return Err(Error::from(format!(
+                        "[rb] tenant can not resolve the main table of sql, please run it in bypass_tenant(): {}",
+                        sql
+                    )));
+                }
+                return Ok(Action::Next);
+            }
+        };
+        let joined = if is_insert {
+            vec![]
+        } else {
+            joined_tables(sql, &lower, &mask, table.end)
+        };
+        let tables: Vec<&TableRef> = std::iter::once(&table)
+            .chain(joined.iter().map(|v| &v.table))
+            .collect();
+        self.check_unfiltered(sql, &tables)?;
+        let filter_main = self.is_tenant_table(&table.name);
+        let filter_joined: Vec<&JoinedTable> = joined
+            .iter()
+            .filter(|v| self.is_tenant_table(&v.table.name))
+            .collect();
+        if !filter_main && filter_joined.is_empty() {
+            return Ok(Action::Next);
+        }
+        let tenant = match tenant {
+            Some(tenant) => tenant,
+            None => {
+                let name = match filter_main {
+                    true => &table.name,
+                    false => &filter_joined[0].table.name,
+                };
+                return Err(Error::from(format!(
+                    "[rb] tenant not set for table '{}', please run in with_tenant() or bypass_tenant()",
+                    name
+                )));
+            }
+        };
+        if is_insert {
+            self.fill_insert(sql, args, &table, tenant)?;
+            return Ok(Action::Next);
+        }
+        if joined.iter().any(|v| v.outer) {
+            return Err(Error::from(format!(
+                "[rb] tenant can not filter the right/full join, please run it in bypass_tenant(): {}",
+                sql
+            )));
+        }
+        if let Some(v) = filter_joined.iter().find(|v| v.left && v.on.is_none()) {
+            return Err(Error::from(format!(
+                "[rb] tenant can not filter the left join of table '{}' without `on`, please run it in bypass_tenant(): {}",
+                v.table.name, sql
+            )));
+        }
+        if filter_main {
+            self.check_update_set(sql, &lower, &mask, args, &table, &tenant)?;
+        }
+        // the inner joined tables are filtered in the where, the left joined tables in the `on`
+        let qualify = !joined.is_empty();
+        let mut where_tables = vec![];
+        if filter_main {
+            where_tables.push((&table, table.qualifier(qualify)));
+        }
+        for v in filter_joined.iter().filter(|v| !v.left) {
+            where_tables.push((&v.table, v.table.qualifier(qualify)));
+        }
+        if !where_tables.is_empty() {
+            let (new_sql, index) = self.add_condition(sql, &lower, &mask, &table, &where_tables);
+            *sql = new_sql;
+            for _ in 0..where_tables.len() {
+                args.insert(index, tenant.clone());
+            }
+        }
+        // the `on` is before the where, so the positions are not changed by the where
+        for v in filter_joined.iter().rev().filter(|v| v.left) {
+            if let Some(on) = v.on {
+                let (new_sql, index) = self.add_on_condition(sql, on, &v.table);
+                *sql = new_sql;
+                args.insert(index, tenant.clone());
+            }
+        }
+        Ok(Action::Next)
+    }
+}
@@ -1,5 +1,9 @@
 pub mod intercept_log;
 pub mod intercept_page;
+pub mod intercept_read_write;
+pub mod intercept_sharding;
+pub mod intercept_sql_guard;
+pub mod intercept_tenant;
 
 use crate::executor::Executor;
 use crate::Error;
@@ -96,6 +100,117 @@ pub trait Intercept: Any + Send + Sync + Debug {
     ) -> Result<Action, Error> {
         Ok(Action::Next)
     }
+
+    /// called when the stream of `query_stream()`/`fetch_stream()` ends,
+    /// result is the rows count or the error of the stream.
+    /// return Err will be the last item of the stream.
+    async fn after_stream(
+        &self,
+        _task_id: i64,
+        _rb: &dyn Executor,
+        _sql: &str,
+        _args: &[Value],
+        _result: &Result<u64, Error>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// choose the datasource registered by `RBatis::init_ds()` to run the query, None = not route.
+    /// it is called after all `before()` with the final sql, the query run on a connection
+    /// of the datasource bounded by the executor timeout, then `after()` run as normal.
+    /// if the datasource fails, the query fall back to the executor connection.
+    ///
+    /// only the query of `RBatisConnExecutor`(and `RBatis`) is routed,
+    /// the transaction and `query_stream()` always run on the executor connection.
+    fn route(&self, _task_id: i64, _rb: &dyn Executor, _sql: &str) -> Option<String> {
+        None
+    }
+
+    /// called after the transaction begin,
+    /// task_id is tx_id.
+    /// if return an error, the transaction will be rollback and the begin return the error.
+    async fn on_begin(&self, _task_id: i64, _rb: &dyn Executor) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// called after the transaction commit,
+    /// task_id is tx_id.
+    /// the savepoint release of nested tx will not call this.
+    /// the transaction is already committed, so the error is only logged.
+    async fn on_commit(&self, _task_id: i64, _rb: &dyn Executor) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// called after the transaction rollback,
+    /// task_id is tx_id.
+    /// the rollback to savepoint of nested tx will not call this.
+    /// the transaction is already rollback, so the error is only logged.
+    async fn on_rollback(&self, _task_id: i64, _rb: &dyn Executor) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Run after_stream of all interceptors.
+pub async fn apply_after_stream(
+    intercepts: &SyncVec<Arc<dyn Intercept>>,
+    id: i64,
+    executor: &dyn Executor,
+    sql: &str,
+    args: &[Value],
+    result: &Result<u64, Error>,
+) -> Result<(), Error> {
+    for item in intercepts.iter() {
+        item.after_stream(id, executor, sql, args, result).await?;
+    }
+    Ok(())
+}
+
+/// Run route of all interceptors, return the first datasource chosen.
+pub fn apply_route(
+    intercepts: &SyncVec<Arc<dyn Intercept>>,
+    id: i64,
+    executor: &dyn Executor,
+    sql: &str,
+) -> Option<String> {
+    intercepts
+        .iter()
+        .find_map(|item| item.route(id, executor, sql))
+}
+
+/// Run on_begin of all interceptors.
+pub async fn apply_begin(
+    intercepts: &SyncVec<Arc<dyn Intercept>>,
+    id: i64,
+    executor: &dyn Executor,
+) -> Result<(), Error> {
+    for item in intercepts.iter() {
+        item.on_begin(id, executor).await?;
+    }
+    Ok(())
+}
+
+/// Run on_commit of all interceptors.
+pub async fn apply_commit(
+    intercepts: &SyncVec<Arc<dyn Intercept>>,
+    id: i64,
+    executor: &dyn Executor,
+) -> Result<(), Error> {
+    for item in intercepts.iter() {
+        item.on_commit(id, executor).await?;
+    }
+    Ok(())
+}
+
+/// Run on_rollback of all interceptors.
+pub async fn apply_rollback(
+    intercepts: &SyncVec<Arc<dyn Intercept>>,
+    id: i64,
+    executor: &dyn Executor,
+) -> Result<(), Error> {
+    for item in intercepts.iter() {
+        item.on_rollback(id, executor).await?;
+    }
+    Ok(())
 }
 
 /// Run before-interceptors. Returns `true` if an interceptor returned `Action::Return`.
@@ -1,8 +1,10 @@
+pub mod fill;
 pub mod id_generator;
 pub mod intercept;
 pub mod page;
 pub mod table_sync;
 
+pub use fill::*;
 pub use id_generator::*;
 pub use intercept::*;
 pub use page::*;
@@ -0,0 +1,258 @@
+use crate::Error;
+use fast_pool::plugin::{CheckMode, DurationConnection, DurationManager};
+use futures_core::future::BoxFuture;
+use futures_core::stream::BoxStream;
+use rbdc::db::{ConnectOptions, Connection, Driver, ExecResult, Row};
+use rbdc::pool::{ConnectionGuard, ConnectionManager, Pool};
+use rbdc_pool_fast::ConnManagerProxy;
+use rbs::value::map::ValueMap;
+use rbs::Value;
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// the default pool, a `fast_pool` pool same as `rbdc_pool_fast::FastPool`.
+///
+/// a connection closed by `close()`(for example the statement timeout discard it) is dropped
+/// from the pool, instead of returned to the idle queue.
+#[derive(Debug, Clone)]
+pub struct DefaultPool {
+    pub manager: Arc<ConnectionManager>,
+    pub inner: fast_pool::Pool<DiscardManager>,
+}
+
+impl DefaultPool {
+    pub fn new_url<D: Driver + 'static>(driver: D, url: &str) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        Self::new(ConnectionManager::new(driver, url)?)
+    }
+
+    pub fn new_option<D: Driver + 'static, Options: ConnectOptions>(
+        driver: D,
+        options: Options,
+    ) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        Self::new(ConnectionManager::new_options(driver, options))
+    }
+}
+
+/// the pool manager, a discarded connection always fails the check,
+/// even the `CheckMode` skip the ping.
+pub struct DiscardManager {
+    pub inner: DurationManager<ConnManagerProxy>,
+}
+
+/// the connection in the pool
+pub struct PoolConnection {
+    conn: DurationConnection<ConnectionGuard>,
+    discarded: bool,
+}
+
+impl fast_pool::Manager for DiscardManager {
+    type Connection = PoolConnection;
+    type Error = Error;
+
+    async fn connect(&self) -> Result<Self::Connection, Self::Error> {
+        Ok(PoolConnection {
+            conn: self.inner.connect().await?,
+            discarded: false,
+        })
+    }
+
+    async fn check(&self, conn: &mut Self::Connection) -> Result<(), Self::Error> {
+        if conn.discarded {
+            return Err(Error::from("[rb] connection is discarded"));
+        }
+        self.inner.check(&mut conn.conn).await
+    }
+}
+
+/// drop the discarded connections in the idle queue, and decrease the pool connections
+fn drop_discarded(pool: &fast_pool::Pool<DiscardManager>) {
+    for _ in 0..pool.idle_send.len() {
+        match pool.idle_recv.try_recv() {
+            Ok(conn) => {
+                if conn.discarded {
+                    // a not checked guard decrease the connections on drop
+                    drop(fast_pool::ConnectionGuard::new(conn, pool.clone()));
+                } else {
+                    _ = pool.idle_send.send(conn);
+                }
+            }
+            Err(_) => break,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ConnProxy {
+    conn: Option<fast_pool::ConnectionGuard<DiscardManager>>,
+    pool: fast_pool::Pool<DiscardManager>,
+}
+
+#[async_trait::async_trait]
+impl Pool for DefaultPool {
+    fn new(manager: ConnectionManager) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        Ok(Self {
+            manager: manager.clone().into(),
+            inner: fast_pool::Pool::new(DiscardManager {
+                inner: DurationManager::new(ConnManagerProxy::new(manager), CheckMode::NoLimit),
+            }),
+        })
+    }
+
+    async fn get(&self) -> Result<Box<dyn Connection>, Error> {
+        let v = self
+            .inner
+            .get_timeout(self.inner.get_timeout_wait())
+            .await
+            .map_err(|e| Error::from(e.to_string()))?;
+        Ok(Box::new(ConnProxy {
+            conn: Some(v),
+            pool: self.inner.clone(),
+        }))
+    }
+
+    async fn get_timeout(&self, mut d: Duration) -> Result<Box<dyn Connection>, Error> {
+        if d.is_zero() {
+            let state = self.inner.state();
+            if state.in_use < state.max_open {
+                d = Duration::from_secs(10);
+            } else {
+                return Err(Error::from("Time out in the connection pool"));
+            }
+        }
+        let v = self
+            .inner
+            .get_timeout(Some(d))
+            .await
+            .map_err(|e| Error::from(e.to_string()))?;
+        Ok(Box::new(ConnProxy {
+            conn: Some(v),
+            pool: self.inner.clone(),
+        }))
+    }
+
+    async fn set_timeout(&self, timeout: Option<Duration>) {
+        self.inner.set_timeout_wait(timeout);
+    }
+
+    async fn set_conn_max_lifetime(&self, max_lifetime: Option<Duration>) {
+        let mode = &self.inner.manager.inner.mode;
+        if let Some(max_lifetime) = max_lifetime {
+            mode.set_mode(CheckMode::MaxLifetime(max_lifetime));
+        } else {
+            mode.set_mode(CheckMode::NoLimit);
+        }
+    }
+
+    async fn set_max_idle_conns(&self, n: u64) {
+        self.inner.set_max_idle_conns(n);
+    }
+
+    async fn set_max_open_conns(&self, n: u64) {
+        self.inner.set_max_open(n);
+    }
+
+    fn driver_type(&self) -> &str {
+        self.manager.driver_type()
+    }
+
+    async fn state(&self) -> Value {
+        let mut m = ValueMap::with_capacity(10);
+        let state = self.inner.state();
+        m.insert("max_open".to_string().into(), state.max_open.into());
+        m.insert("connections".to_string().into(), state.connections.into());
+        m.insert("in_use".to_string().into(), state.in_use.into());
+        m.insert("idle".to_string().into(), state.idle.into());
+        m.insert("waits".to_string().into(), state.waits.into());
+        m.insert("connecting".to_string().into(), state.connecting.into());
+        m.insert("checking".to_string().into(), state.checking.into());
+        Value::Map(m)
+    }
+
+    fn driver(&self) -> &dyn Driver {
+        self.manager.driver.deref()
+    }
+}
+
+impl Connection for ConnProxy {
+    fn exec_rows(
+        &mut self,
+        sql: &str,
+        params: Vec<Value>,
+    ) -> BoxFuture<'_, Result<BoxStream<'_, Result<Box<dyn Row>, Error>>, Error>> {
+        match &mut self.conn {
+            Some(conn) => conn.conn.exec_rows(sql, params),
+            None => Box::pin(async { Err(Error::from("conn is drop")) }),
+        }
+    }
+
+    fn exec_decode(
+        &mut self,
+        sql: &str,
+        params: Vec<Value>,
+    ) -> BoxFuture<'_, Result<Value, Error>> {
+        match &mut self.conn {
+            Some(conn) => conn.conn.exec_decode(sql, params),
+            None => Box::pin(async { Err(Error::from("conn is drop")) }),
+        }
+    }
+
+    fn exec(&mut self, sql: &str, params: Vec<Value>) -> BoxFuture<'_, Result<ExecResult, Error>> {
+        match &mut self.conn {
+            Some(conn) => conn.conn.exec(sql, params),
+            None => Box::pin(async { Err(Error::from("conn is drop")) }),
+        }
+    }
+
+    fn ping(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+        match &mut self.conn {
+            Some(conn) => conn.conn.ping(),
+            None => Box::pin(async { Err(Error::from("conn is drop")) }),
+        }
+    }
+
+    /// close the connection and drop it from the pool, the `ConnProxy` can not be used after close.
+    fn close(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+        Box::pin(async move {
+            let mut conn = self
+                .conn
+                .take()
+                .ok_or_else(|| Error::from("conn is drop"))?;
+            conn.discarded = true;
+            let result = conn.conn.close().await;
+            // already closed, the guard not need close it again on drop
+            conn.conn.auto_close = None;
+            drop(conn);
+            drop_discarded(&self.pool);
+            result
+        })
+    }
+
+    fn begin(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+        match &mut self.conn {
+            Some(conn) => conn.conn.begin(),
+            None => Box::pin(async { Err(Error::from("conn is drop")) }),
+        }
+    }
+    fn commit(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+        match &mut self.conn {
+            Some(conn) => conn.conn.commit(),
+            None => Box::pin(async { Err(Error::from("conn is drop")) }),
+        }
+    }
+    fn rollback(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+        match &mut self.conn {
+            Some(conn) => conn.conn.rollback(),
+            None => Box::pin(async { Err(Error::from("conn is drop")) }),
+        }
+    }
+}
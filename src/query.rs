@@ -0,0 +1,427 @@
+use crate::Error;
+use rbs::Value;
+use serde::Serialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Joiner {
+    And,
+    Or,
+}
+
+#[derive(Clone, Debug)]
+enum Condition {
+    /// (column, operator sql, args)
+    Column(String, String, Vec<Value>),
+    /// (sql, args)
+    Sql(String, Vec<Value>),
+    Group(Query),
+}
+
+/// the typed condition builder of `crud!` `select_by_wrapper`/`update_by_wrapper`/`delete_by_wrapper`/`count_by_wrapper`.
+///
+/// the conditions are joined by `and`, `or(|q| ...)` join a group by `or`.
+/// the column names must be identifiers(`name`, `t.name`), the sql return error for the others,
+/// and the reserved words are quoted by the driver(see `crud_traits::quote_identifier`).
+/// ```rust
+/// use rbatis::Query;
+///
+/// // where name = ? and age > ? and (title like ? or status is null) order by id desc limit 10
+/// let q = Query::new()
+///     .eq("name", "a")
+///     .gt("age", 18)
+///     .and(|q| q.like("title", "%a%").or(|q| q.is_null("status")))
+///     .order_by_desc("id")
+///     .limit(10);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Query {
+    conditions: Vec<(Joiner, Condition)>,
+    columns: Vec<String>,
+    order_by: Vec<(String, bool)>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+}
+
+fn to_value<V: Serialize>(v: V) -> Value {
+    rbs::value(v).unwrap_or_default()
+}
+
+/// check the column is an identifier(`name`, `t.name`, or `*`/`t.*` if `star`) and quote it by the driver
+fn quote_column(driver_type: &str, column: &str, star: bool) -> Result<String, Error> {
+    let mut parts = column.split('.').peekable();
+    while let Some(part) = parts.next() {
+        let last = parts.peek().is_none();
+        if !(crate::crud_traits::is_identifier(part) || (star && last && part == "*")) {
+            return Err(Error::from(format!(
+                "[rb] invalid column '{}' of Query, it must be an identifier",
+                column
+            )));
+        }
+    }
+    Ok(crate::crud_traits::quote_identifier(driver_type, column))
+}
+
+impl Query {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(mut self, joiner: Joiner, condition: Condition) -> Self {
+        self.conditions.push((joiner, condition));
+        self
+    }
+
+    fn column_op(self, column: &str, op: &str, args: Vec<Value>) -> Self {
+        self.push(
+            Joiner::And,
+            Condition::Column(column.to_string(), op.to_string(), args),
+        )
+    }
+
+    /// `column = ?`
+    pub fn eq<V: Serialize>(self, column: &str, v: V) -> Self {
+        self.column_op(column, "= ?", vec![to_value(v)])
+    }
+
+    /// `column <> ?`
+    pub fn ne<V: Serialize>(self, column: &str, v: V) -> Self {
+        self.column_op(column, "<> ?", vec![to_value(v)])
+    }
+
+    /// `column > ?`
+    pub fn gt<V: Serialize>(self, column: &str, v: V) -> Self {
+        self.column_op(column, "> ?", vec![to_value(v)])
+    }
+
+    /// `column >= ?`
+    pub fn ge<V: Serialize>(self, column: &str, v: V) -> Self {
+        self.column_op(column, ">= ?", vec![to_value(v)])
+    }
+
+    /// `column < ?`
+    pub fn lt<V: Serialize>(self, column: &str, v: V) -> Self {
+        self.column_op(column, "< ?", vec![to_value(v)])
+    }
+
+    /// `column <= ?`
+    pub fn le<V: Serialize>(self, column: &str, v: V) -> Self {
+        self.column_op(column, "<= ?", vec![to_value(v)])
+    }
+
+    /// `column like ?`, the pattern is not escaped, for example `%a%`
+    pub fn like<V: Serialize>(self, column: &str, pattern: V) -> Self {
+        self.column_op(column, "like ?", vec![to_value(pattern)])
+    }
+
+    /// `column not like ?`
+    pub fn not_like<V: Serialize>(self, column: &str, pattern: V) -> Self {
+        self.column_op(column, "not like ?", vec![to_value(pattern)])
+    }
+
+    /// `column between ? and ?`
+    pub fn between<V: Serialize>(self, column: &str, from: V, to: V) -> Self {
+        self.column_op(
+            column,
+            "between ? and ?",
+            vec![to_value(from), to_value(to)],
+        )
+    }
+
+    /// `column not between ? and ?`
+    pub fn not_between<V: Serialize>(self, column: &str, from: V, to: V) -> Self {
+        self.column_op(
+            column,
+            "not between ? and ?",
+            vec![to_value(from), to_value(to)],
+        )
+    }
+
+    /// `column is null`
+    pub fn is_null(self, column: &str) -> Self {
+        self.column_op(column, "is null", vec![])
+    }
+
+    /// `column is not null`
+    pub fn is_not_null(self, column: &str) -> Self {
+        self.column_op(column, "is not null", vec![])
+    }
+
+    /// `column in (?, ?)`, the empty values match no row
+    pub fn is_in<V: Serialize>(self, column: &str, values: Vec<V>) -> Self {
+        if values.is_empty() {
+            return self.push(Joiner::And, Condition::Sql("1 = 0".to_string(), vec![]));
+        }
+        let op = format!("in ({})", vec!["?"; values.len()].join(", "));
+        self.column_op(column, &op, values.into_iter().map(to_value).collect())
+    }
+
+    /// `column not in (?, ?)`, the empty values match all rows
+    pub fn not_in<V: Serialize>(self, column: &str, values: Vec<V>) -> Self {
+        if values.is_empty() {
+            return self;
+        }
+        let op = format!("not in ({})", vec!["?"; values.len()].join(", "));
+        self.column_op(column, &op, values.into_iter().map(to_value).collect())
+    }
+
+    /// `exists (sub_sql)`, for example `exists("select 1 from item where item.order_id = orders.id and sku = ?", vec![value!("a")])`
+    pub fn exists(self, sub_sql: &str, args: Vec<Value>) -> Self {
+        self.push(
+            Joiner::And,
+            Condition::Sql(format!("exists ({})", sub_sql), args),
+        )
+    }
+
+    /// `not exists (sub_sql)`
+    pub fn not_exists(self, sub_sql: &str, args: Vec<Value>) -> Self {
+        self.push(
+            Joiner::And,
+            Condition::Sql(format!("not exists ({})", sub_sql), args),
+        )
+    }
+
+    /// `and (group)`
+    pub fn and<F: FnOnce(Query) -> Query>(self, f: F) -> Self {
+        let group = f(Query::new());
+        self.push(Joiner::And, Condition::Group(group))
+    }
+
+    /// `or (group)`
+    pub fn or<F: FnOnce(Query) -> Query>(self, f: F) -> Self {
+        let group = f(Query::new());
+        self.push(Joiner::Or, Condition::Group(group))
+    }
+
+    /// the select columns(`name`, `t.name`, `*`), default `*`
+    pub fn columns<S: ToString>(mut self, columns: Vec<S>) -> Self {
+        self.columns = columns.into_iter().map(|v| v.to_string()).collect();
+        self
+    }
+
+    /// `order by column asc`
+    pub fn order_by_asc(mut self, column: &str) -> Self {
+        self.order_by.push((column.to_string(), true));
+        self
+    }
+
+    /// `order by column desc`
+    pub fn order_by_desc(mut self, column: &str) -> Self {
+        self.order_by.push((column.to_string(), false));
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// move the conditions into a group `(..)`, so the `and` condition added later applies to all of them,
+    /// for example `a = ? or b = ?` => `(a = ? or b = ?) and deleted = ?`.
+    /// the columns/order by/limit are kept
+    pub(crate) fn group(mut self) -> Self {
+        if self.conditions.iter().any(|(j, _)| *j == Joiner::Or) {
+            let group = Query {
+                conditions: std::mem::take(&mut self.conditions),
+                ..Default::default()
+            };
+            self.conditions.push((Joiner::And, Condition::Group(group)));
+        }
+        self
+    }
+
+    /// is the column used by the conditions?
+    pub fn has_column(&self, column: &str) -> bool {
+        self.conditions.iter().any(|(_, c)| match c {
+            Condition::Column(name, _, _) => name == column,
+            Condition::Sql(_, _) => false,
+            Condition::Group(q) => q.has_column(column),
+        })
+    }
+
+    /// is the conditions empty?
+    pub fn is_empty(&self) -> bool {
+        self.conditions.iter().all(|(_, c)| match c {
+            Condition::Group(q) => q.is_empty(),
+            _ => false,
+        })
+    }
+
+    fn condition_sql(
+        &self,
+        driver_type: &str,
+        sql: &mut String,
+        args: &mut Vec<Value>,
+    ) -> Result<(), Error> {
+        let mut first = true;
+        for (joiner, condition) in &self.conditions {
+            if let Condition::Group(q) = condition {
+                if q.is_empty() {
+                    continue;
+                }
+            }
+            if !first {
+                sql.push_str(match joiner {
+                    Joiner::And => " and ",
+                    Joiner::Or => " or ",
+                });
+            }
+            first = false;
+            match condition {
+                Condition::Column(column, op, a) => {
+                    sql.push_str(&quote_column(driver_type, column, false)?);
+                    sql.push(' ');
+                    sql.push_str(op);
+                    args.extend(a.iter().cloned());
+                }
+                Condition::Sql(s, a) => {
+                    sql.push_str(s);
+                    args.extend(a.iter().cloned());
+                }
+                Condition::Group(q) => {
+                    sql.push('(');
+                    q.condition_sql(driver_type, sql, args)?;
+                    sql.push(')');
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// the where sql, for example ` where name = ? and age > ?`, empty = no condition
+    pub fn where_sql(&self, driver_type: &str) -> Result<(String, Vec<Value>), Error> {
+        let mut sql = String::new();
+        let mut args = vec![];
+        if !self.is_empty() {
+            sql.push_str(" where ");
+            self.condition_sql(driver_type, &mut sql, &mut args)?;
+        }
+        Ok((sql, args))
+    }
+
+    /// the order by and limit sql of the driver
+    pub fn order_limit_sql(&self, driver_type: &str) -> Result<String, Error> {
+        let mut sql = String::new();
+        if !self.order_by.is_empty() {
+            sql.push_str(" order by ");
+            let mut order = vec![];
+            for (column, asc) in &self.order_by {
+                order.push(format!(
+                    "{} {}",
+                    quote_column(driver_type, column, false)?,
+                    if *asc { "asc" } else { "desc" }
+                ));
+            }
+            sql.push_str(&order.join(", "));
+        }
+        if self.limit.is_none() && self.offset.is_none() {
+            return Ok(sql);
+        }
+        let offset = self.offset.unwrap_or(0);
+        if driver_type == "mssql" {
+            //mssql must have `order by`
+            if self.order_by.is_empty() {
+                sql.push_str(" order by (select null)");
+            }
+            sql.push_str(&format!(" offset {} rows", offset));
+            if let Some(limit) = self.limit {
+                sql.push_str(&format!(" fetch next {} rows only", limit));
+            }
+        } else {
+            match self.limit {
+                Some(limit) => sql.push_str(&format!(" limit {}", limit)),
+                None if driver_type == "sqlite" => sql.push_str(" limit -1"),
+                None if driver_type == "mysql" => sql.push_str(" limit 18446744073709551615"),
+                None => {}
+            }
+            if offset > 0 {
+                sql.push_str(&format!(" offset {}", offset));
+            }
+        }
+        Ok(sql)
+    }
+
+    /// `select columns from table where ... order by ... limit ...`
+    pub fn select_sql(
+        &self,
+        table: &str,
+        driver_type: &str,
+    ) -> Result<(String, Vec<Value>), Error> {
+        let columns = if self.columns.is_empty() {
+            "*".to_string()
+        } else {
+            let mut columns = vec![];
+            for column in &self.columns {
+                columns.push(quote_column(driver_type, column, true)?);
+            }
+            columns.join(", ")
+        };
+        let (where_sql, args) = self.where_sql(driver_type)?;
+        let sql = format!(
+            "select {} from {}{}{}",
+            columns,
+            table,
+            where_sql,
+            self.order_limit_sql(driver_type)?
+        );
+        Ok((sql, args))
+    }
+
+    /// `select count(1) as count from table where ...`
+    pub fn count_sql(&self, table: &str, driver_type: &str) -> Result<(String, Vec<Value>), Error> {
+        let (where_sql, args) = self.where_sql(driver_type)?;
+        Ok((
+            format!("select count(1) as count from {}{}", table, where_sql),
+            args,
+        ))
+    }
+
+    /// `update table set col = ? where ...`, the set skip the null values and `skips` columns
+    pub fn update_sql(
+        &self,
+        table: &str,
+        driver_type: &str,
+        set: &Value,
+        skips: &[&str],
+    ) -> Result<(String, Vec<Value>), Error> {
+        let mut sets = vec![];
+        let mut args = vec![];
+        for (k, v) in set {
+            let column = match k.as_str() {
+                Some(column) => column,
+                None => continue,
+            };
+            if *v == Value::Null || skips.contains(&column) {
+                continue;
+            }
+            sets.push(format!("{} = ?", quote_column(driver_type, column, false)?));
+            args.push(v.clone());
+        }
+        if sets.is_empty() {
+            return Err(Error::from(format!(
+                "[rb] update table '{}' has no column to set",
+                table
+            )));
+        }
+        let (where_sql, where_args) = self.where_sql(driver_type)?;
+        args.extend(where_args);
+        Ok((
+            format!("update {} set {}{}", table, sets.join(", "), where_sql),
+            args,
+        ))
+    }
+
+    /// `delete from table where ...`
+    pub fn delete_sql(
+        &self,
+        table: &str,
+        driver_type: &str,
+    ) -> Result<(String, Vec<Value>), Error> {
+        let (where_sql, args) = self.where_sql(driver_type)?;
+        Ok((format!("delete from {}{}", table, where_sql), args))
+    }
+}
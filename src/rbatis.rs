@@ -2,10 +2,14 @@ use crate::executor::{Executor, RBatisConnExecutor, RBatisTxExecutor};
 use crate::intercept::intercept_log::LogInterceptor;
 use crate::intercept::intercept_page::PageIntercept;
 use crate::intercept::Intercept;
-use crate::plugin::{IdGenerator, Snowflake};
+use crate::plugin::{FillHandler, IdGenerator, Snowflake};
 use crate::table_sync::{sync, ColumnMapper};
-use crate::{DefaultPool, Error};
-use dark_std::sync::SyncVec;
+use crate::{
+    DataSourceRouter, DefaultPool, DefaultRouter, Error, RetryPolicy, RouteKind, TxOptions,
+    PRIMARY_DS,
+};
+use dark_std::sync::{SyncHashMap, SyncVec};
+use futures::FutureExt;
 use log::LevelFilter;
 use rbdc::pool::ConnectionManager;
 use rbdc::pool::Pool;
@@ -13,7 +17,9 @@ use rbs::value;
 use serde::Serialize;
 use std::any::Any;
 use std::fmt::Debug;
+use std::future::Future;
 use std::ops::Deref;
+use std::panic::AssertUnwindSafe;
 use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 
@@ -26,6 +32,16 @@ pub struct RBatis {
     pub intercepts: Arc<SyncVec<Arc<dyn Intercept>>>,
     //rb task id gen
     pub task_id_generator: Arc<dyn IdGenerator>,
+    //default timeout of each exec/query
+    pub exec_timeout: Arc<parking_lot::RwLock<Option<Duration>>>,
+    //the named datasources except primary
+    pub datasources: Arc<SyncHashMap<String, Arc<dyn Pool>>>,
+    //choose the datasource of each exec/query
+    pub router: Arc<parking_lot::RwLock<Arc<dyn DataSourceRouter>>>,
+    //the datasource name of use_ds(), None = routed by router
+    pub ds_name: Option<String>,
+    //fill the rows of crud! insert/update
+    pub fill_handler: Arc<parking_lot::RwLock<Option<Arc<dyn FillHandler>>>>,
 }
 
 impl Default for RBatis {
@@ -34,6 +50,11 @@ impl Default for RBatis {
             pool: Arc::new(Default::default()),
             intercepts: Arc::new(SyncVec::new()),
             task_id_generator: Arc::new(Snowflake::default()),
+            exec_timeout: Arc::new(Default::default()),
+            datasources: Arc::new(SyncHashMap::new()),
+            router: Arc::new(parking_lot::RwLock::new(Arc::new(DefaultRouter::new()))),
+            ds_name: None,
+            fill_handler: Arc::new(Default::default()),
         }
     }
 }
@@ -124,6 +145,128 @@ impl RBatis {
         Ok(())
     }
 
+    /// init a named datasource, the name `PRIMARY_DS` is same as `init()`.
+    /// for example:
+    /// ```rust
+    /// use rbatis::RBatis;
+    /// use rbdc_sqlite::SqliteDriver;
+    ///
+    /// let rb = RBatis::new();
+    /// rb.init(SqliteDriver {}, "sqlite://target/sqlite.db").unwrap();
+    /// rb.init_ds("replica-1", SqliteDriver {}, "sqlite://target/sqlite.db").unwrap();
+    /// rb.init_ds("analytics", SqliteDriver {}, "sqlite://target/analytics.db").unwrap();
+    /// ```
+    pub fn init_ds<Driver: rbdc::db::Driver + 'static>(
+        &self,
+        name: &str,
+        driver: Driver,
+        url: &str,
+    ) -> Result<(), Error> {
+        if name == PRIMARY_DS {
+            return self.init(driver, url);
+        }
+        if url.is_empty() {
+            return Err(Error::from("[rb] link url is empty!"));
+        }
+        let mut option = driver.default_option();
+        option.set_uri(url)?;
+        let manager = ConnectionManager {
+            driver: std::sync::Arc::new(Box::new(driver)),
+            option: std::sync::Arc::new(option),
+        };
+        self.init_ds_pool(name, DefaultPool::new(manager)?)
+    }
+
+    /// init a named datasource by pool, the name `PRIMARY_DS` is same as `init_pool()`.
+    pub fn init_ds_pool<Pool: rbdc::pool::Pool + 'static>(
+        &self,
+        name: &str,
+        pool: Pool,
+    ) -> Result<(), Error> {
+        if name == PRIMARY_DS {
+            return self.init_pool(pool);
+        }
+        if self.datasources.contains_key(&name.to_string()) {
+            return Err(Error::from(format!(
+                "[rb] datasource '{}' already initialized",
+                name
+            )));
+        }
+        self.datasources.insert(name.to_string(), Arc::new(pool));
+        Ok(())
+    }
+
+    /// the names of all inited datasources, sorted
+    pub fn ds_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.datasources.iter().map(|(k, _)| k.clone()).collect();
+        if self.pool.get().is_some() {
+            names.push(PRIMARY_DS.to_string());
+        }
+        names.sort();
+        names
+    }
+
+    /// return an RBatis use the named datasource, all exec/query/acquire/tx on it use this datasource.
+    /// for example:
+    /// ```rust
+    /// use rbatis::{Error, RBatis};
+    ///
+    /// async fn test_use_ds(rb: &RBatis) -> Result<(), Error> {
+    ///     let analytics = rb.use_ds("analytics");
+    ///     analytics.exec("delete from report", vec![]).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn use_ds(&self, name: &str) -> RBatis {
+        let mut rb = self.clone();
+        rb.ds_name = Some(name.to_string());
+        rb
+    }
+
+    /// set the `DataSourceRouter`, default is `DefaultRouter`
+    pub fn set_router<R: DataSourceRouter + 'static>(&self, router: R) {
+        *self.router.write() = Arc::new(router);
+    }
+
+    /// set the `FillHandler` of `crud!` insert/update, for example `DefaultFillHandler`
+    pub fn set_fill_handler<H: FillHandler + 'static>(&self, handler: H) {
+        *self.fill_handler.write() = Some(Arc::new(handler));
+    }
+
+    /// get the `FillHandler`, None = not set
+    pub fn get_fill_handler(&self) -> Option<Arc<dyn FillHandler>> {
+        self.fill_handler.read().clone()
+    }
+
+    /// the datasource name of the exec/query, None = use `get_pool()`
+    pub fn route_ds(&self, kind: RouteKind, sql: &str) -> Option<String> {
+        if self.ds_name.is_some() || self.datasources.is_empty() {
+            return None;
+        }
+        let router = self.router.read().clone();
+        router.route(self, kind, sql)
+    }
+
+    /// get the pool of the exec/query routed by `DataSourceRouter`
+    pub fn get_route_pool(&self, kind: RouteKind, sql: &str) -> Result<&dyn Pool, Error> {
+        match self.route_ds(kind, sql) {
+            None => self.get_pool(),
+            Some(name) => self.get_ds_pool(&name),
+        }
+    }
+
+    /// get an DataBase Connection of the exec/query routed by `DataSourceRouter`
+    pub async fn acquire_route(
+        &self,
+        kind: RouteKind,
+        sql: &str,
+    ) -> Result<RBatisConnExecutor, Error> {
+        match self.route_ds(kind, sql) {
+            None => self.acquire().await,
+            Some(name) => self.use_ds(&name).acquire().await,
+        }
+    }
+
     /// set_intercepts for many.
     /// notice:
     /// do not forget add PageIntercept!
@@ -144,6 +287,9 @@ impl RBatis {
     /// }
     /// ```
     pub fn get_pool(&self) -> Result<&dyn Pool, Error> {
+        if let Some(name) = &self.ds_name {
+            return self.get_ds_pool(name);
+        }
         let p = self
             .pool
             .get()
@@ -151,6 +297,46 @@ impl RBatis {
         Ok(p.deref())
     }
 
+    /// get the pool of named datasource
+    pub fn get_ds_pool(&self, name: &str) -> Result<&dyn Pool, Error> {
+        if name == PRIMARY_DS {
+            let p = self
+                .pool
+                .get()
+                .ok_or_else(|| Error::from("[rb] rbatis pool not inited!"))?;
+            return Ok(p.deref());
+        }
+        let p = self
+            .datasources
+            .get(name)
+            .ok_or_else(|| Error::from(format!("[rb] datasource '{}' not inited!", name)))?;
+        Ok(p.deref())
+    }
+
+    /// set the default timeout of each exec/query, None = no timeout.
+    ///
+    /// if the timeout elapsed, the call return an error checked by `rbatis::is_timeout()`,
+    /// and the connection is closed and discarded instead of reuse by the pool.
+    /// the executor or tx holding the timeout connection is not usable after that, please acquire a new one.
+    /// the `query_stream()` and the replica query of `ReadWriteSplitIntercept` are bounded too.
+    /// use `RBatisConnExecutor::with_timeout()` to set the timeout of one call.
+    /// for example:
+    /// ```rust
+    /// use std::time::Duration;
+    /// use rbatis::RBatis;
+    ///
+    /// let rb = RBatis::new();
+    /// rb.set_exec_timeout(Some(Duration::from_secs(30)));
+    /// ```
+    pub fn set_exec_timeout(&self, timeout: Option<Duration>) {
+        *self.exec_timeout.write() = timeout;
+    }
+
+    /// get the default timeout of each exec/query
+    pub fn get_exec_timeout(&self) -> Option<Duration> {
+        *self.exec_timeout.read()
+    }
+
     /// get driver type
     pub fn driver_type(&self) -> Result<&str, Error> {
         let pool = self.get_pool()?;
@@ -190,6 +376,27 @@ impl RBatis {
         conn.begin().await
     }
 
+    /// get an DataBase Connection,and begin with isolation level and access mode,used for the next step
+    /// for example:
+    /// ```rust
+    /// use rbatis::{Error, IsolationLevel, RBatis, TxOptions};
+    ///
+    /// async fn test_acquire_begin_option(rb: &RBatis) -> Result<(), Error> {
+    ///     let options = TxOptions::new()
+    ///         .isolation_level(IsolationLevel::RepeatableRead)
+    ///         .read_only();
+    ///     let tx = rb.acquire_begin_option(options).await?;
+    ///     tx.commit().await
+    /// }
+    /// ```
+    pub async fn acquire_begin_option(
+        &self,
+        options: TxOptions,
+    ) -> Result<RBatisTxExecutor, Error> {
+        let conn = self.acquire().await?;
+        conn.begin_option(options).await
+    }
+
     /// try get an DataBase Connection,and call begin method,used for the next step
     pub async fn try_acquire_begin(&self) -> Result<RBatisTxExecutor, Error> {
         let conn = self.try_acquire().await?;
@@ -197,6 +404,137 @@ impl RBatis {
         Ok(executor)
     }
 
+    /// run the closure in a transaction.
+    /// acquire a connection and begin, commit on `Ok`, rollback on `Err` or panic.
+    /// if the closure already call `tx.commit()` or `tx.rollback()`, the tx will not be commit again.
+    /// for example:
+    /// ```rust
+    /// use rbatis::{Error, RBatis};
+    ///
+    /// async fn test_transaction(rb: &RBatis) -> Result<(), Error> {
+    ///     let rows = rb
+    ///         .transaction(|tx| async move {
+    ///             let r = tx.exec("update activity set status = 1", vec![]).await?;
+    ///             Ok(r.rows_affected)
+    ///         })
+    ///         .await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn transaction<F, Fut, T>(&self, f: F) -> Result<T, Error>
+    where
+        F: Fn(RBatisTxExecutor) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        self.transaction_retry(&RetryPolicy::default(), f).await
+    }
+
+    /// same as `transaction()`, but re-run the closure by `RetryPolicy`
+    /// when the driver reports a serialization failure or deadlock.
+    /// for example:
+    /// ```rust
+    /// use rbatis::{Error, RBatis, RetryPolicy};
+    ///
+    /// async fn test_transaction_retry(rb: &RBatis) -> Result<(), Error> {
+    ///     rb.transaction_retry(&RetryPolicy::new(3), |tx| async move {
+    ///         tx.exec("update account set balance = balance - 1 where id = 1", vec![]).await?;
+    ///         Ok(())
+    ///     })
+    ///     .await
+    /// }
+    /// ```
+    pub async fn transaction_retry<F, Fut, T>(&self, policy: &RetryPolicy, f: F) -> Result<T, Error>
+    where
+        F: Fn(RBatisTxExecutor) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        self.transaction_option(TxOptions::default(), policy, f)
+            .await
+    }
+
+    /// same as `transaction_retry()`, but begin the transaction with `TxOptions`.
+    /// for example:
+    /// ```rust
+    /// use rbatis::{Error, IsolationLevel, RBatis, RetryPolicy, TxOptions};
+    ///
+    /// async fn test_transaction_option(rb: &RBatis) -> Result<(), Error> {
+    ///     let options = TxOptions::new().isolation_level(IsolationLevel::Serializable);
+    ///     rb.transaction_option(options, &RetryPolicy::new(3), |tx| async move {
+    ///         tx.exec("update account set balance = balance - 1 where id = 1", vec![]).await?;
+    ///         Ok(())
+    ///     })
+    ///     .await
+    /// }
+    /// ```
+    pub async fn transaction_option<F, Fut, T>(
+        &self,
+        options: TxOptions,
+        policy: &RetryPolicy,
+        f: F,
+    ) -> Result<T, Error>
+    where
+        F: Fn(RBatisTxExecutor) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let result = self.transaction_once(&options, &f).await;
+            match result {
+                Err(e) if policy.can_retry(attempt, &e) => {
+                    log::warn!(
+                        "[rb] transaction retry {}/{}, error: {}",
+                        attempt,
+                        policy.max_retries,
+                        e
+                    );
+                    rbdc::rt::sleep(policy.backoff * attempt).await;
+                }
+                _ => return result,
+            }
+        }
+    }
+
+    async fn transaction_once<F, Fut, T>(&self, options: &TxOptions, f: &F) -> Result<T, Error>
+    where
+        F: Fn(RBatisTxExecutor) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let tx = self.acquire_begin_option(options.clone()).await?;
+        let result = AssertUnwindSafe(async { f(tx.clone()).await })
+            .catch_unwind()
+            .await;
+        match result {
+            Ok(Ok(v)) => {
+                if !tx.done() {
+                    if let Err(e) = tx.commit().await {
+                        _ = tx.rollback().await;
+                        return Err(e);
+                    }
+                }
+                Ok(v)
+            }
+            Ok(Err(e)) => {
+                if !tx.done() {
+                    if let Err(rollback_err) = tx.rollback().await {
+                        log::error!(
+                            "[rb] transaction [{}] rollback fail={}",
+                            tx.tx_id,
+                            rollback_err
+                        );
+                    }
+                }
+                Err(e)
+            }
+            Err(panic) => {
+                if !tx.done() {
+                    _ = tx.rollback().await;
+                }
+                std::panic::resume_unwind(panic)
+            }
+        }
+    }
+
     /// is RBatis enable debug_mode?
     pub fn is_debug_mode(&self) -> bool {
         crate::decode::is_debug_mode()
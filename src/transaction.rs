@@ -0,0 +1,334 @@
+use crate::Error;
+use std::fmt::{Debug, Formatter};
+use std::time::Duration;
+
+/// retry policy of `RBatis::transaction_retry()`.
+///
+/// the closure will be re-run when the driver reports a serialization failure or deadlock,
+/// you can change the check fn by `retry_if()`
+/// for example:
+/// ```rust
+/// use std::time::Duration;
+/// use rbatis::RetryPolicy;
+///
+/// let policy = RetryPolicy::new(3).backoff(Duration::from_millis(100));
+/// ```
+#[derive(Clone)]
+pub struct RetryPolicy {
+    /// max retry times, 0 = not retry
+    pub max_retries: u32,
+    /// sleep before each retry, the n-th retry sleep `n * backoff`
+    pub backoff: Duration,
+    /// check the error can retry
+    pub retry_if: fn(&Error) -> bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Debug for RetryPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_retries", &self.max_retries)
+            .field("backoff", &self.backoff)
+            .finish()
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            backoff: Duration::from_millis(50),
+            retry_if: is_serialization_failure,
+        }
+    }
+
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    pub fn retry_if(mut self, f: fn(&Error) -> bool) -> Self {
+        self.retry_if = f;
+        self
+    }
+
+    /// `attempt` start with 1
+    pub fn can_retry(&self, attempt: u32, e: &Error) -> bool {
+        attempt <= self.max_retries && (self.retry_if)(e)
+    }
+}
+
+/// the error codes(SQLSTATE or driver error number) of serialization failure or deadlock
+const SERIALIZATION_FAILURE_CODES: [&str; 5] = ["40001", "40p01", "1213", "1205", "3960"];
+
+/// the messages of serialization failure or deadlock, matched by whole words
+const SERIALIZATION_FAILURE_MESSAGES: [&[&str]; 7] = [
+    &["deadlock"],
+    &["deadlocked"],
+    &["could", "not", "serialize"],
+    &["serialization", "failure"],
+    &["lock", "wait", "timeout"],
+    &["snapshot", "isolation", "transaction", "aborted"],
+    &["database", "is", "locked"],
+];
+
+/// the words of the error message, the quoted values(for example `Duplicate entry '140001'`) are skipped
+fn error_words(msg: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut word = String::new();
+    let mut quote: Option<char> = None;
+    for c in msg.chars() {
+        if let Some(q) = quote {
+            if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        if c == '\'' || c == '"' || c == '`' {
+            quote = Some(c);
+        }
+        if c.is_alphanumeric() {
+            word.extend(c.to_lowercase());
+        } else if !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// is the error a serialization failure or deadlock reported by driver?
+/// the codes and messages are matched by whole words, outside of the quoted values.
+///
+/// * postgres: `40001` could not serialize access, `40P01` deadlock detected
+/// * mysql: `1213` Deadlock found, `1205` Lock wait timeout
+/// * mssql: `1205` deadlocked, `3960` snapshot isolation update conflict
+/// * sqlite: database is locked
+pub fn is_serialization_failure(e: &Error) -> bool {
+    let words = error_words(&e.to_string());
+    if words
+        .iter()
+        .any(|w| SERIALIZATION_FAILURE_CODES.contains(&w.as_str()))
+    {
+        return true;
+    }
+    SERIALIZATION_FAILURE_MESSAGES.iter().any(|phrase| {
+        words
+            .windows(phrase.len())
+            .any(|w| w.iter().zip(phrase.iter()).all(|(a, b)| a == b))
+    })
+}
+
+/// transaction isolation level
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IsolationLevel {
+    ReadUncommitted,
+    ReadCommitted,
+    RepeatableRead,
+    Serializable,
+}
+
+impl IsolationLevel {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            IsolationLevel::ReadUncommitted => "read uncommitted",
+            IsolationLevel::ReadCommitted => "read committed",
+            IsolationLevel::RepeatableRead => "repeatable read",
+            IsolationLevel::Serializable => "serializable",
+        }
+    }
+}
+
+/// transaction access mode
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessMode {
+    ReadWrite,
+    ReadOnly,
+}
+
+impl AccessMode {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            AccessMode::ReadWrite => "read write",
+            AccessMode::ReadOnly => "read only",
+        }
+    }
+}
+
+/// options of begin transaction, used by `RBatis::acquire_begin_option()` and `RBatisConnExecutor::begin_option()`
+///
+/// the begin sql is chosen by `driver_type()`:
+/// * postgres: `begin isolation level serializable, read only, deferrable`
+/// * mysql: `set transaction isolation level serializable` + `start transaction read only`,
+///   the `set transaction` without `session` only take effect on the next transaction
+/// * mssql: `set transaction isolation level serializable` + `begin tran`,
+///   the isolation level is session scoped, so it is reset to `read committed` after commit/rollback.
+///   `read only` is not supported
+/// * sqlite: always serializable, `begin immediate` for read write or serializable, otherwise `begin deferred`.
+///   `read only` is not supported
+/// * other: `begin` + `set transaction isolation level serializable, read only`,
+///   the `set transaction` in the transaction only take effect on the current transaction
+///
+/// `deferrable` is postgres only, the unsupported options return an error on begin.
+///
+/// for example:
+/// ```rust
+/// use rbatis::{IsolationLevel, TxOptions};
+///
+/// let options = TxOptions::new()
+///     .isolation_level(IsolationLevel::RepeatableRead)
+///     .read_only();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TxOptions {
+    pub isolation_level: Option<IsolationLevel>,
+    pub access_mode: Option<AccessMode>,
+    /// postgres only, take effect with `serializable` and `read only`
+    pub deferrable: bool,
+}
+
+impl TxOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn isolation_level(mut self, level: IsolationLevel) -> Self {
+        self.isolation_level = Some(level);
+        self
+    }
+
+    pub fn access_mode(mut self, mode: AccessMode) -> Self {
+        self.access_mode = Some(mode);
+        self
+    }
+
+    pub fn read_only(self) -> Self {
+        self.access_mode(AccessMode::ReadOnly)
+    }
+
+    pub fn read_write(self) -> Self {
+        self.access_mode(AccessMode::ReadWrite)
+    }
+
+    pub fn deferrable(mut self) -> Self {
+        self.deferrable = true;
+        self
+    }
+
+    /// is all options default? default options will use the driver `begin()`
+    pub fn is_default(&self) -> bool {
+        self == &TxOptions::default()
+    }
+
+    fn modes(&self) -> Vec<String> {
+        let mut modes = vec![];
+        if let Some(level) = &self.isolation_level {
+            modes.push(format!("isolation level {}", level.as_sql()));
+        }
+        if let Some(mode) = &self.access_mode {
+            modes.push(mode.as_sql().to_string());
+        }
+        modes
+    }
+
+    /// check the options are supported by the driver, return an error if not
+    pub fn check(&self, driver_type: &str) -> Result<(), Error> {
+        if self.deferrable && !matches!(driver_type, "postgres" | "pg") {
+            return Err(Error::from(format!(
+                "[rb] the driver '{}' not support deferrable transaction",
+                driver_type
+            )));
+        }
+        if self.access_mode == Some(AccessMode::ReadOnly)
+            && matches!(driver_type, "mssql" | "sqlite")
+        {
+            return Err(Error::from(format!(
+                "[rb] the driver '{}' not support read only transaction",
+                driver_type
+            )));
+        }
+        Ok(())
+    }
+
+    /// the sql run before begin transaction, None = not need
+    pub fn set_sql(&self, driver_type: &str) -> Option<String> {
+        match driver_type {
+            "mysql" | "mssql" => self
+                .isolation_level
+                .map(|level| format!("set transaction isolation level {}", level.as_sql())),
+            _ => None,
+        }
+    }
+
+    /// the sql run after begin transaction, it only set the current transaction. None = not need
+    pub fn after_begin_sql(&self, driver_type: &str) -> Option<String> {
+        match driver_type {
+            "postgres" | "pg" | "mysql" | "mssql" | "sqlite" => None,
+            _ => {
+                let modes = self.modes();
+                if modes.is_empty() {
+                    None
+                } else {
+                    Some(format!("set transaction {}", modes.join(", ")))
+                }
+            }
+        }
+    }
+
+    /// the sql run after commit/rollback, reset the session scoped options. None = not need
+    pub fn reset_sql(&self, driver_type: &str) -> Option<String> {
+        match driver_type {
+            "mssql" => self.isolation_level.map(|_| {
+                format!(
+                    "set transaction isolation level {}",
+                    IsolationLevel::ReadCommitted.as_sql()
+                )
+            }),
+            _ => None,
+        }
+    }
+
+    /// the sql to begin transaction, None = use the driver `begin()`
+    pub fn begin_sql(&self, driver_type: &str) -> Option<String> {
+        if self.is_default() {
+            return None;
+        }
+        match driver_type {
+            "postgres" | "pg" => {
+                let mut modes = self.modes();
+                if self.deferrable {
+                    modes.push("deferrable".to_string());
+                }
+                if modes.is_empty() {
+                    None
+                } else {
+                    Some(format!("begin {}", modes.join(", ")))
+                }
+            }
+            "mysql" => self
+                .access_mode
+                .map(|mode| format!("start transaction {}", mode.as_sql())),
+            "sqlite" => {
+                let immediate = self.access_mode == Some(AccessMode::ReadWrite)
+                    || (self.access_mode.is_none()
+                        && matches!(
+                            self.isolation_level,
+                            Some(IsolationLevel::Serializable | IsolationLevel::RepeatableRead)
+                        ));
+                if immediate {
+                    Some("begin immediate".to_string())
+                } else {
+                    Some("begin deferred".to_string())
+                }
+            }
+            _ => None,
+        }
+    }
+}
@@ -180,6 +180,7 @@ macro_rules! table_field_btree {
 ///}
 ///let name=rbatis::field_name!(MockTable.id);
 /// ```
+/// it does not follow `#[serde(rename)]`, see `#[derive(rbatis::Columns)]` for the column names.
 ///
 #[allow(unused_macros)]
 #[macro_export]
@@ -1 +1,2 @@
-cargo test --workspace
\ No newline at end of file
+cargo test --workspace
+cargo test --workspace --features strict_sql
\ No newline at end of file
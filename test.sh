@@ -1 +1,2 @@
-cargo test --workspace
\ No newline at end of file
+cargo test --workspace
+cargo test -p rbatis -p rbatis-codegen --features strict_sql
\ No newline at end of file
@@ -32,7 +32,7 @@ mod test {
     use std::future::Future;
     use std::pin::Pin;
     use std::str::FromStr;
-    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::sync::atomic::{AtomicI32, AtomicU64, Ordering};
     use std::sync::Arc;
 
     #[derive(Debug)]
@@ -839,12 +839,12 @@ mod test {
             let queue = Arc::new(SyncVec::new());
             rb.set_intercepts(vec![Arc::new(MockIntercept::new(queue.clone()))]);
             rb.init(MockDriver {}, "test").unwrap();
-            let r = MockTable::select_by_map(&rb, value! {"1": ["1", "2"]})
+            let r = MockTable::select_by_map(&rb, value! {"id": ["1", "2"]})
                 .await
                 .unwrap();
             let (sql, args) = queue.pop().unwrap();
             println!("{}", sql);
-            assert_eq!(sql, "select * from mock_table where 1 in (?, ? )");
+            assert_eq!(sql, "select * from mock_table where id in (?, ? )");
             assert_eq!(args, vec![value!("1"), value!("2")]);
         };
         block_on(f);
@@ -857,12 +857,12 @@ mod test {
             let queue = Arc::new(SyncVec::new());
             rb.set_intercepts(vec![Arc::new(MockIntercept::new(queue.clone()))]);
             rb.init(MockDriver {}, "test").unwrap();
-            let r = MockTable::delete_by_map(&rb, value! {"1": ["1", "2"]})
+            let r = MockTable::delete_by_map(&rb, value! {"id": ["1", "2"]})
                 .await
                 .unwrap();
             let (sql, args) = queue.pop().unwrap();
             println!("{}", sql);
-            assert_eq!(sql, "delete from mock_table where 1 in (?, ? )");
+            assert_eq!(sql, "delete from mock_table where id in (?, ? )");
             assert_eq!(args, vec![value!("1"), value!("2")]);
         };
         block_on(f);
@@ -933,7 +933,7 @@ mod test {
         block_on(f);
     }
 
-    rbatis::htmlsql_select_page!(htmlsql_select_page_by_name(name: &str) -> MockTable => r#"<select id="select_page_data">`select `<if test="do_count == true">`count(1) from table`</if><if test="do_count == false">`* from table limit ${page_no},${page_size}`</if></select>"#);
+    rbatis::htmlsql_select_page!(htmlsql_select_page_by_name(name: &str) -> MockTable => r#"<select id="select_page_data">`select `<if test="do_count == true">`count(1) from table`</if><if test="do_count == false">`* from table limit ${safe page_no},${safe page_size}`</if></select>"#);
     #[test]
     fn test_htmlsql_select_page_by_name() {
         let f = async move {
@@ -1015,7 +1015,7 @@ mod test {
           count(1) from table
         </if>
         <if test="do_count == false">
-          * from table limit ${page_no},${page_size}
+          * from table limit ${safe page_no},${safe page_size}
         </if>
        </select>"#);
     #[test]
@@ -1042,7 +1042,7 @@ mod test {
     }
 
     // Test htmlsql_select_page returns data with total > 0
-    rbatis::htmlsql_select_page!(htmlsql_select_page_with_data(name: &str) -> MockTable => r#"<select id="select_page_data">`select `<if test="do_count == true">`count(1) from table`</if><if test="do_count == false">`* from table limit ${page_no},${page_size}`</if></select>"#);
+    rbatis::htmlsql_select_page!(htmlsql_select_page_with_data(name: &str) -> MockTable => r#"<select id="select_page_data">`select `<if test="do_count == true">`count(1) from table`</if><if test="do_count == false">`* from table limit ${safe page_no},${safe page_size}`</if></select>"#);
     #[test]
     fn test_htmlsql_select_page_with_data() {
         let f = async move {
@@ -1390,4 +1390,1441 @@ mod test {
         };
         block_on(f);
     }
+
+    #[test]
+    fn test_select_by_map_with_order_limit() {
+        let f = async move {
+            let mut rb = RBatis::new();
+            let queue = Arc::new(SyncVec::new());
+            rb.set_intercepts(vec![Arc::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let r = MockTable::select_by_map(
+                &rb,
+                value! {"status": 1, "order_by": ["create_time desc", "id"], "limit": 10, "offset": 20},
+            )
+            .await
+            .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select * from mock_table where status = ? order by create_time desc, id asc limit 10 offset 20"
+            );
+            assert_eq!(args, vec![value!(1)]);
+
+            let r = MockTable::select_by_map(
+                &rb,
+                value! {"column": "status", "group_by": "status", "order_by": "status"},
+            )
+            .await
+            .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select status from mock_table group by status order by status asc"
+            );
+            assert!(args.is_empty());
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_select_by_map_with_invalid_order() {
+        let f = async move {
+            let mut rb = RBatis::new();
+            let queue = Arc::new(SyncVec::new());
+            rb.set_intercepts(vec![Arc::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let r = MockTable::select_by_map(&rb, value! {"order_by": "(select 1)"}).await;
+            assert!(r.is_err());
+            let r = MockTable::select_by_map(&rb, value! {"order_by": "not_exist desc"}).await;
+            assert!(r.is_err());
+            let r =
+                MockTable::select_by_map(&rb, value! {"order_by": "id desc; drop table x"}).await;
+            assert!(r.is_err());
+            let r = MockTable::select_by_map(&rb, value! {"group_by": ["id", "1=1"]}).await;
+            assert!(r.is_err());
+            let r = MockTable::select_by_map(&rb, value! {"limit": "10"}).await;
+            assert!(r.is_err());
+            assert!(queue.is_empty());
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_select_page_by_map() {
+        let f = async move {
+            let mut rb = RBatis::new();
+            let queue = Arc::new(SyncVec::new());
+            rb.set_intercepts(vec![
+                Arc::new(PageIntercept::new()),
+                Arc::new(MockIntercept::new(queue.clone())),
+            ]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let r = MockTable::select_page_by_map(
+                &rb,
+                &PageRequest::new(2, 10),
+                value! {"status": 1, "order_by": "id desc"},
+            )
+            .await
+            .unwrap();
+            assert_eq!(r.total, 1);
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select * from mock_table where status = ? order by id desc limit 10,10 "
+            );
+            assert_eq!(args, vec![value!(1)]);
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select count(1) as count from mock_table where status = ?"
+            );
+            assert_eq!(args, vec![value!(1)]);
+
+            let r = MockTable::select_page_by_map(
+                &rb,
+                &PageRequest::new(1, 10),
+                value! {"status": 1, "limit": 1},
+            )
+            .await;
+            assert!(r.is_err());
+
+            // count the groups
+            let r = MockTable::select_page_by_map(
+                &rb,
+                &PageRequest::new(1, 10),
+                value! {"status": 1, "column": "status", "group_by": "status"},
+            )
+            .await
+            .unwrap();
+            let (sql, _) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select status from mock_table where status = ? group by status limit 0,10 "
+            );
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select count(1) as count from (select count(1) as count from mock_table where status = ? group by status) as t"
+            );
+            assert_eq!(args, vec![value!(1)]);
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_select_page_by_wrapper() {
+        let f = async move {
+            let mut rb = RBatis::new();
+            let queue = Arc::new(SyncVec::new());
+            rb.set_intercepts(vec![Arc::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let query = rbatis::Query::new().eq("status", 1).order_by_desc("id");
+            let r = MockTable::select_page_by_wrapper(&rb, &PageRequest::new(3, 5), &query)
+                .await
+                .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select * from mock_table where status = ? order by id desc limit 5 offset 10"
+            );
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select count(1) as count from mock_table where status = ?"
+            );
+        };
+        block_on(f);
+    }
+
+    #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+    struct LogicTable {
+        pub id: Option<String>,
+        pub name: Option<String>,
+        pub delete_flag: Option<i32>,
+    }
+
+    crud!(LogicTable {}, logic_delete = "delete_flag");
+
+    #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+    struct LogicNamedTable {
+        pub id: Option<String>,
+        pub delete_flag: Option<i32>,
+    }
+
+    crud!(
+        LogicNamedTable {},
+        "logic_named",
+        logic_delete = "delete_flag"
+    );
+
+    #[test]
+    fn test_logic_delete_select() {
+        let f = async move {
+            let mut rb = RBatis::new();
+            let queue = Arc::new(SyncVec::new());
+            rb.set_intercepts(vec![Arc::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let r = LogicTable::select_by_map(&rb, value! {"id":"1"})
+                .await
+                .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select * from logic_table where id = ? and delete_flag = ?"
+            );
+            assert_eq!(args, vec![value!("1"), value!(0)]);
+
+            let r = LogicTable::select_by_map(&rb, value! {"delete_flag": 1})
+                .await
+                .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "select * from logic_table where delete_flag = ?");
+            assert_eq!(args, vec![value!(1)]);
+
+            let r = LogicNamedTable::select_by_map(&rb, value! {})
+                .await
+                .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "select * from logic_named where delete_flag = ?");
+            assert_eq!(args, vec![value!(0)]);
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_logic_delete_update() {
+        let f = async move {
+            let mut rb = RBatis::new();
+            let queue = Arc::new(SyncVec::new());
+            rb.set_intercepts(vec![Arc::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let t = LogicTable {
+                id: Some("1".into()),
+                name: Some("a".into()),
+                delete_flag: None,
+            };
+            let r = LogicTable::update_by_map(&rb, &t, value! {"id":"1"})
+                .await
+                .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "update logic_table set name=?  where id = ? and delete_flag = ?"
+            );
+            assert_eq!(args, vec![value!("a"), value!("1"), value!(0)]);
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_logic_delete_delete() {
+        let f = async move {
+            let mut rb = RBatis::new();
+            let queue = Arc::new(SyncVec::new());
+            rb.set_intercepts(vec![Arc::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let r = LogicTable::delete_by_map(&rb, value! {"id":"1"})
+                .await
+                .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "update logic_table set delete_flag = 1 where id = ?");
+            assert_eq!(args, vec![value!("1")]);
+
+            let r = LogicTable::delete_by_map_physical(&rb, value! {"id":"1"})
+                .await
+                .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "delete from logic_table where id = ?");
+            assert_eq!(args, vec![value!("1")]);
+
+            let r = MockTable::delete_by_map_physical(&rb, value! {"id":"1"})
+                .await
+                .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "delete from mock_table where id = ?");
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_logic_delete_insert() {
+        let f = async move {
+            let mut rb = RBatis::new();
+            let queue = Arc::new(SyncVec::new());
+            rb.set_intercepts(vec![Arc::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let t = LogicTable {
+                id: Some("1".into()),
+                name: None,
+                delete_flag: None,
+            };
+            let r = LogicTable::insert(&rb, &t).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "insert into logic_table (id, delete_flag ) VALUES (?, ? )"
+            );
+            assert_eq!(args, vec![value!("1"), value!(0)]);
+        };
+        block_on(f);
+    }
+
+    /// return the exec result of `rows_affected`
+    #[derive(Debug)]
+    struct RowsAffectedIntercept(u64);
+
+    #[async_trait]
+    impl Intercept for RowsAffectedIntercept {
+        async fn before(
+            &self,
+            _task_id: i64,
+            _rb: &dyn Executor,
+            _sql: &mut String,
+            _args: &mut Vec<Value>,
+            result: ResultType<&mut Result<ExecResult, Error>, &mut Result<Value, Error>>,
+        ) -> Result<Action, Error> {
+            if let ResultType::Exec(result) = result {
+                *result = Ok(ExecResult {
+                    rows_affected: self.0,
+                    last_insert_id: Value::Null,
+                });
+                return Ok(Action::Return);
+            }
+            Ok(Action::Next)
+        }
+    }
+
+    #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+    struct VersionTable {
+        pub id: Option<String>,
+        pub name: Option<String>,
+        pub version: Option<i64>,
+    }
+
+    crud!(VersionTable {}, version = "version");
+
+    #[test]
+    fn test_version_update() {
+        let f = async move {
+            let mut rb = RBatis::new();
+            let queue = Arc::new(SyncVec::new());
+            rb.set_intercepts(vec![
+                Arc::new(MockIntercept::new(queue.clone())),
+                Arc::new(RowsAffectedIntercept(1)),
+            ]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let t = VersionTable {
+                id: Some("1".into()),
+                name: Some("a".into()),
+                version: Some(3),
+            };
+            let r = VersionTable::update_by_map(&rb, &t, value! {"id":"1"})
+                .await
+                .unwrap();
+            assert_eq!(r.rows_affected, 1);
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "update version_table set name=?, version=?  where id = ? and version = ?"
+            );
+            assert_eq!(
+                args,
+                vec![value!("a"), value!(4i64), value!("1"), value!(3i64)]
+            );
+
+            let r = VersionTable::update_by_map(&rb, &t, value! {"id":"1", "column": ["name"]})
+                .await
+                .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "update version_table set name=?, version=?  where id = ? and version = ?"
+            );
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_version_conflict() {
+        let f = async move {
+            let mut rb = RBatis::new();
+            let queue = Arc::new(SyncVec::new());
+            rb.set_intercepts(vec![Arc::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let mut t = VersionTable {
+                id: Some("1".into()),
+                name: Some("a".into()),
+                version: Some(3),
+            };
+            let err = VersionTable::update_by_map(&rb, &t, value! {"id":"1"})
+                .await
+                .unwrap_err();
+            assert!(rbatis::is_version_conflict(&err));
+
+            //without version, update without lock
+            t.version = None;
+            let r = VersionTable::update_by_map(&rb, &t, value! {"id":"1"})
+                .await
+                .unwrap();
+            assert_eq!(r.rows_affected, 0);
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "update version_table set name=?  where id = ?");
+        };
+        block_on(f);
+    }
+
+    #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+    struct FillTable {
+        pub id: Option<String>,
+        pub name: Option<String>,
+        pub create_time: Option<String>,
+        pub update_time: Option<String>,
+        pub created_by: Option<String>,
+    }
+
+    crud!(FillTable {});
+
+    fn fill_rb(queue: Arc<SyncVec<(String, Vec<Value>)>>) -> RBatis {
+        let mut rb = RBatis::new();
+        rb.set_intercepts(vec![Arc::new(MockIntercept::new(queue))]);
+        rb.init(MockDriver {}, "test").unwrap();
+        rb.set_fill_handler(
+            rbatis::plugin::DefaultFillHandler::new()
+                .on_insert("create_time", || value!("t1"))
+                .on_insert_update("update_time", || value!("t2"))
+                .on_insert("created_by", || value!("admin"))
+                .on_insert("not_exist", || value!("x")),
+        );
+        rb
+    }
+
+    #[test]
+    fn test_fill_insert() {
+        let f = async move {
+            let queue = Arc::new(SyncVec::new());
+            let rb = fill_rb(queue.clone());
+            let t = FillTable {
+                id: Some("1".into()),
+                name: None,
+                create_time: None,
+                update_time: None,
+                created_by: Some("user".into()),
+            };
+            let r = FillTable::insert(&rb, &t).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "insert into fill_table (id, create_time, update_time, created_by ) VALUES (?, ?, ?, ? )"
+            );
+            assert_eq!(
+                args,
+                vec![value!("1"), value!("t1"), value!("t2"), value!("user")]
+            );
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_fill_update() {
+        let f = async move {
+            let queue = Arc::new(SyncVec::new());
+            let rb = fill_rb(queue.clone());
+            let t = FillTable {
+                id: Some("1".into()),
+                name: Some("a".into()),
+                create_time: None,
+                update_time: None,
+                created_by: None,
+            };
+            let r = FillTable::update_by_map(&rb, &t, value! {"id":"1"})
+                .await
+                .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "update fill_table set name=?, update_time=?  where id = ?"
+            );
+            assert_eq!(args, vec![value!("a"), value!("t2"), value!("1")]);
+
+            let r = FillTable::update_by_map(&rb, &t, value! {"id":"1", "column": ["name"]})
+                .await
+                .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "update fill_table set name=?, update_time=?  where id = ?"
+            );
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_select_by_wrapper() {
+        let f = async move {
+            let mut rb = RBatis::new();
+            let queue = Arc::new(SyncVec::new());
+            rb.set_intercepts(vec![Arc::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let query = rbatis::Query::new()
+                .eq("name", "a")
+                .gt("status", 1)
+                .and(|q| q.like("remark", "%a%").or(|q| q.is_null("sort")))
+                .between("version", 1, 3)
+                .not_in("id", vec!["1", "2"])
+                .exists(
+                    "select 1 from item where item.mock_id = mock_table.id",
+                    vec![],
+                )
+                .order_by_desc("id")
+                .limit(10)
+                .offset(20);
+            let r = MockTable::select_by_wrapper(&rb, &query).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select * from mock_table where name = ? and status > ? and (remark like ? or (sort is null)) and version between ? and ? and id not in (?, ?) and exists (select 1 from item where item.mock_id = mock_table.id) order by id desc limit 10 offset 20"
+            );
+            assert_eq!(
+                args,
+                vec![
+                    value!("a"),
+                    value!(1),
+                    value!("%a%"),
+                    value!(1),
+                    value!(3),
+                    value!("1"),
+                    value!("2")
+                ]
+            );
+
+            let query = rbatis::Query::new()
+                .columns(vec!["id", "name"])
+                .is_in("id", Vec::<String>::new());
+            let r = MockTable::select_by_wrapper(&rb, &query).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "select id, name from mock_table where 1 = 0");
+            assert!(args.is_empty());
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_wrapper_identifier() {
+        let query = rbatis::Query::new()
+            .eq("order", 1)
+            .eq("t.name", "a")
+            .columns(vec!["t.*", "group"])
+            .order_by_desc("order");
+        let (sql, _) = query.select_sql("t", "mysql").unwrap();
+        assert_eq!(
+            sql,
+            "select t.*, `group` from t where `order` = ? and t.name = ? order by `order` desc"
+        );
+        let invalid = [
+            rbatis::Query::new().eq("id = 1 or 1", 1),
+            rbatis::Query::new().and(|q| q.is_null("id;")),
+            rbatis::Query::new().columns(vec!["count(1)"]),
+            rbatis::Query::new().order_by_asc("id desc, (select 1)"),
+        ];
+        for query in invalid {
+            assert!(query.select_sql("t", "mysql").is_err());
+        }
+        let query = rbatis::Query::new().eq("id;", 1);
+        assert!(query.where_sql("mysql").is_err());
+        assert!(query.count_sql("t", "mysql").is_err());
+        assert!(query.delete_sql("t", "mysql").is_err());
+        assert!(rbatis::Query::new()
+            .update_sql("t", "mysql", &value! {"name = 1, x": 1}, &[])
+            .is_err());
+    }
+
+    #[test]
+    fn test_wrapper_limit_sql() {
+        let query = rbatis::Query::new().eq("id", 1).limit(10).offset(20);
+        let (sql, _) = query.select_sql("t", "postgres").unwrap();
+        assert_eq!(sql, "select * from t where id = ? limit 10 offset 20");
+        let (sql, _) = query.select_sql("t", "mssql").unwrap();
+        assert_eq!(
+            sql,
+            "select * from t where id = ? order by (select null) offset 20 rows fetch next 10 rows only"
+        );
+        let (sql, _) = rbatis::Query::new()
+            .offset(5)
+            .select_sql("t", "sqlite")
+            .unwrap();
+        assert_eq!(sql, "select * from t limit -1 offset 5");
+    }
+
+    #[test]
+    fn test_count_update_delete_by_wrapper() {
+        let f = async move {
+            let mut rb = RBatis::new();
+            let queue = Arc::new(SyncVec::new());
+            rb.set_intercepts(vec![Arc::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let query = rbatis::Query::new()
+                .eq("name", "a")
+                .order_by_asc("id")
+                .limit(1);
+            let r = MockTable::count_by_wrapper(&rb, &query).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select count(1) as count from mock_table where name = ?"
+            );
+            assert_eq!(args, vec![value!("a")]);
+
+            let t = LogicTable {
+                id: Some("1".into()),
+                name: Some("b".into()),
+                delete_flag: None,
+            };
+            let r = LogicTable::update_by_wrapper(&rb, &t, &query)
+                .await
+                .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "update logic_table set name = ? where name = ? and delete_flag = ?"
+            );
+            assert_eq!(args, vec![value!("b"), value!("a"), value!(0)]);
+
+            let r = LogicTable::delete_by_wrapper(&rb, &query).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "update logic_table set delete_flag = 1 where name = ?");
+            assert_eq!(args, vec![value!("a")]);
+
+            let r = MockTable::delete_by_wrapper(&rb, &query).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "delete from mock_table where name = ?");
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_version_update_by_wrapper() {
+        let f = async move {
+            let mut rb = RBatis::new();
+            let queue = Arc::new(SyncVec::new());
+            rb.set_intercepts(vec![
+                Arc::new(MockIntercept::new(queue.clone())),
+                Arc::new(RowsAffectedIntercept(0)),
+            ]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let t = VersionTable {
+                id: Some("1".into()),
+                name: Some("a".into()),
+                version: Some(3),
+            };
+            let query = rbatis::Query::new().eq("id", "1");
+            let r = VersionTable::update_by_wrapper(&rb, &t, &query).await;
+            assert!(rbatis::is_version_conflict(&r.unwrap_err()));
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "update version_table set name = ?, version = ? where id = ? and version = ?"
+            );
+            assert_eq!(
+                args,
+                vec![value!("a"), value!(4i64), value!("1"), value!(3i64)]
+            );
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_wrapper_or_group() {
+        let f = async move {
+            let mut rb = RBatis::new();
+            let queue = Arc::new(SyncVec::new());
+            rb.set_intercepts(vec![
+                Arc::new(MockIntercept::new(queue.clone())),
+                Arc::new(RowsAffectedIntercept(1)),
+            ]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let query = rbatis::Query::new()
+                .eq("name", "a")
+                .or(|q| q.eq("name", "b"))
+                .order_by_asc("id");
+            let _ = LogicTable::select_by_wrapper(&rb, &query).await;
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select * from logic_table where (name = ? or (name = ?)) and delete_flag = ? order by id asc"
+            );
+            assert_eq!(args, vec![value!("a"), value!("b"), value!(0)]);
+
+            let t = VersionTable {
+                id: Some("1".into()),
+                name: Some("a".into()),
+                version: Some(3),
+            };
+            let query = rbatis::Query::new().eq("id", "1").or(|q| q.eq("id", "2"));
+            VersionTable::update_by_wrapper(&rb, &t, &query)
+                .await
+                .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "update version_table set name = ?, version = ? where (id = ? or (id = ?)) and version = ?"
+            );
+            assert_eq!(
+                args,
+                vec![
+                    value!("a"),
+                    value!(4i64),
+                    value!("1"),
+                    value!("2"),
+                    value!(3i64)
+                ]
+            );
+        };
+        block_on(f);
+    }
+
+    /// return the query result of rows
+    #[derive(Debug)]
+    struct RowsIntercept(Value);
+
+    #[async_trait]
+    impl Intercept for RowsIntercept {
+        async fn before(
+            &self,
+            _task_id: i64,
+            _rb: &dyn Executor,
+            _sql: &mut String,
+            _args: &mut Vec<Value>,
+            result: ResultType<&mut Result<ExecResult, Error>, &mut Result<Value, Error>>,
+        ) -> Result<Action, Error> {
+            if let ResultType::Query(result) = result {
+                *result = Ok(self.0.clone());
+                return Ok(Action::Return);
+            }
+            Ok(Action::Next)
+        }
+    }
+
+    fn rows_rb(queue: Arc<SyncVec<(String, Vec<Value>)>>, rows: Value) -> RBatis {
+        let mut rb = RBatis::new();
+        rb.set_intercepts(vec![
+            Arc::new(MockIntercept::new(queue)),
+            Arc::new(RowsIntercept(rows)),
+        ]);
+        rb.init(MockDriver {}, "test").unwrap();
+        rb
+    }
+
+    #[test]
+    fn test_count_decode_error() {
+        let f = async move {
+            let queue = Arc::new(SyncVec::new());
+            // more than one row can not be the count
+            let rb = rows_rb(
+                queue.clone(),
+                Value::Array(vec![value! {"count": 1}, value! {"count": 2}]),
+            );
+            let r = MockTable::count_by_wrapper(&rb, &rbatis::Query::new()).await;
+            assert!(r.is_err());
+            let r = MockTable::count_by_map(&rb, value! {}).await;
+            assert!(r.is_err());
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_count_by_map() {
+        let f = async move {
+            let queue = Arc::new(SyncVec::new());
+            let rb = rows_rb(queue.clone(), Value::Array(vec![value! {"count": 3}]));
+            let r = MockTable::count_by_map(
+                &rb,
+                value! {"status": 1, "column": ["id"], "order_by": "id desc"},
+            )
+            .await
+            .unwrap();
+            assert_eq!(r, 3);
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select count(1) as count from mock_table where status = ?"
+            );
+            assert_eq!(args, vec![value!(1)]);
+
+            let r = LogicTable::count_by_map(&rb, value! {}).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select count(1) as count from logic_table where delete_flag = ?"
+            );
+            assert!(MockTable::count_by_map(&rb, value! {"limit": 1})
+                .await
+                .is_err());
+
+            // the "column" of user is checked, only the internal count sql is not
+            for column in [value!("count(1) as count"), value!(["id", "1 as x"])] {
+                let r = MockTable::select_by_map(&rb, value! {"column": column}).await;
+                assert!(r.is_err());
+            }
+            assert!(queue.pop().is_none());
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_exists_by_map() {
+        let f = async move {
+            let queue = Arc::new(SyncVec::new());
+            let rb = rows_rb(queue.clone(), Value::Array(vec![value! {"1": 1}]));
+            let r = MockTable::exists_by_map(&rb, value! {"id": "1"})
+                .await
+                .unwrap();
+            assert!(r);
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "select 1 from mock_table where id = ? limit 1");
+            assert_eq!(args, vec![value!("1")]);
+
+            let rb = rows_rb(queue.clone(), Value::Array(vec![]));
+            let r = MockTable::exists_by_map(&rb, value! {"id": "1"})
+                .await
+                .unwrap();
+            assert!(!r);
+            let r = MockTable::exists_by_map(&rb, value! {"id": Value::Array(vec![])})
+                .await
+                .unwrap();
+            assert!(!r);
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_select_one_by_map() {
+        let f = async move {
+            let queue = Arc::new(SyncVec::new());
+            let rb = rows_rb(
+                queue.clone(),
+                Value::Array(vec![value! {"id": "1", "name": "a"}]),
+            );
+            let r = LogicTable::select_one_by_map(&rb, value! {"id": "1"})
+                .await
+                .unwrap();
+            assert_eq!(r.unwrap().name, Some("a".to_string()));
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select * from logic_table where id = ? and delete_flag = ? limit 2"
+            );
+
+            let rb = rows_rb(queue.clone(), Value::Array(vec![]));
+            let r = LogicTable::select_one_by_map(&rb, value! {"id": "1"})
+                .await
+                .unwrap();
+            assert!(r.is_none());
+
+            let rb = rows_rb(
+                queue.clone(),
+                Value::Array(vec![value! {"id": "1"}, value! {"id": "2"}]),
+            );
+            let r = LogicTable::select_one_by_map(&rb, value! {"name": "a"}).await;
+            assert!(r.is_err());
+        };
+        block_on(f);
+    }
+
+    #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+    struct UpsertTable {
+        pub id: Option<i64>,
+        pub name: Option<String>,
+    }
+
+    crud!(UpsertTable {});
+
+    #[test]
+    fn test_upsert_sqlite() {
+        let f = async move {
+            let path = std::env::temp_dir().join(format!(
+                "rbatis_upsert_{}.db",
+                rbatis::plugin::snowflake::new_snowflake_id()
+            ));
+            let rb = RBatis::new();
+            rb.link(
+                rbdc_sqlite::SqliteDriver {},
+                &format!("sqlite://{}", path.display()),
+            )
+            .await
+            .unwrap();
+            rb.exec(
+                "create table upsert_table (id integer primary key, name text)",
+                vec![],
+            )
+            .await
+            .unwrap();
+            let tables = vec![
+                UpsertTable {
+                    id: Some(1),
+                    name: Some("a".into()),
+                },
+                UpsertTable {
+                    id: Some(2),
+                    name: Some("b".into()),
+                },
+            ];
+            UpsertTable::upsert_batch(&rb, &tables, &["id"], 10)
+                .await
+                .unwrap();
+            let t = UpsertTable {
+                id: Some(1),
+                name: Some("c".into()),
+            };
+            UpsertTable::upsert(&rb, &t, &["id"]).await.unwrap();
+            let rows = UpsertTable::select_by_map(&rb, value! {"order_by": "id"})
+                .await
+                .unwrap();
+            assert_eq!(rows.len(), 2);
+            assert_eq!(rows[0], t);
+            assert_eq!(rows[1].name, Some("b".into()));
+
+            assert!(UpsertTable::upsert(&rb, &t, &["id;"]).await.is_err());
+            drop(rb);
+            let _ = std::fs::remove_file(path);
+        };
+        block_on(f);
+    }
+
+    #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+    struct UserRole {
+        pub org_id: Option<i64>,
+        pub user_id: Option<i64>,
+        pub role: Option<String>,
+        pub delete_flag: Option<i32>,
+    }
+
+    crud!(
+        UserRole {},
+        primary_key = ["org_id", "user_id"],
+        logic_delete = "delete_flag"
+    );
+
+    #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+    struct CodeTable {
+        pub code: Option<String>,
+        pub id: Option<String>,
+    }
+
+    crud!(CodeTable {}, primary_key = "code");
+
+    #[test]
+    fn test_select_by_id() {
+        let f = async move {
+            let queue = Arc::new(SyncVec::new());
+            let rb = rows_rb(queue.clone(), Value::Array(vec![value! {"id": "1"}]));
+            let r = LogicTable::select_by_id(&rb, "1").await.unwrap();
+            assert_eq!(r.unwrap().id, Some("1".to_string()));
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select * from logic_table where id = ? and delete_flag = ?"
+            );
+            assert_eq!(args, vec![value!("1"), value!(0)]);
+
+            let r = LogicTable::select_by_ids(&rb, &["1", "2"]).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select * from logic_table where id in (?, ?) and delete_flag = ?"
+            );
+            assert_eq!(args, vec![value!("1"), value!("2"), value!(0)]);
+
+            let r = UserRole::select_by_id(&rb, (1, 2)).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select * from user_role where org_id = ? and user_id = ? and delete_flag = ?"
+            );
+            assert_eq!(args, vec![value!(1), value!(2), value!(0)]);
+
+            let r = UserRole::select_by_ids(
+                &rb,
+                &[
+                    value! {"org_id": 1, "user_id": 2},
+                    value! {"user_id": 4, "org_id": 3},
+                ],
+            )
+            .await
+            .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select * from user_role where ((org_id = ? and user_id = ?) or (org_id = ? and user_id = ?)) and delete_flag = ?"
+            );
+            assert_eq!(
+                args,
+                vec![value!(1), value!(2), value!(3), value!(4), value!(0)]
+            );
+
+            assert!(UserRole::select_by_id(&rb, 1).await.is_err());
+            assert!(UserRole::select_by_ids(&rb, &Vec::<i64>::new())
+                .await
+                .unwrap()
+                .is_empty());
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_update_delete_by_id() {
+        let f = async move {
+            let mut rb = RBatis::new();
+            let queue = Arc::new(SyncVec::new());
+            rb.set_intercepts(vec![Arc::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let t = UserRole {
+                org_id: Some(1),
+                user_id: Some(2),
+                role: Some("admin".into()),
+                delete_flag: None,
+            };
+            let r = UserRole::update_by_id(&rb, &t).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "update user_role set role = ? where org_id = ? and user_id = ? and delete_flag = ?"
+            );
+            assert_eq!(
+                args,
+                vec![value!("admin"), value!(1i64), value!(2i64), value!(0)]
+            );
+
+            let r = UserRole::delete_by_id(&rb, (1, 2)).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "update user_role set delete_flag = 1 where org_id = ? and user_id = ?"
+            );
+
+            let r = MockTable::delete_by_ids(&rb, &["1", "2"]).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "delete from mock_table where id in (?, ?)");
+            assert_eq!(args, vec![value!("1"), value!("2")]);
+
+            let mut no_id = t.clone();
+            no_id.user_id = None;
+            assert!(UserRole::update_by_id(&rb, &no_id).await.is_err());
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_update_by_map_primary_key() {
+        let f = async move {
+            let mut rb = RBatis::new();
+            let queue = Arc::new(SyncVec::new());
+            rb.set_intercepts(vec![Arc::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let t = CodeTable {
+                code: Some("c".into()),
+                id: Some("1".into()),
+            };
+            let r = CodeTable::update_by_map(&rb, &t, value! {"code": "c"})
+                .await
+                .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "update code_table set id=?  where code = ?");
+            assert_eq!(args, vec![value!("1"), value!("c")]);
+
+            let r = CodeTable::update_by_id(&rb, &t).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "update code_table set id = ? where code = ?");
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_update_batch_by_id_pipeline() {
+        let f = async move {
+            let mut rb = RBatis::new();
+            let queue = Arc::new(SyncVec::new());
+            rb.set_intercepts(vec![
+                Arc::new(MockIntercept::new(queue.clone())),
+                Arc::new(RowsAffectedIntercept(1)),
+            ]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let tables = vec![
+                CodeTable {
+                    code: Some("a".into()),
+                    id: Some("1".into()),
+                },
+                CodeTable {
+                    code: Some("b".into()),
+                    id: Some("2".into()),
+                },
+            ];
+            let r = CodeTable::update_batch_by_id(&rb, &tables, 10)
+                .await
+                .unwrap();
+            assert_eq!(r.rows_affected, 2);
+            assert_eq!(queue.len(), 2);
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "update code_table set id = ? where code = ?");
+            assert_eq!(args, vec![value!("2"), value!("b")]);
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_update_batch_by_id_sqlite() {
+        let f = async move {
+            let path = std::env::temp_dir().join(format!(
+                "rbatis_update_batch_{}.db",
+                rbatis::plugin::snowflake::new_snowflake_id()
+            ));
+            let rb = RBatis::new();
+            rb.link(
+                rbdc_sqlite::SqliteDriver {},
+                &format!("sqlite://{}", path.display()),
+            )
+            .await
+            .unwrap();
+            rb.exec(
+                "create table upsert_table (id integer primary key, name text)",
+                vec![],
+            )
+            .await
+            .unwrap();
+            let mut tables = vec![];
+            for id in 0..5 {
+                tables.push(UpsertTable {
+                    id: Some(id),
+                    name: Some(format!("name{}", id)),
+                });
+            }
+            UpsertTable::insert_batch(&rb, &tables, 10).await.unwrap();
+            for t in &mut tables {
+                t.name = Some(format!("new{}", t.id.unwrap()));
+            }
+            tables[3].name = None;
+            let r = UpsertTable::update_batch_by_id(&rb, &tables, 2)
+                .await
+                .unwrap();
+            assert_eq!(r.rows_affected, 5);
+            let rows = UpsertTable::select_by_map(&rb, value! {"order_by": "id"})
+                .await
+                .unwrap();
+            assert_eq!(rows[0].name, Some("new0".into()));
+            assert_eq!(rows[2].name, Some("new2".into()));
+            assert_eq!(rows[3].name, Some("name3".into()));
+            assert_eq!(rows[4].name, Some("new4".into()));
+            drop(rb);
+            let _ = std::fs::remove_file(path);
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_insert_returning_reselect() {
+        let f = async move {
+            let queue = Arc::new(SyncVec::new());
+            let rows = Value::Array(vec![
+                value! {"code": "b", "id": "2"},
+                value! {"code": "a", "id": "1"},
+            ]);
+            let rb = rows_rb(queue.clone(), rows);
+            let tables = vec![
+                CodeTable {
+                    code: Some("a".into()),
+                    id: Some("1".into()),
+                },
+                CodeTable {
+                    code: Some("b".into()),
+                    id: Some("2".into()),
+                },
+            ];
+            let r = CodeTable::insert_batch_returning(&rb, &tables, 10)
+                .await
+                .unwrap();
+            assert_eq!(r[0].code, Some("a".into()));
+            assert_eq!(r[1].code, Some("b".into()));
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "select * from code_table where code in (?, ?)");
+            assert_eq!(args, vec![value!("a"), value!("b")]);
+            let (sql, _) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "insert into code_table (code, id) values (?, ?), (?, ?)"
+            );
+        };
+        block_on(f);
+    }
+
+    #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+    struct KeyTable {
+        pub key: Option<String>,
+        pub name: Option<String>,
+    }
+
+    crud!(KeyTable {}, primary_key = "key");
+
+    #[test]
+    fn test_insert_returning_reserved_primary_key() {
+        let f = async move {
+            let queue = Arc::new(SyncVec::new());
+            let rows = Value::Array(vec![
+                value! {"key": "b", "name": "b"},
+                value! {"key": "a", "name": "a"},
+            ]);
+            let rb = rows_rb(queue.clone(), rows);
+            let tables = vec![
+                KeyTable {
+                    key: Some("a".into()),
+                    name: Some("a".into()),
+                },
+                KeyTable {
+                    key: Some("b".into()),
+                    name: Some("b".into()),
+                },
+            ];
+            let r = KeyTable::insert_batch_returning(&rb, &tables, 10)
+                .await
+                .unwrap();
+            assert_eq!(r, tables);
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "select * from key_table where \"key\" in (?, ?)");
+            assert_eq!(args, vec![value!("a"), value!("b")]);
+            // the supplied ids are inserted in one batch
+            let (sql, _) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "insert into key_table (\"key\", name) values (?, ?), (?, ?)"
+            );
+            assert!(queue.pop().is_none());
+        };
+        block_on(f);
+    }
+
+    #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct ReturningTable {
+        pub id: Option<i64>,
+        pub name: Option<String>,
+        pub status: Option<i32>,
+    }
+
+    crud!(ReturningTable {});
+
+    /// exec return the generated id 1, 3, 5..(the auto_increment_increment = 2), query return the rows
+    #[derive(Debug)]
+    struct GeneratedIdIntercept(AtomicU64, Value);
+
+    #[async_trait]
+    impl Intercept for GeneratedIdIntercept {
+        async fn before(
+            &self,
+            _task_id: i64,
+            _rb: &dyn Executor,
+            _sql: &mut String,
+            _args: &mut Vec<Value>,
+            result: ResultType<&mut Result<ExecResult, Error>, &mut Result<Value, Error>>,
+        ) -> Result<Action, Error> {
+            match result {
+                ResultType::Exec(result) => {
+                    *result = Ok(ExecResult {
+                        rows_affected: 1,
+                        last_insert_id: Value::U64(self.0.fetch_add(2, Ordering::SeqCst)),
+                    });
+                }
+                ResultType::Query(result) => *result = Ok(self.1.clone()),
+            }
+            Ok(Action::Return)
+        }
+    }
+
+    #[test]
+    fn test_insert_returning_generated_ids() {
+        let f = async move {
+            let mut rb = RBatis::new();
+            let queue = Arc::new(SyncVec::new());
+            rb.set_intercepts(vec![
+                Arc::new(MockIntercept::new(queue.clone())),
+                Arc::new(GeneratedIdIntercept(
+                    AtomicU64::new(1),
+                    Value::Array(vec![
+                        value! {"id": 3, "name": "b", "status": 0},
+                        value! {"id": 1, "name": "a", "status": 0},
+                    ]),
+                )),
+            ]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let tables = vec![
+                ReturningTable {
+                    id: None,
+                    name: Some("a".into()),
+                    status: None,
+                },
+                ReturningTable {
+                    id: None,
+                    name: Some("b".into()),
+                    status: None,
+                },
+            ];
+            let r = ReturningTable::insert_batch_returning(&rb, &tables, 10)
+                .await
+                .unwrap();
+            assert_eq!(r[0].id, Some(1));
+            assert_eq!(r[1].id, Some(3));
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "select * from returning_table where id in (?, ?)");
+            assert_eq!(args, vec![Value::U64(1), Value::U64(3)]);
+            // the rows of generated ids are inserted one by one
+            for name in ["b", "a"] {
+                let (sql, args) = queue.pop().unwrap();
+                assert_eq!(sql, "insert into returning_table (name) values (?)");
+                assert_eq!(args, vec![value!(name)]);
+            }
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_insert_returning_sqlite() {
+        let f = async move {
+            let path = std::env::temp_dir().join(format!(
+                "rbatis_insert_returning_{}.db",
+                rbatis::plugin::snowflake::new_snowflake_id()
+            ));
+            let rb = RBatis::new();
+            rb.link(
+                rbdc_sqlite::SqliteDriver {},
+                &format!("sqlite://{}", path.display()),
+            )
+            .await
+            .unwrap();
+            rb.exec(
+                "create table returning_table (id integer primary key autoincrement, name text, status integer default 1)",
+                vec![],
+            )
+            .await
+            .unwrap();
+            let tables = vec![
+                ReturningTable {
+                    id: None,
+                    name: Some("a".into()),
+                    status: None,
+                },
+                ReturningTable {
+                    id: None,
+                    name: Some("b".into()),
+                    status: None,
+                },
+            ];
+            let rows = ReturningTable::insert_batch_returning(&rb, &tables, 10)
+                .await
+                .unwrap();
+            assert_eq!(
+                rows,
+                vec![
+                    ReturningTable {
+                        id: Some(1),
+                        name: Some("a".into()),
+                        status: Some(1),
+                    },
+                    ReturningTable {
+                        id: Some(2),
+                        name: Some("b".into()),
+                        status: Some(1),
+                    }
+                ]
+            );
+            let rows = ReturningTable::insert_returning(&rb, &tables[0])
+                .await
+                .unwrap();
+            assert_eq!(rows[0].id, Some(3));
+            drop(rb);
+            let _ = std::fs::remove_file(path);
+        };
+        block_on(f);
+    }
+
+    #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct ReservedTable {
+        pub id: Option<String>,
+        pub order: Option<i32>,
+        pub group: Option<String>,
+    }
+
+    crud!(ReservedTable {}, "user");
+
+    #[test]
+    fn test_quote_reserved_identifier() {
+        let f = async move {
+            let queue = Arc::new(SyncVec::new());
+            let rb = rows_rb(queue.clone(), Value::Array(vec![]));
+            let table = ReservedTable {
+                id: Some("1".into()),
+                order: Some(1),
+                group: Some("a".into()),
+            };
+            ReservedTable::insert(&rb, &table).await.unwrap();
+            let (sql, _) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "insert into \"user\" (id, \"order\", \"group\" ) VALUES (?, ?, ? )"
+            );
+
+            ReservedTable::select_by_map(
+                &rb,
+                value! {"order >=": 1, "group": ["a"], "order_by": "order desc"},
+            )
+            .await
+            .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select * from \"user\" where \"order\" >= ? and \"group\" in (? ) order by \"order\" desc"
+            );
+            assert_eq!(args, vec![value!(1), value!("a")]);
+
+            ReservedTable::update_by_map(&rb, &table, value! {"id": "1"})
+                .await
+                .unwrap();
+            let (sql, _) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "update \"user\" set \"order\"=?, \"group\"=?  where id = ?"
+            );
+
+            ReservedTable::delete_by_wrapper(&rb, &rbatis::Query::new().eq("id", "1"))
+                .await
+                .unwrap();
+            let (sql, _) = queue.pop().unwrap();
+            assert_eq!(sql, "delete from \"user\" where id = ?");
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_reject_invalid_condition_key() {
+        let f = async move {
+            let queue = Arc::new(SyncVec::new());
+            let rb = rows_rb(queue.clone(), Value::Array(vec![]));
+            for condition in [
+                value! {"1": "1"},
+                value! {"id = 1 or 1": 1},
+                value! {"id; drop table mock_table": 1},
+                value! {"id >>": 1},
+                value! {"id >": ["1", "2"]},
+            ] {
+                assert!(MockTable::select_by_map(&rb, condition.clone())
+                    .await
+                    .is_err());
+                assert!(MockTable::delete_by_map(&rb, condition).await.is_err());
+            }
+            assert_eq!(queue.len(), 0);
+
+            MockTable::select_by_map(&rb, value! {"name  NOT   LIKE": "%a%"})
+                .await
+                .unwrap();
+            let (sql, _) = queue.pop().unwrap();
+            assert_eq!(sql, "select * from mock_table where name not like ?");
+        };
+        block_on(f);
+    }
 }
@@ -1,11 +1,21 @@
-/// Comprehensive tests for crud_traits module:
-/// - ColumnSet trait and its Value implementation
-/// - ValueOperatorSql trait and its Value implementation
-/// - FilterByColumns trait and its Value implementation
+//! Comprehensive tests for crud_traits module:
+//! - ColumnSet trait and its Value implementation
+//! - ValueOperatorSql trait and its Value implementation
+//! - FilterByColumns trait and its Value implementation
+//! - struct_fields / check_column / select_tail_sql
+//! - upsert_sql
+//! - CrudOptions::update_batch_sql
+//! - insert_sql/insert_returning_sql
+//! - quote_identifier/quote_columns/quote_condition
+//! - select_columns
 
 #[cfg(test)]
 mod test {
-    use rbatis::crud_traits::{ColumnSet, FilterByColumns, ValueOperatorSql};
+    use rbatis::crud_traits::{
+        check_column, insert_returning_sql, insert_sql, quote_columns, quote_condition,
+        quote_identifier, select_columns, select_tail_sql, struct_fields, upsert_sql, ColumnSet,
+        CrudOptions, FilterByColumns, ValueOperatorSql, RESERVED_WORDS,
+    };
     use rbs::value::map::ValueMap;
     use rbs::{value, Value};
 
@@ -403,4 +413,384 @@ mod test {
         let result_map = result.as_map().unwrap();
         assert_eq!(result_map.len(), 2); // 2 columns selected from value! macro
     }
+
+    // ==================== select_tail_sql Tests ====================
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct FieldsTable {
+        id: Option<String>,
+        #[serde(rename = "title")]
+        name: Option<String>,
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct FlattenTable {
+        id: Option<String>,
+        #[serde(flatten)]
+        extra: std::collections::HashMap<String, String>,
+    }
+
+    #[test]
+    fn test_struct_fields() {
+        assert_eq!(struct_fields::<FieldsTable>(), Some(&["id", "title"][..]));
+        assert_eq!(struct_fields::<FlattenTable>(), None);
+        assert_eq!(struct_fields::<Value>(), None);
+    }
+
+    #[test]
+    fn test_check_column() {
+        let fields = struct_fields::<FieldsTable>();
+        assert!(check_column("title", fields).is_ok());
+        assert!(check_column("name", fields).is_err());
+        // unknown fields only allow the plain identifier
+        assert!(check_column("any_column1", None).is_ok());
+        assert!(check_column("1abc", None).is_err());
+        assert!(check_column("id desc", None).is_err());
+        assert!(check_column("id;drop", None).is_err());
+    }
+
+    #[test]
+    fn test_select_columns() {
+        let fields = struct_fields::<FieldsTable>();
+        assert_eq!(
+            select_columns("mysql", &Value::from("id, title"), fields).unwrap(),
+            "id, title"
+        );
+        assert_eq!(
+            select_columns("mysql", &value!(["id"]), None).unwrap(),
+            "id"
+        );
+        assert_eq!(
+            select_columns("mysql", &value!(["order"]), None).unwrap(),
+            "`order`"
+        );
+        assert_eq!(select_columns("mysql", &Value::Null, fields).unwrap(), "*");
+        assert_eq!(
+            select_columns("mysql", &Value::from("*"), fields).unwrap(),
+            "*"
+        );
+        assert!(select_columns("mysql", &Value::from("count(1) as count"), None).is_err());
+        assert!(select_columns("mysql", &Value::from("name"), fields).is_err());
+        assert!(select_columns("mysql", &value!(["id", "id;drop"]), None).is_err());
+    }
+
+    #[test]
+    fn test_select_tail_sql() {
+        let fields = struct_fields::<FieldsTable>();
+        let mut condition = value! {"id": 1, "order_by": "id desc", "limit": 5, "offset": 10};
+        let sql = select_tail_sql(&mut condition, fields, "postgres").unwrap();
+        assert_eq!(sql, " order by id desc limit 5 offset 10");
+        assert_eq!(condition, value! {"id": 1});
+
+        let mut condition = value! {"group_by": ["id", "title"], "limit": 5};
+        let sql = select_tail_sql(&mut condition, fields, "mssql").unwrap();
+        assert_eq!(
+            sql,
+            " group by id, title order by (select null) offset 0 rows fetch next 5 rows only"
+        );
+
+        let mut condition = value! {"order_by": "id up"};
+        assert!(select_tail_sql(&mut condition, fields, "mysql").is_err());
+        let mut condition = value! {"offset": -1};
+        assert!(select_tail_sql(&mut condition, fields, "mysql").is_err());
+    }
+
+    // ==================== upsert_sql Tests ====================
+
+    #[test]
+    fn test_upsert_sql() {
+        let rows = Value::Array(vec![
+            value! {"id": 1, "name": "a", "age": Value::Null},
+            value! {"id": 2, "name": "b", "age": 3},
+        ]);
+        let (sql, args) = upsert_sql("sqlite", "t", &rows, &["id"]).unwrap();
+        assert_eq!(
+            sql,
+            "insert into t (id, name, age) values (?, ?, ?), (?, ?, ?) on conflict (id) do update set name = excluded.name, age = excluded.age"
+        );
+        assert_eq!(
+            args,
+            vec![
+                value!(1),
+                value!("a"),
+                Value::Null,
+                value!(2),
+                value!("b"),
+                value!(3)
+            ]
+        );
+
+        let rows = Value::Array(vec![value! {"id": 1, "name": "a"}]);
+        let (sql, _) = upsert_sql("postgres", "t", &rows, &["id", "name"]).unwrap();
+        assert_eq!(
+            sql,
+            "insert into t (id, name) values (?, ?) on conflict (id, name) do nothing"
+        );
+
+        let (sql, _) = upsert_sql("mysql", "t", &rows, &["id"]).unwrap();
+        assert_eq!(
+            sql,
+            "insert into t (id, name) values (?, ?) on duplicate key update name = values(name)"
+        );
+
+        let (sql, _) = upsert_sql("mssql", "t", &rows, &["id"]).unwrap();
+        assert_eq!(
+            sql,
+            "merge into t as target using (values (?, ?)) as source (id, name) on target.id = source.id when matched then update set target.name = source.name when not matched then insert (id, name) values (source.id, source.name);"
+        );
+    }
+
+    #[test]
+    fn test_upsert_sql_error() {
+        let rows = Value::Array(vec![value! {"id": 1, "name": "a"}]);
+        assert!(upsert_sql("oracle", "t", &rows, &["id"]).is_err());
+        assert!(upsert_sql("sqlite", "t", &rows, &[]).is_err());
+        assert!(upsert_sql("sqlite", "t", &rows, &["code"]).is_err());
+        assert!(upsert_sql("sqlite", "t", &Value::Array(vec![]), &["id"]).is_err());
+    }
+
+    // ==================== update_batch_sql Tests ====================
+
+    #[test]
+    fn test_update_batch_sql() {
+        let rows = Value::Array(vec![
+            value! {"id": 1, "name": "a", "age": 2},
+            value! {"id": 2, "name": "b", "age": Value::Null},
+        ]);
+        let options = CrudOptions::new();
+        let (sql, args) = options
+            .update_batch_sql("mysql", "t", &rows)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            sql,
+            "update t set name = case when id = ? then ? when id = ? then ? else name end, age = case when id = ? then ? else age end where id in (?, ?)"
+        );
+        assert_eq!(
+            args,
+            vec![
+                value!(1),
+                value!("a"),
+                value!(2),
+                value!("b"),
+                value!(1),
+                value!(2),
+                value!(1),
+                value!(2)
+            ]
+        );
+
+        let (sql, args) = options
+            .update_batch_sql("postgres", "t", &rows)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            sql,
+            "update t as t set name = v.name, age = coalesce(v.age, t.age) from (values (?, ?, ?), (?, ?, ?)) as v(id, name, age) where t.id = v.id"
+        );
+        assert_eq!(
+            args,
+            vec![
+                value!(1),
+                value!("a"),
+                value!(2),
+                value!(2),
+                value!("b"),
+                Value::Null
+            ]
+        );
+
+        assert!(options
+            .update_batch_sql("mssql", "t", &rows)
+            .unwrap()
+            .is_none());
+        assert!(CrudOptions::new()
+            .version("version")
+            .update_batch_sql("mysql", "t", &rows)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_update_batch_sql_composite_key() {
+        let rows = Value::Array(vec![
+            value! {"org_id": 1, "user_id": 2, "role": "a"},
+            value! {"org_id": 1, "user_id": 3, "role": "b"},
+        ]);
+        let options = CrudOptions::new()
+            .primary_key(["org_id", "user_id"])
+            .logic_delete("delete_flag");
+        let (sql, args) = options
+            .update_batch_sql("sqlite", "t", &rows)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            sql,
+            "update t set role = case when org_id = ? and user_id = ? then ? when org_id = ? and user_id = ? then ? else role end where ((org_id = ? and user_id = ?) or (org_id = ? and user_id = ?)) and delete_flag = ?"
+        );
+        assert_eq!(args.len(), 11);
+
+        let (sql, _) = options.update_batch_sql("pg", "t", &rows).unwrap().unwrap();
+        assert_eq!(
+            sql,
+            "update t as t set role = v.role from (values (?, ?, ?), (?, ?, ?)) as v(org_id, user_id, role) where t.org_id = v.org_id and t.user_id = v.user_id and t.delete_flag = 0"
+        );
+
+        let rows = Value::Array(vec![value! {"org_id": 1, "role": "a"}]);
+        assert!(options.update_batch_sql("mysql", "t", &rows).is_err());
+    }
+
+    #[test]
+    fn test_update_batch_sql_identifier() {
+        let options = CrudOptions::new();
+        let rows = Value::Array(vec![value! {"id": 1, "order": "a"}]);
+        let (sql, _) = options
+            .update_batch_sql("mysql", "t", &rows)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            sql,
+            "update t set `order` = case when id = ? then ? else `order` end where id = ?"
+        );
+        let (sql, _) = options.update_batch_sql("pg", "t", &rows).unwrap().unwrap();
+        assert_eq!(
+            sql,
+            "update t as t set \"order\" = v.\"order\" from (values (?, ?)) as v(id, \"order\") where t.id = v.id"
+        );
+
+        let rows = Value::Array(vec![value! {"id": 1, "name = 1, x": "a"}]);
+        assert!(options.update_batch_sql("mysql", "t", &rows).is_err());
+        assert!(options.update_batch_sql("pg", "t", &rows).is_err());
+
+        let options = CrudOptions::new().primary_key("id or 1=1");
+        assert!(options.id_query(&[value!(1)]).is_err());
+    }
+
+    // ==================== insert_returning_sql Tests ====================
+
+    #[test]
+    fn test_insert_returning_sql() {
+        let rows = Value::Array(vec![
+            value! {"id": Value::Null, "name": "a"},
+            value! {"id": Value::Null, "name": "b"},
+        ]);
+        let (sql, args) = insert_sql("t", &rows).unwrap();
+        assert_eq!(sql, "insert into t (name) values (?), (?)");
+        assert_eq!(args, vec![value!("a"), value!("b")]);
+
+        let (sql, _) = insert_returning_sql("sqlite", "t", &rows).unwrap().unwrap();
+        assert_eq!(sql, "insert into t (name) values (?), (?) returning *");
+        let (sql, _) = insert_returning_sql("pg", "t", &rows).unwrap().unwrap();
+        assert_eq!(sql, "insert into t (name) values (?), (?) returning *");
+        let (sql, _) = insert_returning_sql("mssql", "t", &rows).unwrap().unwrap();
+        assert_eq!(
+            sql,
+            "insert into t (name) output inserted.* values (?), (?)"
+        );
+        assert!(insert_returning_sql("mysql", "t", &rows).unwrap().is_none());
+
+        let empty = Value::Array(vec![value! {"id": Value::Null}]);
+        assert!(insert_sql("t", &empty).is_err());
+        assert!(insert_returning_sql("sqlite", "t", &empty).is_err());
+    }
+
+    #[test]
+    fn test_inserted_id() {
+        let options = CrudOptions::new();
+        let rows = Value::Array(vec![
+            value! {"id": Value::Null, "name": "a"},
+            value! {"id": 7, "name": "b"},
+            value! {"id": Value::Null, "name": "c"},
+        ]);
+        assert!(options.has_generated_id(&rows));
+        assert!(!options.has_generated_id(&Value::Array(vec![value! {"id": 7}])));
+        let ids = vec![
+            options.inserted_id(&rows[0], &Value::U64(3)).unwrap(),
+            options.inserted_id(&rows[1], &Value::U64(3)).unwrap(),
+            options.inserted_id(&rows[2], &Value::U64(4)).unwrap(),
+        ];
+        assert_eq!(ids, vec![Value::U64(3), value!(7), Value::U64(4)]);
+        assert!(options.inserted_id(&rows[0], &Value::Null).is_err());
+
+        let selected = Value::Array(vec![
+            value! {"id": 4, "name": "c"},
+            value! {"id": 3, "name": "a"},
+            value! {"id": 7, "name": "b"},
+        ]);
+        let sorted = options.sort_by_ids(selected, &ids);
+        let names: Vec<&str> = sorted
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|row| row["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+
+        let options = CrudOptions::new().primary_key(["org_id", "user_id"]);
+        let rows = Value::Array(vec![value! {"org_id": 1, "user_id": Value::Null}]);
+        assert!(!options.has_generated_id(&rows));
+        assert!(options.inserted_id(&rows[0], &Value::U64(3)).is_err());
+    }
+
+    // ==================== quote Tests ====================
+
+    #[test]
+    fn test_quote_identifier() {
+        assert_eq!(quote_identifier("mysql", "order"), "`order`");
+        assert_eq!(quote_identifier("postgres", "order"), "\"order\"");
+        assert_eq!(quote_identifier("sqlite", "Group"), "\"Group\"");
+        assert_eq!(quote_identifier("mssql", "user"), "[user]");
+        assert_eq!(quote_identifier("mysql", "name"), "name");
+        assert_eq!(quote_identifier("pg", "app.user"), "app.\"user\"");
+        assert_eq!(quote_identifier("pg", "public.user"), "\"public\".\"user\"");
+        assert_eq!(
+            quote_identifier("mysql", "count(1) as count"),
+            "count(1) as count"
+        );
+    }
+
+    #[test]
+    fn test_quote_reserved_words() {
+        for word in ["where", "set", "values", "when", "then", "with", "unique"] {
+            assert_eq!(quote_identifier("mysql", word), format!("`{}`", word));
+            assert_eq!(quote_identifier("postgres", word), format!("\"{}\"", word));
+            assert_eq!(quote_identifier("sqlite", word), format!("\"{}\"", word));
+            assert_eq!(quote_identifier("mssql", word), format!("[{}]", word));
+        }
+        // every word is found by the binary search
+        for word in RESERVED_WORDS {
+            assert_eq!(quote_identifier("pg", word), format!("\"{}\"", word));
+        }
+        let mut rows = Value::Array(vec![value! {"id": 1, "where": 2, "set": 3, "values": 4}]);
+        quote_columns("mysql", &mut rows);
+        assert_eq!(
+            rows,
+            Value::Array(vec![
+                value! {"id": 1, "`where`": 2, "`set`": 3, "`values`": 4}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_quote_columns() {
+        let mut rows = Value::Array(vec![value! {"id": 1, "order": 2}]);
+        quote_columns("mysql", &mut rows);
+        assert_eq!(rows, Value::Array(vec![value! {"id": 1, "`order`": 2}]));
+    }
+
+    #[test]
+    fn test_quote_condition() {
+        let mut condition = value! {"order": 1, "age >=": 2, "t.name  Like": "%a%", "id": ["1"]};
+        quote_condition("mysql", &mut condition).unwrap();
+        assert_eq!(
+            condition,
+            value! {"`order`": 1, "age >= ": 2, "t.name like ": "%a%", "id": ["1"]}
+        );
+        for key in ["", "1", "id,name", "id = 1 or 1", "id;", "age >>", "id in"] {
+            let mut condition = value! {(key): 1};
+            assert!(quote_condition("mysql", &mut condition).is_err(), "{}", key);
+        }
+        let mut condition = value! {"id >": ["1"]};
+        assert!(quote_condition("mysql", &mut condition).is_err());
+    }
 }
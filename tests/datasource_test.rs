@@ -0,0 +1,252 @@
+//! Tests for multiple named datasources:
+//! - RBatis::init_ds() / ds_names() / get_ds_pool()
+//! - RBatis::use_ds()
+//! - DefaultRouter read/write routing
+//! - custom DataSourceRouter
+
+#[cfg(test)]
+mod test {
+    use dark_std::sync::SyncVec;
+    use futures_core::future::BoxFuture;
+    use futures_core::Stream;
+    use rbatis::{DataSourceRouter, DefaultRouter, Error, RBatis, RouteKind, PRIMARY_DS};
+    use rbdc::db::{ConnectOptions, Connection, Driver, ExecResult, Row};
+    use rbs::Value;
+    use std::pin::Pin;
+    use std::sync::Arc;
+
+    // ==================== Mock Infrastructure ====================
+
+    /// record (datasource, sql)
+    type SqlLog = Arc<SyncVec<(String, String)>>;
+
+    #[derive(Debug, Clone)]
+    struct DsDriver {
+        ds: String,
+        log: SqlLog,
+    }
+
+    impl DsDriver {
+        fn new_conn(&self) -> Box<dyn Connection> {
+            Box::new(DsConnection {
+                ds: self.ds.clone(),
+                log: self.log.clone(),
+            })
+        }
+    }
+
+    impl Driver for DsDriver {
+        fn name(&self) -> &str {
+            "test"
+        }
+
+        fn connect(&self, _url: &str) -> BoxFuture<'_, Result<Box<dyn Connection>, Error>> {
+            Box::pin(async { Ok(self.new_conn()) })
+        }
+
+        fn connect_opt<'a>(
+            &'a self,
+            _option: &'a dyn ConnectOptions,
+        ) -> BoxFuture<'a, Result<Box<dyn Connection>, Error>> {
+            Box::pin(async { Ok(self.new_conn()) })
+        }
+
+        fn default_option(&self) -> Box<dyn ConnectOptions> {
+            Box::new(DsConnectOptions {
+                driver: self.clone(),
+            })
+        }
+    }
+
+    #[derive(Debug)]
+    struct DsConnection {
+        ds: String,
+        log: SqlLog,
+    }
+
+    impl Connection for DsConnection {
+        fn exec_rows(
+            &mut self,
+            _sql: &str,
+            _params: Vec<Value>,
+        ) -> BoxFuture<
+            '_,
+            Result<Pin<Box<dyn Stream<Item = Result<Box<dyn Row>, Error>> + Send + '_>>, Error>,
+        > {
+            Box::pin(async { Err(Error::from("mock")) })
+        }
+
+        fn exec_decode(
+            &mut self,
+            sql: &str,
+            _params: Vec<Value>,
+        ) -> BoxFuture<'_, Result<Value, Error>> {
+            self.log.push((self.ds.clone(), sql.to_string()));
+            Box::pin(async { Ok(Value::Array(vec![])) })
+        }
+
+        fn exec(
+            &mut self,
+            sql: &str,
+            _params: Vec<Value>,
+        ) -> BoxFuture<'_, Result<ExecResult, Error>> {
+            self.log.push((self.ds.clone(), sql.to_string()));
+            Box::pin(async { Ok(ExecResult::default()) })
+        }
+
+        fn close(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+            Box::pin(async { Ok(()) })
+        }
+
+        fn ping(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct DsConnectOptions {
+        driver: DsDriver,
+    }
+
+    impl ConnectOptions for DsConnectOptions {
+        fn connect(&self) -> BoxFuture<'_, Result<Box<dyn Connection>, Error>> {
+            Box::pin(async { Ok(self.driver.new_conn()) })
+        }
+
+        fn set_uri(&mut self, _uri: &str) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    fn ds_rb(names: &[&str]) -> (RBatis, SqlLog) {
+        let log: SqlLog = Arc::new(SyncVec::new());
+        let rb = RBatis::new();
+        for name in names {
+            let driver = DsDriver {
+                ds: name.to_string(),
+                log: log.clone(),
+            };
+            rb.init_ds(name, driver, "test").unwrap();
+        }
+        (rb, log)
+    }
+
+    fn take_ds(log: &SqlLog) -> Vec<String> {
+        let ds = log.iter().map(|(ds, _)| ds.clone()).collect();
+        log.clear();
+        ds
+    }
+
+    // ==================== Register Tests ====================
+
+    #[test]
+    fn test_init_ds() {
+        let (rb, _log) = ds_rb(&[PRIMARY_DS, "replica-1", "analytics"]);
+        assert_eq!(rb.ds_names(), vec!["analytics", "primary", "replica-1"]);
+        assert!(rb.get_pool().is_ok());
+        assert!(rb.get_ds_pool("analytics").is_ok());
+        assert!(rb.get_ds_pool("not_exist").is_err());
+        let driver = DsDriver {
+            ds: "analytics".to_string(),
+            log: Arc::new(SyncVec::new()),
+        };
+        assert!(rb.init_ds("analytics", driver, "test").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_use_ds() {
+        let (rb, log) = ds_rb(&[PRIMARY_DS, "replica-1", "analytics"]);
+        let analytics = rb.use_ds("analytics");
+        analytics.exec("delete from report", vec![]).await.unwrap();
+        analytics
+            .query("select * from report", vec![])
+            .await
+            .unwrap();
+        let _: Vec<Value> = analytics
+            .exec_decode("select * from report", vec![])
+            .await
+            .unwrap();
+        let tx = analytics.acquire_begin().await.unwrap();
+        tx.exec("update report set a = 1", vec![]).await.unwrap();
+        tx.commit().await.unwrap();
+        assert!(take_ds(&log).iter().all(|ds| ds == "analytics"));
+
+        let err = rb.use_ds("not_exist").exec("select 1", vec![]).await;
+        assert!(err.is_err());
+    }
+
+    // ==================== DefaultRouter Tests ====================
+
+    #[tokio::test]
+    async fn test_default_router_read_write() {
+        let (rb, log) = ds_rb(&[PRIMARY_DS, "replica-1", "replica-2", "analytics"]);
+        rb.exec("insert into a values (1)", vec![]).await.unwrap();
+        assert_eq!(take_ds(&log), vec!["primary"]);
+
+        for _ in 0..4 {
+            rb.query("select * from a", vec![]).await.unwrap();
+        }
+        assert_eq!(
+            take_ds(&log),
+            vec!["replica-1", "replica-2", "replica-1", "replica-2"]
+        );
+
+        let _: Vec<Value> = rb.exec_decode("select * from a", vec![]).await.unwrap();
+        assert_eq!(take_ds(&log), vec!["replica-1"]);
+
+        rb.query("select * from a for update", vec![])
+            .await
+            .unwrap();
+        assert_eq!(take_ds(&log), vec!["primary"]);
+    }
+
+    #[tokio::test]
+    async fn test_default_router_tx_use_primary() {
+        let (rb, log) = ds_rb(&[PRIMARY_DS, "replica-1"]);
+        let tx = rb.acquire_begin().await.unwrap();
+        tx.query("select * from a", vec![]).await.unwrap();
+        tx.commit().await.unwrap();
+        assert!(take_ds(&log).iter().all(|ds| ds == "primary"));
+    }
+
+    #[tokio::test]
+    async fn test_default_router_without_replica() {
+        let (rb, log) = ds_rb(&[PRIMARY_DS, "analytics"]);
+        rb.query("select * from a", vec![]).await.unwrap();
+        assert_eq!(take_ds(&log), vec!["primary"]);
+    }
+
+    #[test]
+    fn test_default_router_is_read() {
+        assert!(DefaultRouter::is_read(" SELECT * from a"));
+        assert!(!DefaultRouter::is_read("update a set b = 1"));
+        assert!(!DefaultRouter::is_read("select /*rb:primary*/ * from a"));
+        assert!(!DefaultRouter::is_read("select * from a for update"));
+        assert!(!DefaultRouter::is_read(
+            "select * from a lock in share mode"
+        ));
+    }
+
+    // ==================== Custom Router Tests ====================
+
+    #[derive(Debug)]
+    struct AnalyticsRouter;
+
+    impl DataSourceRouter for AnalyticsRouter {
+        fn route(&self, _rb: &RBatis, kind: RouteKind, sql: &str) -> Option<String> {
+            if kind == RouteKind::Query && sql.contains("report") {
+                return Some("analytics".to_string());
+            }
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_router() {
+        let (rb, log) = ds_rb(&[PRIMARY_DS, "replica-1", "analytics"]);
+        rb.set_router(AnalyticsRouter);
+        rb.query("select * from report", vec![]).await.unwrap();
+        rb.query("select * from a", vec![]).await.unwrap();
+        assert_eq!(take_ds(&log), vec!["analytics", "primary"]);
+    }
+}
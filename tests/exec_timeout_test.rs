@@ -0,0 +1,335 @@
+//! Tests for statement timeout:
+//! - RBatis::set_exec_timeout() / get_exec_timeout()
+//! - RBatisConnExecutor::with_timeout()
+//! - RBatisTxExecutor::with_timeout()
+//! - timeout connection is discarded instead of reuse by the pool
+//! - DefaultPool drops the closed connection
+//! - query_stream() timeout
+
+#[cfg(test)]
+mod test {
+    use futures_core::future::BoxFuture;
+    use futures_core::Stream;
+    use rbatis::{is_timeout, Error, RBatis};
+    use rbdc::db::{ConnectOptions, Connection, Driver, ExecResult, Row};
+    use rbs::Value;
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    // ==================== Mock Infrastructure ====================
+
+    #[derive(Debug, Clone, Default)]
+    struct SlowDriver {
+        connects: Arc<AtomicUsize>,
+        closes: Arc<AtomicUsize>,
+    }
+
+    impl SlowDriver {
+        fn new_conn(&self) -> Box<dyn Connection> {
+            self.connects.fetch_add(1, Ordering::SeqCst);
+            Box::new(SlowConnection {
+                closes: self.closes.clone(),
+                closed: false,
+            })
+        }
+    }
+
+    impl Driver for SlowDriver {
+        fn name(&self) -> &str {
+            "test"
+        }
+
+        fn connect(&self, _url: &str) -> BoxFuture<'_, Result<Box<dyn Connection>, Error>> {
+            Box::pin(async { Ok(self.new_conn()) })
+        }
+
+        fn connect_opt<'a>(
+            &'a self,
+            _option: &'a dyn ConnectOptions,
+        ) -> BoxFuture<'a, Result<Box<dyn Connection>, Error>> {
+            Box::pin(async { Ok(self.new_conn()) })
+        }
+
+        fn default_option(&self) -> Box<dyn ConnectOptions> {
+            Box::new(SlowConnectOptions {
+                driver: self.clone(),
+            })
+        }
+    }
+
+    /// the sql start with `sleep` will sleep 1 second
+    #[derive(Debug)]
+    struct SlowConnection {
+        closes: Arc<AtomicUsize>,
+        closed: bool,
+    }
+
+    impl Connection for SlowConnection {
+        fn exec_rows(
+            &mut self,
+            sql: &str,
+            _params: Vec<Value>,
+        ) -> BoxFuture<
+            '_,
+            Result<Pin<Box<dyn Stream<Item = Result<Box<dyn Row>, Error>> + Send + '_>>, Error>,
+        > {
+            let slow = sql.starts_with("sleep");
+            Box::pin(async move {
+                if slow {
+                    rbdc::rt::sleep(Duration::from_secs(1)).await;
+                }
+                Err(Error::from("mock"))
+            })
+        }
+
+        fn exec_decode(
+            &mut self,
+            sql: &str,
+            _params: Vec<Value>,
+        ) -> BoxFuture<'_, Result<Value, Error>> {
+            let slow = sql.starts_with("sleep");
+            Box::pin(async move {
+                if slow {
+                    rbdc::rt::sleep(Duration::from_secs(1)).await;
+                }
+                Ok(Value::Array(vec![]))
+            })
+        }
+
+        fn exec(
+            &mut self,
+            sql: &str,
+            _params: Vec<Value>,
+        ) -> BoxFuture<'_, Result<ExecResult, Error>> {
+            let slow = sql.starts_with("sleep");
+            Box::pin(async move {
+                if slow {
+                    rbdc::rt::sleep(Duration::from_secs(1)).await;
+                }
+                Ok(ExecResult {
+                    rows_affected: 1,
+                    last_insert_id: Value::Null,
+                })
+            })
+        }
+
+        fn close(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+            if !self.closed {
+                self.closed = true;
+                self.closes.fetch_add(1, Ordering::SeqCst);
+            }
+            Box::pin(async { Ok(()) })
+        }
+
+        fn ping(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+            let closed = self.closed;
+            Box::pin(async move {
+                if closed {
+                    return Err(Error::from("connection closed"));
+                }
+                Ok(())
+            })
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct SlowConnectOptions {
+        driver: SlowDriver,
+    }
+
+    impl ConnectOptions for SlowConnectOptions {
+        fn connect(&self) -> BoxFuture<'_, Result<Box<dyn Connection>, Error>> {
+            Box::pin(async { Ok(self.driver.new_conn()) })
+        }
+
+        fn set_uri(&mut self, _uri: &str) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    fn slow_rb() -> (RBatis, SlowDriver) {
+        let driver = SlowDriver::default();
+        let rb = RBatis::new();
+        rb.init(driver.clone(), "test").unwrap();
+        (rb, driver)
+    }
+
+    // ==================== Timeout Tests ====================
+
+    #[tokio::test]
+    async fn test_conn_with_timeout() {
+        let (rb, driver) = slow_rb();
+        let conn = rb.acquire().await.unwrap();
+        let r = conn.exec("update fast", vec![]).await.unwrap();
+        assert_eq!(r.rows_affected, 1);
+
+        let err = conn
+            .with_timeout(Duration::from_millis(20))
+            .exec("sleep", vec![])
+            .await
+            .unwrap_err();
+        assert!(is_timeout(&err), "{}", err);
+        // the connection is discarded
+        let err = conn.exec("update fast", vec![]).await.unwrap_err();
+        assert!(!is_timeout(&err));
+        rbdc::rt::sleep(Duration::from_millis(20)).await;
+        assert_eq!(driver.closes.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_conn_with_timeout_not_elapsed() {
+        let (rb, driver) = slow_rb();
+        let conn = rb.acquire().await.unwrap();
+        let r: Vec<Value> = conn
+            .with_timeout(Duration::from_secs(5))
+            .exec_decode("select fast", vec![])
+            .await
+            .unwrap();
+        assert!(r.is_empty());
+        assert_eq!(driver.closes.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_rbatis_exec_timeout() {
+        let (rb, _driver) = slow_rb();
+        assert_eq!(rb.get_exec_timeout(), None);
+        rb.set_exec_timeout(Some(Duration::from_millis(20)));
+        assert_eq!(rb.get_exec_timeout(), Some(Duration::from_millis(20)));
+
+        let err = rb.exec("sleep", vec![]).await.unwrap_err();
+        assert!(is_timeout(&err));
+        let err = rb.query("sleep", vec![]).await.unwrap_err();
+        assert!(is_timeout(&err));
+        let err = rb
+            .exec_decode::<Vec<Value>>("sleep", vec![])
+            .await
+            .unwrap_err();
+        assert!(is_timeout(&err));
+        assert!(rb.exec("update fast", vec![]).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_conn_timeout_override_rbatis() {
+        let (rb, _driver) = slow_rb();
+        rb.set_exec_timeout(Some(Duration::from_millis(20)));
+        let conn = rb.acquire().await.unwrap();
+        assert_eq!(conn.get_timeout(), Some(Duration::from_millis(20)));
+        let conn = conn.with_timeout(Duration::from_secs(5));
+        assert_eq!(conn.get_timeout(), Some(Duration::from_secs(5)));
+        assert!(conn.exec("sleep", vec![]).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_tx_with_timeout() {
+        let (rb, _driver) = slow_rb();
+        let tx = rb.acquire_begin().await.unwrap();
+        let err = tx
+            .with_timeout(Duration::from_millis(20))
+            .query("sleep", vec![])
+            .await
+            .unwrap_err();
+        assert!(is_timeout(&err));
+        assert!(tx.commit().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_timeout_conn_not_reuse() {
+        let (rb, driver) = slow_rb();
+        rb.set_exec_timeout(Some(Duration::from_millis(20)));
+        assert!(rb.exec("sleep", vec![]).await.is_err());
+        rbdc::rt::sleep(Duration::from_millis(20)).await;
+        let connects = driver.connects.load(Ordering::SeqCst);
+        assert!(rb.exec("update fast", vec![]).await.is_ok());
+        assert!(driver.connects.load(Ordering::SeqCst) > connects);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_conn_dropped_by_pool() {
+        let (rb, driver) = slow_rb();
+        rb.get_pool().unwrap().set_max_open_conns(1).await;
+        rb.set_exec_timeout(Some(Duration::from_millis(20)));
+        assert!(rb.exec("update fast", vec![]).await.is_ok());
+        let state = rb.get_pool().unwrap().state().await;
+        assert_eq!(state["connections"], Value::from(1u64));
+        assert_eq!(state["idle"], Value::from(1u64));
+
+        assert!(rb.exec("sleep", vec![]).await.is_err());
+        rbdc::rt::sleep(Duration::from_millis(20)).await;
+        assert_eq!(driver.closes.load(Ordering::SeqCst), 1);
+        // the closed connection is dropped from the pool, not returned to the idle queue
+        let state = rb.get_pool().unwrap().state().await;
+        assert_eq!(state["connections"], Value::from(0u64));
+        assert_eq!(state["idle"], Value::from(0u64));
+        assert_eq!(state["in_use"], Value::from(0u64));
+        assert!(rb.exec("update fast", vec![]).await.is_ok());
+        assert_eq!(driver.connects.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_closed_conn_dropped_by_pool() {
+        let (rb, driver) = slow_rb();
+        let conn = rb.acquire().await.unwrap();
+        let other = rb.acquire().await.unwrap();
+        drop(other);
+        conn.conn.lock().await.close().await.unwrap();
+        let state = rb.get_pool().unwrap().state().await;
+        assert_eq!(state["connections"], Value::from(1u64));
+        assert_eq!(state["idle"], Value::from(1u64));
+        assert_eq!(state["in_use"], Value::from(0u64));
+        assert_eq!(driver.closes.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_closed_conn_not_reuse_without_ping() {
+        use fast_pool::plugin::CheckMode;
+        use rbatis::DefaultPool;
+        use rbdc::pool::{ConnectionManager, Pool};
+
+        let driver = SlowDriver::default();
+        let pool =
+            DefaultPool::new(ConnectionManager::new(driver.clone(), "test").unwrap()).unwrap();
+        // skip the ping of the pool check
+        pool.inner
+            .manager
+            .inner
+            .mode
+            .set_mode(CheckMode::SkipInterval(Duration::from_secs(60)));
+        pool.set_max_open_conns(1).await;
+        let rb = RBatis::new();
+        rb.init_pool(pool).unwrap();
+        rb.set_exec_timeout(Some(Duration::from_millis(20)));
+        assert!(rb.exec("sleep", vec![]).await.is_err());
+        rbdc::rt::sleep(Duration::from_millis(20)).await;
+        assert!(rb.exec("update fast", vec![]).await.is_ok());
+        assert_eq!(driver.connects.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_query_stream_timeout() {
+        use futures::StreamExt;
+        let (rb, driver) = slow_rb();
+        let conn = rb.acquire().await.unwrap();
+        let mut stream = conn
+            .with_timeout(Duration::from_millis(20))
+            .query_stream("sleep", vec![])
+            .await
+            .unwrap();
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert!(is_timeout(&err), "{}", err);
+        rbdc::rt::sleep(Duration::from_millis(20)).await;
+        assert_eq!(driver.closes.load(Ordering::SeqCst), 1);
+
+        rb.set_exec_timeout(Some(Duration::from_millis(20)));
+        let mut stream = rb.query_stream("sleep", vec![]).await.unwrap();
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert!(is_timeout(&err), "{}", err);
+    }
+
+    #[test]
+    fn test_is_timeout() {
+        assert!(is_timeout(&rbatis::timeout_error(Duration::from_secs(1))));
+        assert!(!is_timeout(&Error::from("connection closed")));
+    }
+}
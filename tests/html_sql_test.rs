@@ -210,6 +210,7 @@ mod test {
         pub count: u64, //page count num
     }
 
+    #[cfg(not(feature = "strict_sql"))]
     #[test]
     fn test_exec_decode() {
         let f = async move {
@@ -235,6 +236,36 @@ mod test {
         block_on(f);
     }
 
+    #[test]
+    fn test_safe_arg() {
+        let f = async move {
+            let mut rb = RBatis::new();
+            rb.init(MockDriver {}, "test").unwrap();
+            let queue = Arc::new(SyncVec::new());
+            rb.set_intercepts(vec![Arc::new(MockIntercept::new(queue.clone()))]);
+            #[html_sql(
+                r#"<mapper>
+            <select id="select_by_table">
+            select * from ${safe table} where id = #{id}
+            </select>
+            </mapper>"#
+            )]
+            pub async fn select_by_table(
+                rb: &RBatis,
+                table: &str,
+                id: &u64,
+            ) -> Result<Value, Error> {
+                impled!()
+            }
+            let r = select_by_table(&rb, "t", &1).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "select * from t where id = ?");
+            assert_eq!(args, vec![Value::U64(1)]);
+        };
+        block_on(f);
+    }
+
+    #[cfg(not(feature = "strict_sql"))]
     #[test]
     fn test_macro() {
         let f = async move {
@@ -293,6 +324,7 @@ mod test {
         block_on(f);
     }
 
+    #[cfg(not(feature = "strict_sql"))]
     #[test]
     fn test_method_call() {
         let f = async move {
@@ -319,6 +351,7 @@ mod test {
         block_on(f);
     }
 
+    #[cfg(not(feature = "strict_sql"))]
     #[test]
     fn test_binary() {
         let f = async move {
@@ -336,6 +369,7 @@ mod test {
         block_on(f);
     }
 
+    #[cfg(not(feature = "strict_sql"))]
     #[test]
     fn test_unary() {
         let f = async move {
@@ -353,6 +387,7 @@ mod test {
         block_on(f);
     }
 
+    #[cfg(not(feature = "strict_sql"))]
     #[test]
     fn test_paren() {
         let f = async move {
@@ -370,6 +405,7 @@ mod test {
         block_on(f);
     }
 
+    #[cfg(not(feature = "strict_sql"))]
     #[test]
     fn test_field() {
         let f = async move {
@@ -407,6 +443,7 @@ mod test {
         block_on(f);
     }
 
+    #[cfg(not(feature = "strict_sql"))]
     #[test]
     fn test_reference() {
         let f = async move {
@@ -443,6 +480,7 @@ mod test {
         block_on(f);
     }
 
+    #[cfg(not(feature = "strict_sql"))]
     #[test]
     fn test_index() {
         let f = async move {
@@ -461,6 +499,7 @@ mod test {
         block_on(f);
     }
 
+    #[cfg(not(feature = "strict_sql"))]
     #[test]
     fn test_lit() {
         let f = async move {
@@ -509,6 +548,7 @@ mod test {
         block_on(f);
     }
 
+    #[cfg(not(feature = "strict_sql"))]
     #[test]
     fn test_bind() {
         let f = async move {
@@ -546,6 +586,7 @@ mod test {
         block_on(f);
     }
 
+    #[cfg(not(feature = "strict_sql"))]
     #[test]
     fn test_for() {
         let f = async move {
@@ -563,6 +604,7 @@ mod test {
         block_on(f);
     }
 
+    #[cfg(not(feature = "strict_sql"))]
     #[test]
     fn test_for_item() {
         let f = async move {
@@ -680,6 +722,7 @@ mod test {
         block_on(f);
     }
 
+    #[cfg(not(feature = "strict_sql"))]
     #[test]
     fn test_impl_level() {
         let f = async move {
@@ -717,6 +760,7 @@ mod test {
         block_on(f);
     }
 
+    #[cfg(not(feature = "strict_sql"))]
     #[test]
     fn test_impl_generic() {
         let f = async move {
@@ -765,6 +809,7 @@ mod test {
         block_on(f);
     }
 
+    #[cfg(not(feature = "strict_sql"))]
     #[test]
     fn test_impl_trait() {
         let f = async move {
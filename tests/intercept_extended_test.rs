@@ -6,6 +6,7 @@
 //! - RBatis::remove_intercept_dyn
 //! - RBatis::get_intercept_dyn
 //! - RBatis::is_debug_mode / driver_type
+//! - Intercept on_begin / on_commit / on_rollback hooks
 
 #[cfg(test)]
 mod test {
@@ -517,6 +518,125 @@ mod test {
         assert_eq!(result.rows_affected, 999);
     }
 
+    // ==================== Transaction Lifecycle Hook Tests ====================
+
+    type TxEvents = Arc<std::sync::Mutex<Vec<(String, i64)>>>;
+
+    #[derive(Debug)]
+    struct TxHookIntercept {
+        pub events: TxEvents,
+    }
+
+    #[async_trait]
+    impl Intercept for TxHookIntercept {
+        async fn on_begin(&self, task_id: i64, _rb: &dyn Executor) -> Result<(), Error> {
+            self.events
+                .lock()
+                .unwrap()
+                .push(("begin".to_string(), task_id));
+            Ok(())
+        }
+
+        async fn on_commit(&self, task_id: i64, _rb: &dyn Executor) -> Result<(), Error> {
+            self.events
+                .lock()
+                .unwrap()
+                .push(("commit".to_string(), task_id));
+            Ok(())
+        }
+
+        async fn on_rollback(&self, task_id: i64, _rb: &dyn Executor) -> Result<(), Error> {
+            self.events
+                .lock()
+                .unwrap()
+                .push(("rollback".to_string(), task_id));
+            Ok(())
+        }
+    }
+
+    fn tx_hook_rb() -> (RBatis, TxEvents) {
+        let events = Arc::new(std::sync::Mutex::new(vec![]));
+        let mut rb = RBatis::new();
+        rb.set_intercepts(vec![Arc::new(TxHookIntercept {
+            events: events.clone(),
+        })]);
+        rb.init(MockDriver {}, "test").unwrap();
+        (rb, events)
+    }
+
+    #[tokio::test]
+    async fn test_intercept_tx_hooks_commit() {
+        let (rb, events) = tx_hook_rb();
+        let tx = rb.acquire_begin().await.unwrap();
+        let tx_id = tx.tx_id;
+        tx.commit().await.unwrap();
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![("begin".to_string(), tx_id), ("commit".to_string(), tx_id)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_intercept_tx_hooks_rollback() {
+        let (rb, events) = tx_hook_rb();
+        let tx = rb.acquire_begin().await.unwrap();
+        let tx_id = tx.tx_id;
+        tx.rollback().await.unwrap();
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ("begin".to_string(), tx_id),
+                ("rollback".to_string(), tx_id)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_intercept_tx_hooks_skip_savepoint() {
+        let (rb, events) = tx_hook_rb();
+        let tx = rb.acquire_begin().await.unwrap();
+        let tx_id = tx.tx_id;
+        let nested = tx.begin_nested().await.unwrap();
+        nested.rollback().await.unwrap();
+        let nested = tx.begin_nested().await.unwrap();
+        nested.commit().await.unwrap();
+        tx.commit().await.unwrap();
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![("begin".to_string(), tx_id), ("commit".to_string(), tx_id)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_intercept_tx_hooks_error() {
+        #[derive(Debug)]
+        struct RejectCommitIntercept;
+
+        #[async_trait]
+        impl Intercept for RejectCommitIntercept {
+            async fn on_commit(&self, _task_id: i64, _rb: &dyn Executor) -> Result<(), Error> {
+                Err(Error::from("reject commit"))
+            }
+        }
+
+        let mut rb = RBatis::new();
+        rb.set_intercepts(vec![Arc::new(RejectCommitIntercept)]);
+        rb.init(MockDriver {}, "test").unwrap();
+        let tx = rb.acquire_begin().await.unwrap();
+        // the tx is already committed, the hook error is only logged
+        tx.commit().await.unwrap();
+        assert!(tx.done());
+    }
+
+    #[tokio::test]
+    async fn test_intercept_tx_hooks_not_called_by_conn_executor() {
+        let (rb, events) = tx_hook_rb();
+        let conn = rb.acquire().await.unwrap();
+        conn.commit().await.unwrap();
+        conn.rollback().await.unwrap();
+        assert!(events.lock().unwrap().is_empty());
+    }
+
     // ==================== set_intercepts Tests ====================
 
     #[test]
@@ -0,0 +1,329 @@
+//! Tests for ReadWriteSplitIntercept:
+//! - round-robin / weighted replica choice
+//! - write, tx, hint and use_ds run on the primary
+//! - fall back to the primary if the replica fails
+//! - replica query run with the timeout and the other intercepts
+
+#[cfg(test)]
+mod test {
+    use dark_std::sync::SyncVec;
+    use futures_core::future::BoxFuture;
+    use futures_core::Stream;
+    use rbatis::executor::Executor;
+    use rbatis::intercept::{Intercept, ResultType};
+    use rbatis::intercept_read_write::ReadWriteSplitIntercept;
+    use rbatis::{Action, Error, RBatis, PRIMARY_DS, PRIMARY_HINT};
+    use rbdc::db::{ConnectOptions, Connection, Driver, ExecResult, Row};
+    use rbs::Value;
+    use std::pin::Pin;
+    use std::sync::Arc;
+
+    // ==================== Mock Infrastructure ====================
+
+    /// record (datasource, sql)
+    type SqlLog = Arc<SyncVec<(String, String)>>;
+
+    #[derive(Debug, Clone)]
+    struct DsDriver {
+        ds: String,
+        log: SqlLog,
+        fail: bool,
+    }
+
+    impl DsDriver {
+        fn new_conn(&self) -> Box<dyn Connection> {
+            Box::new(DsConnection {
+                ds: self.ds.clone(),
+                log: self.log.clone(),
+                fail: self.fail,
+            })
+        }
+    }
+
+    impl Driver for DsDriver {
+        fn name(&self) -> &str {
+            "test"
+        }
+
+        fn connect(&self, _url: &str) -> BoxFuture<'_, Result<Box<dyn Connection>, Error>> {
+            Box::pin(async { Ok(self.new_conn()) })
+        }
+
+        fn connect_opt<'a>(
+            &'a self,
+            _option: &'a dyn ConnectOptions,
+        ) -> BoxFuture<'a, Result<Box<dyn Connection>, Error>> {
+            Box::pin(async { Ok(self.new_conn()) })
+        }
+
+        fn default_option(&self) -> Box<dyn ConnectOptions> {
+            Box::new(DsConnectOptions {
+                driver: self.clone(),
+            })
+        }
+    }
+
+    #[derive(Debug)]
+    struct DsConnection {
+        ds: String,
+        log: SqlLog,
+        fail: bool,
+    }
+
+    impl Connection for DsConnection {
+        fn exec_rows(
+            &mut self,
+            _sql: &str,
+            _params: Vec<Value>,
+        ) -> BoxFuture<
+            '_,
+            Result<Pin<Box<dyn Stream<Item = Result<Box<dyn Row>, Error>> + Send + '_>>, Error>,
+        > {
+            Box::pin(async { Err(Error::from("mock")) })
+        }
+
+        fn exec_decode(
+            &mut self,
+            sql: &str,
+            _params: Vec<Value>,
+        ) -> BoxFuture<'_, Result<Value, Error>> {
+            self.log.push((self.ds.clone(), sql.to_string()));
+            let fail = self.fail;
+            let slow = sql.contains("sleep");
+            Box::pin(async move {
+                if slow {
+                    rbdc::rt::sleep(std::time::Duration::from_secs(1)).await;
+                }
+                if fail {
+                    return Err(Error::from("replica is down"));
+                }
+                Ok(Value::Array(vec![]))
+            })
+        }
+
+        fn exec(
+            &mut self,
+            sql: &str,
+            _params: Vec<Value>,
+        ) -> BoxFuture<'_, Result<ExecResult, Error>> {
+            self.log.push((self.ds.clone(), sql.to_string()));
+            Box::pin(async { Ok(ExecResult::default()) })
+        }
+
+        fn close(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+            Box::pin(async { Ok(()) })
+        }
+
+        fn ping(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct DsConnectOptions {
+        driver: DsDriver,
+    }
+
+    impl ConnectOptions for DsConnectOptions {
+        fn connect(&self) -> BoxFuture<'_, Result<Box<dyn Connection>, Error>> {
+            Box::pin(async { Ok(self.driver.new_conn()) })
+        }
+
+        fn set_uri(&mut self, _uri: &str) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    /// (name, fail)
+    fn split_rb(names: &[(&str, bool)], intercept: ReadWriteSplitIntercept) -> (RBatis, SqlLog) {
+        let log: SqlLog = Arc::new(SyncVec::new());
+        let rb = RBatis::new();
+        for (name, fail) in names {
+            let driver = DsDriver {
+                ds: name.to_string(),
+                log: log.clone(),
+                fail: *fail,
+            };
+            rb.init_ds(name, driver, "test").unwrap();
+        }
+        rb.intercepts.push(Arc::new(intercept));
+        (rb, log)
+    }
+
+    fn take_ds(log: &SqlLog) -> Vec<String> {
+        let ds = log.iter().map(|(ds, _)| ds.clone()).collect();
+        log.clear();
+        ds
+    }
+
+    // ==================== Choice Tests ====================
+
+    #[test]
+    fn test_next_replica_round_robin() {
+        let intercept = ReadWriteSplitIntercept::new(vec!["r1", "r2"]);
+        let names: Vec<&str> = (0..4).map(|_| intercept.next_replica().unwrap()).collect();
+        assert_eq!(names, vec!["r1", "r2", "r1", "r2"]);
+    }
+
+    #[test]
+    fn test_next_replica_weighted() {
+        let intercept =
+            ReadWriteSplitIntercept::new_weighted(vec![("r1", 3), ("r2", 1), ("r3", 0)]);
+        let names: Vec<&str> = (0..8).map(|_| intercept.next_replica().unwrap()).collect();
+        assert_eq!(names, vec!["r1", "r1", "r1", "r2", "r1", "r1", "r1", "r2"]);
+        let empty = ReadWriteSplitIntercept::new(Vec::<String>::new());
+        assert!(empty.next_replica().is_none());
+    }
+
+    // ==================== Split Tests ====================
+
+    #[tokio::test]
+    async fn test_split_select() {
+        let (rb, log) = split_rb(
+            &[(PRIMARY_DS, false), ("read-1", false), ("read-2", false)],
+            ReadWriteSplitIntercept::new(vec!["read-1", "read-2"]),
+        );
+        rb.query("select * from a", vec![]).await.unwrap();
+        let conn = rb.acquire().await.unwrap();
+        conn.query("select * from a", vec![]).await.unwrap();
+        let _: Vec<Value> = rb.exec_decode("select * from a", vec![]).await.unwrap();
+        assert_eq!(take_ds(&log), vec!["read-1", "read-2", "read-1"]);
+
+        rb.exec("update a set b = 1", vec![]).await.unwrap();
+        rb.query("select * from a for update", vec![])
+            .await
+            .unwrap();
+        assert_eq!(take_ds(&log), vec!["primary", "primary"]);
+    }
+
+    #[tokio::test]
+    async fn test_split_primary_hint() {
+        let (rb, log) = split_rb(
+            &[(PRIMARY_DS, false), ("read-1", false)],
+            ReadWriteSplitIntercept::new(vec!["read-1"]),
+        );
+        let sql = format!("{} select * from a", PRIMARY_HINT);
+        rb.query(&sql, vec![]).await.unwrap();
+        assert_eq!(take_ds(&log), vec!["primary"]);
+    }
+
+    #[tokio::test]
+    async fn test_split_not_in_tx() {
+        let (rb, log) = split_rb(
+            &[(PRIMARY_DS, false), ("read-1", false)],
+            ReadWriteSplitIntercept::new(vec!["read-1"]),
+        );
+        let tx = rb.acquire_begin().await.unwrap();
+        tx.query("select * from a", vec![]).await.unwrap();
+        tx.commit().await.unwrap();
+        assert!(take_ds(&log).iter().all(|ds| ds == "primary"));
+    }
+
+    #[tokio::test]
+    async fn test_split_not_on_use_ds() {
+        let (rb, log) = split_rb(
+            &[(PRIMARY_DS, false), ("read-1", false), ("analytics", false)],
+            ReadWriteSplitIntercept::new(vec!["read-1"]),
+        );
+        rb.use_ds("analytics")
+            .query("select * from a", vec![])
+            .await
+            .unwrap();
+        assert_eq!(take_ds(&log), vec!["analytics"]);
+    }
+
+    #[tokio::test]
+    async fn test_split_fall_back_primary() {
+        let (rb, log) = split_rb(
+            &[(PRIMARY_DS, false), ("read-1", true)],
+            ReadWriteSplitIntercept::new(vec!["read-1", "not_exist"]),
+        );
+        rb.query("select * from a", vec![]).await.unwrap();
+        assert_eq!(take_ds(&log), vec!["read-1", "primary"]);
+        rb.query("select * from a", vec![]).await.unwrap();
+        assert_eq!(take_ds(&log), vec!["primary"]);
+    }
+
+    #[tokio::test]
+    async fn test_split_with_other_intercepts() {
+        /// rewrite the sql in before(), record the result in after()
+        #[derive(Debug)]
+        struct RewriteIntercept {
+            results: Arc<SyncVec<Value>>,
+        }
+
+        #[async_trait::async_trait]
+        impl Intercept for RewriteIntercept {
+            async fn before(
+                &self,
+                _task_id: i64,
+                _rb: &dyn Executor,
+                sql: &mut String,
+                _args: &mut Vec<Value>,
+                _result: ResultType<&mut Result<ExecResult, Error>, &mut Result<Value, Error>>,
+            ) -> Result<Action, Error> {
+                *sql = sql.replace("from a", "from b");
+                Ok(Action::Next)
+            }
+
+            async fn after(
+                &self,
+                _task_id: i64,
+                _rb: &dyn Executor,
+                _sql: &mut String,
+                _args: &mut Vec<Value>,
+                result: ResultType<&mut Result<ExecResult, Error>, &mut Result<Value, Error>>,
+            ) -> Result<Action, Error> {
+                if let ResultType::Query(Ok(v)) = result {
+                    self.results.push(v.clone());
+                }
+                Ok(Action::Next)
+            }
+        }
+
+        let (rb, log) = split_rb(
+            &[(PRIMARY_DS, false), ("read-1", false)],
+            ReadWriteSplitIntercept::new(vec!["read-1"]),
+        );
+        let results = Arc::new(SyncVec::new());
+        // push after the split intercept
+        rb.intercepts.push(Arc::new(RewriteIntercept {
+            results: results.clone(),
+        }));
+        rb.query("select * from a", vec![]).await.unwrap();
+        let sqls: Vec<(String, String)> = log.iter().cloned().collect();
+        assert_eq!(
+            sqls,
+            vec![("read-1".to_string(), "select * from b".to_string())]
+        );
+        assert_eq!(results.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_split_timeout_fall_back_primary() {
+        let (rb, log) = split_rb(
+            &[(PRIMARY_DS, false), ("read-1", false)],
+            ReadWriteSplitIntercept::new(vec!["read-1"]),
+        );
+        rb.set_exec_timeout(Some(std::time::Duration::from_millis(20)));
+        let err = rb.query("select sleep", vec![]).await.unwrap_err();
+        assert!(rbatis::is_timeout(&err), "{}", err);
+        assert_eq!(take_ds(&log), vec!["read-1", "primary"]);
+    }
+
+    #[test]
+    fn test_is_tx() {
+        let f = async move {
+            let (rb, _log) = split_rb(
+                &[(PRIMARY_DS, false)],
+                ReadWriteSplitIntercept::new(vec!["read-1"]),
+            );
+            let conn = rb.acquire().await.unwrap();
+            assert!(!conn.is_tx());
+            let tx = conn.begin().await.unwrap();
+            assert!(tx.is_tx());
+            assert!(tx.defer_async(|_| async {}).is_tx());
+        };
+        rbdc::rt::block_on(f);
+    }
+}
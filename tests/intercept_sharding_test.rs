@@ -0,0 +1,491 @@
+//! Tests for ShardingIntercept:
+//! - ShardingAlgorithm Mod / Range / Hash
+//! - table rewrite and datasource choose by sharding key
+//! - fan out and merge without sharding key
+//! - insert, tx and crud! integration
+
+#[macro_use]
+extern crate rbatis;
+
+#[cfg(test)]
+mod test {
+    use async_trait::async_trait;
+    use dark_std::sync::SyncVec;
+    use futures_core::future::BoxFuture;
+    use futures_core::Stream;
+    use rbatis::executor::Executor;
+    use rbatis::intercept::{Intercept, ResultType};
+    use rbatis::intercept_sharding::{ShardingAlgorithm, ShardingIntercept, ShardingRule};
+    use rbatis::{Action, Error, RBatis, PRIMARY_DS};
+    use rbdc::db::{ConnectOptions, Connection, Driver, ExecResult, Row};
+    use rbs::{value, Value};
+    use serde::{Deserialize, Serialize};
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    // ==================== Mock Infrastructure ====================
+
+    /// record (datasource, sql)
+    type SqlLog = Arc<SyncVec<(String, String)>>;
+
+    #[derive(Debug, Clone)]
+    struct DsDriver {
+        ds: String,
+        log: SqlLog,
+    }
+
+    impl DsDriver {
+        fn new_conn(&self) -> Box<dyn Connection> {
+            Box::new(DsConnection {
+                ds: self.ds.clone(),
+                log: self.log.clone(),
+            })
+        }
+    }
+
+    impl Driver for DsDriver {
+        fn name(&self) -> &str {
+            "test"
+        }
+
+        fn connect(&self, _url: &str) -> BoxFuture<'_, Result<Box<dyn Connection>, Error>> {
+            Box::pin(async { Ok(self.new_conn()) })
+        }
+
+        fn connect_opt<'a>(
+            &'a self,
+            _option: &'a dyn ConnectOptions,
+        ) -> BoxFuture<'a, Result<Box<dyn Connection>, Error>> {
+            Box::pin(async { Ok(self.new_conn()) })
+        }
+
+        fn default_option(&self) -> Box<dyn ConnectOptions> {
+            Box::new(DsConnectOptions {
+                driver: self.clone(),
+            })
+        }
+    }
+
+    #[derive(Debug)]
+    struct DsConnection {
+        ds: String,
+        log: SqlLog,
+    }
+
+    impl Connection for DsConnection {
+        fn exec_rows(
+            &mut self,
+            _sql: &str,
+            _params: Vec<Value>,
+        ) -> BoxFuture<
+            '_,
+            Result<Pin<Box<dyn Stream<Item = Result<Box<dyn Row>, Error>> + Send + '_>>, Error>,
+        > {
+            Box::pin(async { Err(Error::from("mock")) })
+        }
+
+        /// `select count` return 2, others return one row of (ds, sql)
+        fn exec_decode(
+            &mut self,
+            sql: &str,
+            _params: Vec<Value>,
+        ) -> BoxFuture<'_, Result<Value, Error>> {
+            self.log.push((self.ds.clone(), sql.to_string()));
+            let v = if sql.starts_with("select count") {
+                value! {"count": 2}
+            } else {
+                value! {"ds": self.ds.clone(), "sql": sql}
+            };
+            Box::pin(async { Ok(Value::Array(vec![v])) })
+        }
+
+        fn exec(
+            &mut self,
+            sql: &str,
+            _params: Vec<Value>,
+        ) -> BoxFuture<'_, Result<ExecResult, Error>> {
+            self.log.push((self.ds.clone(), sql.to_string()));
+            Box::pin(async {
+                Ok(ExecResult {
+                    rows_affected: 1,
+                    last_insert_id: Value::Null,
+                })
+            })
+        }
+
+        fn close(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+            Box::pin(async { Ok(()) })
+        }
+
+        fn ping(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct DsConnectOptions {
+        driver: DsDriver,
+    }
+
+    impl ConnectOptions for DsConnectOptions {
+        fn connect(&self) -> BoxFuture<'_, Result<Box<dyn Connection>, Error>> {
+            Box::pin(async { Ok(self.driver.new_conn()) })
+        }
+
+        fn set_uri(&mut self, _uri: &str) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    /// orders sharding by `user_id % 4`, shard 0,2 on primary, shard 1,3 on ds_1
+    fn sharding_rb() -> (RBatis, SqlLog) {
+        let log: SqlLog = Arc::new(SyncVec::new());
+        let rb = RBatis::new();
+        for name in [PRIMARY_DS, "ds_1"] {
+            let driver = DsDriver {
+                ds: name.to_string(),
+                log: log.clone(),
+            };
+            rb.init_ds(name, driver, "test").unwrap();
+        }
+        rb.intercepts.insert(
+            0,
+            Arc::new(
+                ShardingIntercept::new().rule(
+                    ShardingRule::new("orders", "user_id", ShardingAlgorithm::Mod(4))
+                        .datasources(vec![PRIMARY_DS, "ds_1"]),
+                ),
+            ),
+        );
+        (rb, log)
+    }
+
+    fn take_log(log: &SqlLog) -> Vec<(String, String)> {
+        let v = log.iter().cloned().collect();
+        log.clear();
+        v
+    }
+
+    // ==================== Algorithm Tests ====================
+
+    #[test]
+    fn test_algorithm_mod() {
+        let a = ShardingAlgorithm::Mod(4);
+        assert_eq!(a.shard_count(), 4);
+        assert_eq!(a.shard(&Value::I64(7)).unwrap(), 3);
+        assert_eq!(a.shard(&Value::U32(8)).unwrap(), 0);
+        assert_eq!(a.shard(&Value::String("5".to_string())).unwrap(), 1);
+        assert!(a.shard(&Value::String("a".to_string())).is_err());
+        assert!(ShardingAlgorithm::Mod(0).shard(&Value::I64(1)).is_err());
+    }
+
+    #[test]
+    fn test_algorithm_range() {
+        let a = ShardingAlgorithm::Range(vec![1000, 2000]);
+        assert_eq!(a.shard_count(), 3);
+        assert_eq!(a.shard(&Value::I64(999)).unwrap(), 0);
+        assert_eq!(a.shard(&Value::I64(1000)).unwrap(), 1);
+        assert_eq!(a.shard(&Value::I64(5000)).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_algorithm_hash() {
+        let a = ShardingAlgorithm::Hash(8);
+        let key = Value::String("user-a".to_string());
+        let shard = a.shard(&key).unwrap();
+        assert!(shard < 8);
+        assert_eq!(a.shard(&key).unwrap(), shard);
+    }
+
+    #[test]
+    fn test_rule_rewrite_and_shards() {
+        let rule = ShardingRule::new("orders", "user_id", ShardingAlgorithm::Mod(4));
+        assert_eq!(rule.physical_table(3), "orders_03");
+        assert!(rule.is_match("select * from `orders` where id = ?"));
+        assert!(!rule.is_match("select * from orders_detail"));
+        // the string literal or the column name is not the table
+        assert!(!rule.is_match("select * from t where name = 'from orders'"));
+        assert!(!rule.is_match("select orders from t where orders = ?"));
+        assert!(rule.is_match("select * from t join orders o on o.id = t.id"));
+        assert!(rule.is_match("select * from t, orders where t.id = orders.id"));
+        assert!(rule.is_match("update db.orders set status = ?"));
+        assert_eq!(
+            rule.rewrite_sql(
+                "select orders.orders from orders where orders = 'orders'",
+                1
+            ),
+            "select orders_01.orders from orders_01 where orders = 'orders'"
+        );
+        assert_eq!(
+            rule.rewrite_sql("select o.* from `orders` o where o.user_id = ?", 2),
+            "select o.* from `orders_02` o where o.user_id = ?"
+        );
+        let shards = rule
+            .shards(
+                "select * from orders where id = ? and user_id in (?, 6, '3')",
+                &[Value::I64(1), Value::I64(5)],
+            )
+            .unwrap();
+        assert_eq!(shards, Some(vec![1, 2, 3]));
+        let shards = rule
+            .shards("select * from orders where user_id > ?", &[Value::I64(1)])
+            .unwrap();
+        assert_eq!(shards, None);
+        let shards = rule
+            .shards(
+                "select * from orders where id = ? order by user_id",
+                &[Value::I64(1)],
+            )
+            .unwrap();
+        assert_eq!(shards, None);
+        // `or`, the parentheses and `not` run on all shards
+        for sql in [
+            "select * from orders where user_id = ? or status = ?",
+            "select * from orders where (user_id = ? and status = ?)",
+            "select * from orders where status = ? and not user_id = ?",
+        ] {
+            let shards = rule.shards(sql, &[Value::I64(1), Value::I64(2)]).unwrap();
+            assert_eq!(shards, None, "{}", sql);
+        }
+        let shards = rule
+            .shards(
+                "select * from orders where user_id = ? and name = 'a or b'",
+                &[Value::I64(1)],
+            )
+            .unwrap();
+        assert_eq!(shards, Some(vec![1]));
+    }
+
+    // ==================== Route Tests ====================
+
+    #[tokio::test]
+    async fn test_query_with_key() {
+        let (rb, log) = sharding_rb();
+        rb.query(
+            "select * from orders where user_id = ?",
+            vec![Value::I64(2)],
+        )
+        .await
+        .unwrap();
+        rb.query(
+            "select * from orders where user_id = ?",
+            vec![Value::I64(7)],
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            take_log(&log),
+            vec![
+                (
+                    "primary".to_string(),
+                    "select * from orders_02 where user_id = ?".to_string()
+                ),
+                (
+                    "ds_1".to_string(),
+                    "select * from orders_03 where user_id = ?".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_fan_out() {
+        let (rb, log) = sharding_rb();
+        let v = rb
+            .query("select * from orders where status = ?", vec![Value::I64(1)])
+            .await
+            .unwrap();
+        assert_eq!(v.as_array().unwrap().len(), 4);
+        let mut sqls: Vec<(String, String)> = take_log(&log);
+        sqls.sort();
+        assert_eq!(
+            sqls,
+            vec![
+                (
+                    "ds_1".to_string(),
+                    "select * from orders_01 where status = ?".to_string()
+                ),
+                (
+                    "ds_1".to_string(),
+                    "select * from orders_03 where status = ?".to_string()
+                ),
+                (
+                    "primary".to_string(),
+                    "select * from orders_00 where status = ?".to_string()
+                ),
+                (
+                    "primary".to_string(),
+                    "select * from orders_02 where status = ?".to_string()
+                ),
+            ]
+        );
+
+        let v = rb
+            .query("select count(1) as count from orders", vec![])
+            .await
+            .unwrap();
+        assert_eq!(v, Value::Array(vec![value! {"count": 8i64}]));
+    }
+
+    #[derive(Debug, Default)]
+    struct CountIntercept {
+        count: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Intercept for CountIntercept {
+        async fn before(
+            &self,
+            _task_id: i64,
+            _rb: &dyn Executor,
+            _sql: &mut String,
+            _args: &mut Vec<Value>,
+            _result: ResultType<&mut Result<ExecResult, Error>, &mut Result<Value, Error>>,
+        ) -> Result<Action, Error> {
+            self.count.fetch_add(1, Ordering::SeqCst);
+            Ok(Action::Next)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_not_run_intercepts() {
+        let (rb, log) = sharding_rb();
+        let counter = Arc::new(CountIntercept::default());
+        rb.intercepts.insert(0, counter.clone());
+        let v = rb
+            .query(
+                "select * from orders where user_id = ? or status = ?",
+                vec![Value::I64(1), Value::I64(2)],
+            )
+            .await
+            .unwrap();
+        assert_eq!(v.as_array().unwrap().len(), 4);
+        assert_eq!(take_log(&log).len(), 4);
+        assert_eq!(counter.count.load(Ordering::SeqCst), 1);
+
+        let tx = rb.acquire_begin().await.unwrap();
+        let r = tx
+            .exec(
+                "update orders set status = ? where id = ?",
+                vec![Value::I64(1), Value::I64(1)],
+            )
+            .await;
+        // shard 1,3 on ds_1
+        assert!(r.is_err());
+        tx.rollback().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_exec_fan_out() {
+        let (rb, log) = sharding_rb();
+        let r = rb
+            .exec(
+                "update orders set status = ? where id = ?",
+                vec![Value::I64(1), Value::I64(1)],
+            )
+            .await
+            .unwrap();
+        assert_eq!(r.rows_affected, 4);
+        assert_eq!(take_log(&log).len(), 4);
+
+        let r = rb
+            .exec(
+                "delete from orders where user_id in (?, ?)",
+                vec![Value::I64(1), Value::I64(5)],
+            )
+            .await
+            .unwrap();
+        assert_eq!(r.rows_affected, 1);
+        assert_eq!(
+            take_log(&log),
+            vec![(
+                "ds_1".to_string(),
+                "delete from orders_01 where user_id in (?, ?)".to_string()
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_insert() {
+        let (rb, log) = sharding_rb();
+        rb.exec(
+            "insert into orders (id,user_id) VALUES (?,?),(?,?)",
+            vec![Value::I64(1), Value::I64(3), Value::I64(2), Value::I64(7)],
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            take_log(&log),
+            vec![(
+                "ds_1".to_string(),
+                "insert into orders_03 (id,user_id) VALUES (?,?),(?,?)".to_string()
+            )]
+        );
+
+        let err = rb
+            .exec(
+                "insert into orders (id,user_id) VALUES (?,?),(?,?)",
+                vec![Value::I64(1), Value::I64(3), Value::I64(2), Value::I64(4)],
+            )
+            .await;
+        assert!(err.is_err());
+        let err = rb
+            .exec("insert into orders (id) VALUES (?)", vec![Value::I64(1)])
+            .await;
+        assert!(err.is_err());
+        assert!(take_log(&log).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_tx() {
+        let (rb, log) = sharding_rb();
+        let tx = rb.acquire_begin().await.unwrap();
+        tx.query(
+            "select * from orders where user_id = ?",
+            vec![Value::I64(4)],
+        )
+        .await
+        .unwrap();
+        let err = tx
+            .query(
+                "select * from orders where user_id = ?",
+                vec![Value::I64(5)],
+            )
+            .await;
+        assert!(err.is_err());
+        tx.commit().await.unwrap();
+        assert!(take_log(&log)
+            .iter()
+            .any(|(ds, sql)| ds == "primary" && sql.contains("orders_00")));
+    }
+
+    // ==================== Crud Tests ====================
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    struct Orders {
+        id: Option<i64>,
+        user_id: Option<i64>,
+    }
+
+    crud!(Orders {});
+
+    #[tokio::test]
+    async fn test_crud() {
+        let (rb, log) = sharding_rb();
+        let orders = Orders {
+            id: Some(1),
+            user_id: Some(6),
+        };
+        Orders::insert(&rb, &orders).await.unwrap();
+        let _ = Orders::select_by_map(&rb, value! {"user_id": 6}).await;
+        Orders::delete_by_map(&rb, value! {"user_id": 6})
+            .await
+            .unwrap();
+        let log = take_log(&log);
+        assert_eq!(log.len(), 3);
+        assert!(log
+            .iter()
+            .all(|(ds, sql)| ds == "primary" && sql.contains("orders_02")));
+    }
+}
@@ -0,0 +1,183 @@
+//! Tests for SqlInjectionGuardIntercept:
+//! - string literal, stacked statements and comment are flagged
+//! - quoted identifiers are not flagged, `[..]` only for mssql
+//! - mysql: `"` is string literal, `\` is escape, `#` is comment
+//! - allow_string_literal / warn_only
+//! - py_sql integration
+
+#[macro_use]
+extern crate rbatis;
+
+#[cfg(test)]
+mod test {
+    use rbatis::intercept_sql_guard::SqlInjectionGuardIntercept;
+    use rbatis::{Error, RBatis};
+    use rbdc::rt::block_on;
+    use rbs::Value;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_check() {
+        let guard = SqlInjectionGuardIntercept::new();
+        assert_eq!(guard.check("mysql", "select * from t where id = ?"), None);
+        assert_eq!(guard.check("mysql", "select * from t where id = ?;"), None);
+        assert_eq!(
+            guard.check("postgres", "select \"order\", `group` from t where a = ?"),
+            None
+        );
+        // `[..]` is the identifier of mssql only
+        assert_eq!(
+            guard.check("mssql", "select [user], [a;b] from t where a = ?"),
+            None
+        );
+        assert_eq!(
+            guard.check("postgres", "select a[1] from t where b = array['x']"),
+            Some("string literal")
+        );
+        assert_eq!(
+            guard.check(
+                "postgres",
+                "select * from t where a = b[';'] ; drop table t"
+            ),
+            Some("string literal")
+        );
+        assert_eq!(
+            guard.check("sqlite", "select [a;b] from t"),
+            Some("stacked statements")
+        );
+        assert_eq!(
+            guard.check("mysql", "select * from t where name = 'a' or '1' = '1'"),
+            Some("string literal")
+        );
+        assert_eq!(
+            guard.check("mysql", "select * from t; drop table t"),
+            Some("stacked statements")
+        );
+        assert_eq!(
+            guard.check("mysql", "select * from t where id = 1 -- and tenant_id = 2"),
+            Some("comment")
+        );
+        assert_eq!(
+            guard.check("mysql", "select * from t where id = 1 /* x */"),
+            Some("comment")
+        );
+        // the identifier may have ';' or '--'
+        assert_eq!(
+            guard.check("postgres", "select \"a;b\", \"c\"\"--\" from t"),
+            None
+        );
+        assert_eq!(guard.check("mysql", "select `a;b`, `c``--` from t"), None);
+    }
+
+    #[test]
+    fn test_check_mysql() {
+        let guard = SqlInjectionGuardIntercept::new();
+        // `"` is the string literal of mysql
+        assert_eq!(
+            guard.check("mysql", "select * from t where name = \"a\" or \"1\"=\"1\""),
+            Some("string literal")
+        );
+        assert_eq!(
+            guard.check("postgres", "select * from t where \"name\" = ?"),
+            None
+        );
+        // `#` is the comment of mysql
+        assert_eq!(
+            guard.check("mysql", "select * from t where id = 1 # and tenant_id = 2"),
+            Some("comment")
+        );
+        assert_eq!(guard.check("postgres", "select a # b from t"), None);
+
+        let guard = SqlInjectionGuardIntercept::new().allow_string_literal(true);
+        assert_eq!(
+            guard.check("mysql", "select * from t where name = \"a\\\" or 1=1 --\""),
+            None
+        );
+        assert_eq!(
+            guard.check("mysql", "select * from t where name = \"a\" -- \""),
+            Some("comment")
+        );
+    }
+
+    #[test]
+    fn test_check_backslash() {
+        // `\` is not the escape of postgres/sqlite/mssql
+        let guard = SqlInjectionGuardIntercept::new().allow_string_literal(true);
+        for driver_type in ["postgres", "sqlite", "mssql"] {
+            assert_eq!(
+                guard.check(driver_type, "select * from t where name = 'a\\' or 1=1 --'"),
+                Some("comment")
+            );
+        }
+        assert_eq!(
+            guard.check("mysql", "select * from t where name = 'a\\' or 1=1 --'"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_allow_string_literal() {
+        let guard = SqlInjectionGuardIntercept::new().allow_string_literal(true);
+        assert_eq!(
+            guard.check("mysql", "select * from t where status = 'a;--'"),
+            None
+        );
+        assert_eq!(
+            guard.check("mysql", "select * from t where name = 'it''s'"),
+            None
+        );
+        assert_eq!(
+            guard.check("mysql", "select * from t where name = 'a'; drop table t"),
+            Some("stacked statements")
+        );
+    }
+
+    #[py_sql("select * from guard_table where name = '${safe name}'")]
+    async fn select_by_raw_name(rb: &RBatis, name: &str) -> Result<Value, Error> {
+        impled!()
+    }
+
+    #[py_sql("select * from guard_table where name = #{name}")]
+    async fn select_by_name(rb: &RBatis, name: &str) -> Result<Value, Error> {
+        impled!()
+    }
+
+    async fn guard_rb(guard: SqlInjectionGuardIntercept) -> (RBatis, std::path::PathBuf) {
+        let path = std::env::temp_dir().join(format!(
+            "rbatis_sql_guard_{}.db",
+            rbatis::plugin::snowflake::new_snowflake_id()
+        ));
+        let rb = RBatis::new();
+        rb.link(
+            rbdc_sqlite::SqliteDriver {},
+            &format!("sqlite://{}", path.display()),
+        )
+        .await
+        .unwrap();
+        rb.exec("create table guard_table (id integer, name text)", vec![])
+            .await
+            .unwrap();
+        rb.intercepts.insert(0, Arc::new(guard));
+        (rb, path)
+    }
+
+    #[test]
+    fn test_guard_py_sql() {
+        let f = async move {
+            let (rb, path) = guard_rb(SqlInjectionGuardIntercept::new()).await;
+            let err = select_by_raw_name(&rb, "a' or '1' = '1").await.unwrap_err();
+            assert!(err.to_string().contains("string literal"));
+            let rows = select_by_name(&rb, "a' or '1' = '1").await.unwrap();
+            assert_eq!(rows, Value::Array(vec![]));
+            drop(rb);
+            let _ = std::fs::remove_file(path);
+
+            let (rb, path) = guard_rb(SqlInjectionGuardIntercept::new().warn_only(true)).await;
+            let rows = select_by_raw_name(&rb, "a").await.unwrap();
+            assert_eq!(rows, Value::Array(vec![]));
+            drop(rb);
+            let _ = std::fs::remove_file(path);
+        };
+        block_on(f);
+    }
+}
@@ -0,0 +1,572 @@
+//! Tests for TenantIntercept:
+//! - select/update/delete add the tenant condition
+//! - insert add or fill the tenant column
+//! - join add the condition of joined tables, the tables can not be filtered return error
+//! - opt in / opt out tables, required tenant and bypass
+//! - crud! integration
+
+#[macro_use]
+extern crate rbatis;
+
+#[cfg(test)]
+mod test {
+    use dark_std::sync::SyncVec;
+    use futures_core::future::BoxFuture;
+    use futures_core::Stream;
+    use rbatis::intercept_tenant::{bypass_tenant, current_tenant, with_tenant, TenantIntercept};
+    use rbatis::{Error, RBatis};
+    use rbdc::db::{ConnectOptions, Connection, Driver, ExecResult, Row};
+    use rbs::{value, Value};
+    use serde::{Deserialize, Serialize};
+    use std::pin::Pin;
+    use std::sync::Arc;
+
+    // ==================== Mock Infrastructure ====================
+
+    /// record (sql, args)
+    type SqlLog = Arc<SyncVec<(String, Vec<Value>)>>;
+
+    #[derive(Debug, Clone)]
+    struct LogDriver {
+        log: SqlLog,
+    }
+
+    impl Driver for LogDriver {
+        fn name(&self) -> &str {
+            "test"
+        }
+
+        fn connect(&self, _url: &str) -> BoxFuture<'_, Result<Box<dyn Connection>, Error>> {
+            Box::pin(async {
+                Ok(Box::new(LogConnection {
+                    log: self.log.clone(),
+                }) as Box<dyn Connection>)
+            })
+        }
+
+        fn connect_opt<'a>(
+            &'a self,
+            _option: &'a dyn ConnectOptions,
+        ) -> BoxFuture<'a, Result<Box<dyn Connection>, Error>> {
+            self.connect("")
+        }
+
+        fn default_option(&self) -> Box<dyn ConnectOptions> {
+            Box::new(LogConnectOptions {
+                driver: self.clone(),
+            })
+        }
+    }
+
+    #[derive(Debug)]
+    struct LogConnection {
+        log: SqlLog,
+    }
+
+    impl Connection for LogConnection {
+        fn exec_rows(
+            &mut self,
+            _sql: &str,
+            _params: Vec<Value>,
+        ) -> BoxFuture<
+            '_,
+            Result<Pin<Box<dyn Stream<Item = Result<Box<dyn Row>, Error>> + Send + '_>>, Error>,
+        > {
+            Box::pin(async { Err(Error::from("mock")) })
+        }
+
+        fn exec_decode(
+            &mut self,
+            sql: &str,
+            params: Vec<Value>,
+        ) -> BoxFuture<'_, Result<Value, Error>> {
+            self.log.push((sql.to_string(), params));
+            Box::pin(async { Ok(Value::Array(vec![])) })
+        }
+
+        fn exec(
+            &mut self,
+            sql: &str,
+            params: Vec<Value>,
+        ) -> BoxFuture<'_, Result<ExecResult, Error>> {
+            self.log.push((sql.to_string(), params));
+            Box::pin(async { Ok(ExecResult::default()) })
+        }
+
+        fn close(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+            Box::pin(async { Ok(()) })
+        }
+
+        fn ping(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct LogConnectOptions {
+        driver: LogDriver,
+    }
+
+    impl ConnectOptions for LogConnectOptions {
+        fn connect(&self) -> BoxFuture<'_, Result<Box<dyn Connection>, Error>> {
+            self.driver.connect("")
+        }
+
+        fn set_uri(&mut self, _uri: &str) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    fn tenant_rb(intercept: TenantIntercept) -> (RBatis, SqlLog) {
+        let log: SqlLog = Arc::new(SyncVec::new());
+        let rb = RBatis::new();
+        rb.init(LogDriver { log: log.clone() }, "test").unwrap();
+        rb.intercepts.insert(0, Arc::new(intercept));
+        (rb, log)
+    }
+
+    fn take_log(log: &SqlLog) -> Vec<(String, Vec<Value>)> {
+        let v = log.iter().cloned().collect();
+        log.clear();
+        v
+    }
+
+    fn entry(sql: &str, args: Vec<Value>) -> (String, Vec<Value>) {
+        (sql.to_string(), args)
+    }
+
+    // ==================== Filter Tests ====================
+
+    #[tokio::test]
+    async fn test_select() {
+        let (rb, log) = tenant_rb(TenantIntercept::new());
+        with_tenant(7, async {
+            assert_eq!(current_tenant(), Some(Value::I64(7)));
+            rb.query("select * from orders", vec![]).await.unwrap();
+            rb.query(
+                "select * from orders where id = ? or id = ? order by id limit 1",
+                vec![Value::I32(1), Value::I32(2)],
+            )
+            .await
+            .unwrap();
+            rb.query(
+                "select o.id from orders o left join users u on u.id = o.user_id where u.name = ?",
+                vec![Value::from("a")],
+            )
+            .await
+            .unwrap();
+            rb.query("select extract(year from created_at) from orders", vec![])
+                .await
+                .unwrap();
+        })
+        .await;
+        assert_eq!(
+            take_log(&log),
+            vec![
+                entry(
+                    "select * from orders where tenant_id = ?",
+                    vec![Value::I64(7)]
+                ),
+                entry(
+                    "select * from orders where tenant_id = ? and (id = ? or id = ?) order by id limit 1",
+                    vec![Value::I64(7), Value::I32(1), Value::I32(2)]
+                ),
+                entry(
+                    "select o.id from orders o left join users u on u.tenant_id = ? and (u.id = o.user_id) where o.tenant_id = ? and (u.name = ?)",
+                    vec![Value::I64(7), Value::I64(7), Value::from("a")]
+                ),
+                entry(
+                    "select extract(year from created_at) from orders where tenant_id = ?",
+                    vec![Value::I64(7)]
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_join() {
+        let (rb, log) = tenant_rb(TenantIntercept::new().tables(vec!["orders"]));
+        with_tenant(7, async {
+            rb.query(
+                "select o.* from users u join orders o on o.user_id = u.id where u.name = ?",
+                vec![Value::from("a")],
+            )
+            .await
+            .unwrap();
+            rb.query(
+                "select * from users, orders where users.id = orders.user_id",
+                vec![],
+            )
+            .await
+            .unwrap();
+            rb.query(
+                "select * from users u left join orders o on o.user_id = u.id and o.status = ? where u.id = ?",
+                vec![Value::I32(1), Value::I32(2)],
+            )
+            .await
+            .unwrap();
+            let err = rb
+                .query(
+                    "select * from users u right join orders o on o.user_id = u.id",
+                    vec![],
+                )
+                .await;
+            assert!(err.unwrap_err().to_string().contains("right/full join"));
+            let err = rb
+                .query("select * from users left join orders using (user_id)", vec![])
+                .await;
+            assert!(err.unwrap_err().to_string().contains("without `on`"));
+        })
+        .await;
+        assert_eq!(
+            take_log(&log),
+            vec![
+                entry(
+                    "select o.* from users u join orders o on o.user_id = u.id where o.tenant_id = ? and (u.name = ?)",
+                    vec![Value::I64(7), Value::from("a")]
+                ),
+                entry(
+                    "select * from users, orders where orders.tenant_id = ? and (users.id = orders.user_id)",
+                    vec![Value::I64(7)]
+                ),
+                entry(
+                    "select * from users u left join orders o on o.tenant_id = ? and (o.user_id = u.id and o.status = ?) where u.id = ?",
+                    vec![Value::I64(7), Value::I32(1), Value::I32(2)]
+                ),
+            ]
+        );
+
+        // all tables are tenant tables
+        let (rb, log) = tenant_rb(TenantIntercept::new());
+        with_tenant(7, async {
+            rb.query(
+                "select * from orders o inner join items i on i.order_id = o.id order by o.id",
+                vec![],
+            )
+            .await
+            .unwrap();
+        })
+        .await;
+        assert_eq!(
+            take_log(&log),
+            vec![entry(
+                "select * from orders o inner join items i on i.order_id = o.id where o.tenant_id = ? and i.tenant_id = ? order by o.id",
+                vec![Value::I64(7), Value::I64(7)]
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unfiltered_table() {
+        let (rb, log) = tenant_rb(TenantIntercept::new().tables(vec!["orders"]));
+        with_tenant(7, async {
+            for sql in [
+                "insert into archive (id, name) select id, name from orders",
+                "select * from users where id in (select user_id from orders)",
+                "select id from users union select id from orders",
+            ] {
+                let err = rb.query(sql, vec![]).await;
+                assert!(err.unwrap_err().to_string().contains("can not filter"));
+            }
+            // the sub query on the other tables
+            rb.query(
+                "select * from orders where id in (select order_id from items where sku = ?)",
+                vec![Value::from("a")],
+            )
+            .await
+            .unwrap();
+        })
+        .await;
+        bypass_tenant(async {
+            rb.query("select id from users union select id from orders", vec![])
+                .await
+                .unwrap();
+        })
+        .await;
+        assert_eq!(
+            take_log(&log),
+            vec![
+                entry(
+                    "select * from orders where tenant_id = ? and (id in (select order_id from items where sku = ?))",
+                    vec![Value::I64(7), Value::from("a")]
+                ),
+                entry("select id from users union select id from orders", vec![]),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_delete() {
+        let (rb, log) = tenant_rb(TenantIntercept::new());
+        with_tenant("t1", async {
+            rb.exec(
+                "update orders set status = ? where id = ?",
+                vec![Value::I32(1), Value::I32(2)],
+            )
+            .await
+            .unwrap();
+            rb.exec("update orders set status = ?", vec![Value::I32(1)])
+                .await
+                .unwrap();
+            rb.exec("delete from orders where id = ?", vec![Value::I32(2)])
+                .await
+                .unwrap();
+            rb.exec(
+                "update orders set tenant_id = ?, status = ? where id = ?",
+                vec![Value::Null, Value::I32(1), Value::I32(2)],
+            )
+            .await
+            .unwrap();
+            // the other tenant
+            let err = rb
+                .exec(
+                    "update orders set status = ?, `tenant_id` = ? where id = ?",
+                    vec![Value::I32(1), Value::from("t2"), Value::I32(2)],
+                )
+                .await;
+            assert!(err.unwrap_err().to_string().contains("other tenant"));
+            let err = rb
+                .exec("update orders o set o.tenant_id = 't2'", vec![])
+                .await;
+            assert!(err.is_err());
+        })
+        .await;
+        assert_eq!(
+            take_log(&log),
+            vec![
+                entry(
+                    "update orders set status = ? where tenant_id = ? and (id = ?)",
+                    vec![Value::I32(1), Value::from("t1"), Value::I32(2)]
+                ),
+                entry(
+                    "update orders set status = ? where tenant_id = ?",
+                    vec![Value::I32(1), Value::from("t1")]
+                ),
+                entry(
+                    "delete from orders where tenant_id = ? and (id = ?)",
+                    vec![Value::from("t1"), Value::I32(2)]
+                ),
+                entry(
+                    "update orders set tenant_id = ?, status = ? where tenant_id = ? and (id = ?)",
+                    vec![
+                        Value::from("t1"),
+                        Value::I32(1),
+                        Value::from("t1"),
+                        Value::I32(2)
+                    ]
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_insert() {
+        let (rb, log) = tenant_rb(TenantIntercept::new());
+        with_tenant(7, async {
+            rb.exec(
+                "insert into orders (id,name) VALUES (?,?),(?,?)",
+                vec![
+                    Value::I32(1),
+                    Value::from("a"),
+                    Value::I32(2),
+                    Value::from("b"),
+                ],
+            )
+            .await
+            .unwrap();
+            rb.exec(
+                "insert into orders (id,tenant_id) VALUES (?,?),(?,?)",
+                vec![Value::I32(1), Value::Null, Value::I32(2), Value::I32(7)],
+            )
+            .await
+            .unwrap();
+            rb.exec(
+                "insert into orders (id,name) VALUES (?,?) on duplicate key update name = values(name)",
+                vec![Value::I32(1), Value::from("a")],
+            )
+            .await
+            .unwrap();
+            let err = rb
+                .exec("insert into orders select * from old_orders", vec![])
+                .await;
+            assert!(err.is_err());
+            // the other tenant
+            let err = rb
+                .exec(
+                    "insert into orders (id,tenant_id) VALUES (?,?),(?,?)",
+                    vec![Value::I32(1), Value::Null, Value::I32(2), Value::I32(8)],
+                )
+                .await;
+            assert!(err.unwrap_err().to_string().contains("other tenant"));
+            let err = rb
+                .exec("insert into orders (id,tenant_id) VALUES (1,8)", vec![])
+                .await;
+            assert!(err.is_err());
+        })
+        .await;
+        assert_eq!(
+            take_log(&log),
+            vec![
+                entry(
+                    "insert into orders (id,name,tenant_id) VALUES (?,?,?),(?,?,?)",
+                    vec![
+                        Value::I32(1),
+                        Value::from("a"),
+                        Value::I64(7),
+                        Value::I32(2),
+                        Value::from("b"),
+                        Value::I64(7)
+                    ]
+                ),
+                entry(
+                    "insert into orders (id,tenant_id) VALUES (?,?),(?,?)",
+                    vec![Value::I32(1), Value::I64(7), Value::I32(2), Value::I32(7)]
+                ),
+                entry(
+                    "insert into orders (id,name,tenant_id) VALUES (?,?,?) on duplicate key update name = values(name)",
+                    vec![Value::I32(1), Value::from("a"), Value::I64(7)]
+                ),
+            ]
+        );
+    }
+
+    // ==================== Config Tests ====================
+
+    #[tokio::test]
+    async fn test_tables() {
+        let (rb, log) = tenant_rb(
+            TenantIntercept::new()
+                .tables(vec!["orders"])
+                .column("org_id"),
+        );
+        with_tenant(7, async {
+            rb.query("select * from users", vec![]).await.unwrap();
+            rb.query("select * from `orders`", vec![]).await.unwrap();
+        })
+        .await;
+        assert_eq!(
+            take_log(&log),
+            vec![
+                entry("select * from users", vec![]),
+                entry(
+                    "select * from `orders` where org_id = ?",
+                    vec![Value::I64(7)]
+                ),
+            ]
+        );
+
+        let (rb, log) = tenant_rb(TenantIntercept::new().ignore_tables(vec!["sys_config"]));
+        with_tenant(7, async {
+            rb.query("select * from sys_config", vec![]).await.unwrap();
+        })
+        .await;
+        assert_eq!(
+            take_log(&log),
+            vec![entry("select * from sys_config", vec![])]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_required_and_bypass() {
+        let (rb, log) = tenant_rb(TenantIntercept::new());
+        let err = rb.query("select * from orders", vec![]).await;
+        assert!(err.is_err());
+        rb.exec("create table orders (id int)", vec![])
+            .await
+            .unwrap();
+        bypass_tenant(async {
+            assert_eq!(current_tenant(), None);
+            rb.query("select * from orders", vec![]).await.unwrap();
+        })
+        .await;
+        assert_eq!(
+            take_log(&log),
+            vec![
+                entry("create table orders (id int)", vec![]),
+                entry("select * from orders", vec![]),
+            ]
+        );
+
+        let (rb, log) = tenant_rb(TenantIntercept::new().required(false));
+        rb.query("select * from orders", vec![]).await.unwrap();
+        assert_eq!(take_log(&log), vec![entry("select * from orders", vec![])]);
+    }
+
+    #[tokio::test]
+    async fn test_unresolved_table() {
+        let sql = "select * from (select * from orders) t";
+        let (rb, log) = tenant_rb(TenantIntercept::new());
+        with_tenant(7, async {
+            let err = rb.query(sql, vec![]).await;
+            assert!(err.unwrap_err().to_string().contains("can not resolve"));
+            // no table
+            rb.query("select 1", vec![]).await.unwrap();
+        })
+        .await;
+        bypass_tenant(async {
+            rb.query(sql, vec![]).await.unwrap();
+        })
+        .await;
+        assert_eq!(
+            take_log(&log),
+            vec![entry("select 1", vec![]), entry(sql, vec![])]
+        );
+
+        // not the tenant tables
+        let (rb, _log) = tenant_rb(TenantIntercept::new().tables(vec!["users"]));
+        with_tenant(7, async {
+            rb.query(sql, vec![]).await.unwrap();
+        })
+        .await;
+
+        // `required(false)` only skip the sql without tenant
+        let (rb, _log) = tenant_rb(TenantIntercept::new().required(false));
+        rb.query(sql, vec![]).await.unwrap();
+        with_tenant(7, async {
+            assert!(rb.query(sql, vec![]).await.is_err());
+        })
+        .await;
+    }
+
+    // ==================== Crud Tests ====================
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    struct Orders {
+        id: Option<i64>,
+        tenant_id: Option<i64>,
+    }
+
+    crud!(Orders {});
+
+    #[tokio::test]
+    async fn test_crud() {
+        let (rb, log) = tenant_rb(TenantIntercept::new());
+        with_tenant(7i64, async {
+            let orders = Orders {
+                id: Some(1),
+                tenant_id: None,
+            };
+            Orders::insert(&rb, &orders).await.unwrap();
+            Orders::select_by_map(&rb, value! {"id": 1}).await.unwrap();
+            Orders::delete_by_map(&rb, value! {"id": 1}).await.unwrap();
+        })
+        .await;
+        assert_eq!(
+            take_log(&log),
+            vec![
+                entry(
+                    "insert into orders (id ,tenant_id) VALUES (? ,?)",
+                    vec![Value::I64(1), Value::I64(7)]
+                ),
+                entry(
+                    "select * from orders where tenant_id = ? and (id = ?)",
+                    vec![Value::I64(7), Value::I32(1)]
+                ),
+                entry(
+                    "delete from orders where tenant_id = ? and (id = ?)",
+                    vec![Value::I64(7), Value::I32(1)]
+                ),
+            ]
+        );
+    }
+}
@@ -210,6 +210,7 @@ mod test {
         pub count: u64, //page count num
     }
 
+    #[cfg(not(feature = "strict_sql"))]
     #[test]
     fn test_exec_decode() {
         let f = async move {
@@ -229,6 +230,24 @@ mod test {
         block_on(f);
     }
 
+    #[test]
+    fn test_safe_arg() {
+        let f = async move {
+            let mut rb = RBatis::new();
+            rb.init(MockDriver {}, "test").unwrap();
+            let queue = Arc::new(SyncVec::new());
+            rb.set_intercepts(vec![Arc::new(MockIntercept::new(queue.clone()))]);
+            pysql!(select_by_table(rb: &RBatis, table: &str, id: &u64)  -> Result<Value, Error> => "select * from ${safe table} where id = #{id}");
+
+            let r = select_by_table(&rb, "t", &1).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "select * from t where id = ?");
+            assert_eq!(args, vec![Value::U64(1)]);
+        };
+        block_on(f);
+    }
+
+    #[cfg(not(feature = "strict_sql"))]
     #[test]
     fn test_macro() {
         let f = async move {
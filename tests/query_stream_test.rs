@@ -0,0 +1,258 @@
+//! Tests for streaming query:
+//! - RBatisConnExecutor::query_stream() / fetch_stream()
+//! - RBatisTxExecutor::query_stream()
+//! - RBatis::fetch_stream()
+//! - Intercept before() / after_stream() on stream
+
+#[cfg(test)]
+mod test {
+    use async_trait::async_trait;
+    use futures::StreamExt;
+    use rbatis::executor::{Executor, RBatisConnExecutor};
+    use rbatis::intercept::{Intercept, ResultType};
+    use rbatis::{Action, Error, RBatis};
+    use rbdc::db::ExecResult;
+    use rbdc_sqlite::SqliteDriver;
+    use rbs::Value;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    struct StreamRow {
+        id: i64,
+        name: String,
+    }
+
+    async fn setup_conn(rb: &RBatis, rows: i64) -> RBatisConnExecutor {
+        rb.link(SqliteDriver {}, "sqlite://:memory:").await.unwrap();
+        let conn = rb.acquire().await.unwrap();
+        conn.exec(
+            "CREATE TABLE test_stream (id INTEGER PRIMARY KEY, name TEXT)",
+            vec![],
+        )
+        .await
+        .unwrap();
+        for i in 0..rows {
+            conn.exec(
+                "INSERT INTO test_stream (id, name) VALUES (?, ?)",
+                vec![Value::I64(i), Value::String(format!("name_{}", i))],
+            )
+            .await
+            .unwrap();
+        }
+        conn
+    }
+
+    #[tokio::test]
+    async fn test_query_stream_rows() {
+        let rb = RBatis::new();
+        let conn = setup_conn(&rb, 200).await;
+        let mut stream = conn
+            .query_stream("SELECT * FROM test_stream ORDER BY id", vec![])
+            .await
+            .unwrap();
+        let mut count = 0;
+        while let Some(row) = stream.next().await {
+            let row = row.unwrap();
+            assert_eq!(row["id"], Value::I64(count));
+            count += 1;
+        }
+        assert_eq!(count, 200);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_stream_decode() {
+        let rb = RBatis::new();
+        let conn = setup_conn(&rb, 3).await;
+        let rows: Vec<StreamRow> = conn
+            .fetch_stream::<StreamRow>("SELECT * FROM test_stream ORDER BY id", vec![])
+            .await
+            .unwrap()
+            .map(|v| v.unwrap())
+            .collect()
+            .await;
+        assert_eq!(
+            rows,
+            vec![
+                StreamRow {
+                    id: 0,
+                    name: "name_0".to_string()
+                },
+                StreamRow {
+                    id: 1,
+                    name: "name_1".to_string()
+                },
+                StreamRow {
+                    id: 2,
+                    name: "name_2".to_string()
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_stream_error() {
+        let rb = RBatis::new();
+        let conn = setup_conn(&rb, 0).await;
+        let mut stream = conn
+            .query_stream("SELECT * FROM not_exist_table", vec![])
+            .await
+            .unwrap();
+        assert!(stream.next().await.unwrap().is_err());
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_query_stream_drop_release_conn() {
+        let rb = RBatis::new();
+        let conn = setup_conn(&rb, 500).await;
+        let mut stream = conn
+            .query_stream("SELECT * FROM test_stream", vec![])
+            .await
+            .unwrap();
+        assert!(stream.next().await.unwrap().is_ok());
+        drop(stream);
+        let r = conn
+            .exec("DELETE FROM test_stream WHERE id = ?", vec![Value::I64(1)])
+            .await
+            .unwrap();
+        assert_eq!(r.rows_affected, 1);
+    }
+
+    #[tokio::test]
+    async fn test_tx_query_stream() {
+        let rb = RBatis::new();
+        let conn = setup_conn(&rb, 0).await;
+        let tx = conn.begin().await.unwrap();
+        tx.exec(
+            "INSERT INTO test_stream (id, name) VALUES (?, ?)",
+            vec![Value::I64(1), Value::String("tx".to_string())],
+        )
+        .await
+        .unwrap();
+        let rows: Vec<StreamRow> = tx
+            .fetch_stream::<StreamRow>("SELECT * FROM test_stream", vec![])
+            .await
+            .unwrap()
+            .map(|v| v.unwrap())
+            .collect()
+            .await;
+        assert_eq!(rows.len(), 1);
+        tx.rollback().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rbatis_fetch_stream() {
+        let rb = RBatis::new();
+        let conn = setup_conn(&rb, 2).await;
+        drop(conn);
+        let rows: Vec<Result<StreamRow, Error>> = rb
+            .fetch_stream::<StreamRow>("SELECT * FROM test_stream", vec![])
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(rows.len(), 2);
+    }
+
+    #[derive(Debug)]
+    struct StreamHookIntercept {
+        pub before_sql: Arc<Mutex<Vec<String>>>,
+        pub stream_end: Arc<Mutex<Vec<Result<u64, String>>>>,
+    }
+
+    #[async_trait]
+    impl Intercept for StreamHookIntercept {
+        async fn before(
+            &self,
+            _task_id: i64,
+            _rb: &dyn Executor,
+            sql: &mut String,
+            _args: &mut Vec<Value>,
+            _result: ResultType<&mut Result<ExecResult, Error>, &mut Result<Value, Error>>,
+        ) -> Result<Action, Error> {
+            self.before_sql.lock().unwrap().push(sql.clone());
+            Ok(Action::Next)
+        }
+
+        async fn after_stream(
+            &self,
+            _task_id: i64,
+            _rb: &dyn Executor,
+            _sql: &str,
+            _args: &[Value],
+            result: &Result<u64, Error>,
+        ) -> Result<(), Error> {
+            self.stream_end
+                .lock()
+                .unwrap()
+                .push(result.as_ref().map(|v| *v).map_err(|e| e.to_string()));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_stream_intercept() {
+        let before_sql = Arc::new(Mutex::new(vec![]));
+        let stream_end = Arc::new(Mutex::new(vec![]));
+        let mut rb = RBatis::new();
+        rb.set_intercepts(vec![Arc::new(StreamHookIntercept {
+            before_sql: before_sql.clone(),
+            stream_end: stream_end.clone(),
+        })]);
+        let conn = setup_conn(&rb, 5).await;
+        before_sql.lock().unwrap().clear();
+        let stream = conn
+            .query_stream("SELECT * FROM test_stream", vec![])
+            .await
+            .unwrap();
+        let rows: Vec<_> = stream.collect().await;
+        assert_eq!(rows.len(), 5);
+        assert_eq!(
+            *before_sql.lock().unwrap(),
+            vec!["SELECT * FROM test_stream".to_string()]
+        );
+        assert_eq!(*stream_end.lock().unwrap(), vec![Ok(5)]);
+    }
+
+    #[tokio::test]
+    async fn test_query_stream_intercept_return() {
+        #[derive(Debug)]
+        struct ReturnRowsIntercept;
+
+        #[async_trait]
+        impl Intercept for ReturnRowsIntercept {
+            async fn before(
+                &self,
+                _task_id: i64,
+                _rb: &dyn Executor,
+                _sql: &mut String,
+                _args: &mut Vec<Value>,
+                result: ResultType<&mut Result<ExecResult, Error>, &mut Result<Value, Error>>,
+            ) -> Result<Action, Error> {
+                if let ResultType::Query(v) = result {
+                    *v = Ok(Value::Array(vec![rbs::value! {"id": 9, "name": "cached"}]));
+                    return Ok(Action::Return);
+                }
+                Ok(Action::Next)
+            }
+        }
+
+        let rb = RBatis::new();
+        let conn = setup_conn(&rb, 0).await;
+        rb.intercepts.insert(0, Arc::new(ReturnRowsIntercept));
+        let rows: Vec<StreamRow> = conn
+            .fetch_stream::<StreamRow>("SELECT * FROM test_stream", vec![])
+            .await
+            .unwrap()
+            .map(|v| v.unwrap())
+            .collect()
+            .await;
+        assert_eq!(
+            rows,
+            vec![StreamRow {
+                id: 9,
+                name: "cached".to_string()
+            }]
+        );
+    }
+}
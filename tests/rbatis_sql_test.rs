@@ -58,6 +58,7 @@ mod test {
         println!("py->args: {}", serde_json::to_string(&args).unwrap());
     }
 
+    #[cfg(not(feature = "strict_sql"))]
     #[rb_py(
         "insert into ${table_name} (
              trim ',':
@@ -71,6 +72,7 @@ mod test {
     )]
     pub fn save(arg: &rbs::Value, _tag: char) {}
 
+    #[cfg(not(feature = "strict_sql"))]
     #[test]
     fn test_save() {
         let mut arg = ValueMap::new();
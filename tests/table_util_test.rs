@@ -221,3 +221,52 @@ fn test_field_name() {
     let age_name = rbatis::field_name!(TestTable.age);
     assert_eq!(age_name, "age");
 }
+
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, rbatis::Columns)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnsBase {
+    pub create_time: Option<String>,
+    #[serde(rename = "modified")]
+    pub update_time: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, rbatis::Columns)]
+pub struct ColumnsTable {
+    pub id: Option<String>,
+    #[serde(rename = "title")]
+    pub name: Option<String>,
+    #[serde(skip)]
+    pub cache: Option<String>,
+    #[serde(flatten)]
+    pub base: ColumnsBase,
+}
+
+#[test]
+fn test_columns_derive() {
+    assert_eq!(ColumnsTable::COL.id, "id");
+    assert_eq!(ColumnsTable::COL.name, "title");
+    assert_eq!(ColumnsTable::COL.base.create_time, "createTime");
+    assert_eq!(ColumnsTable::COL.base.update_time, "modified");
+    assert_eq!(
+        <ColumnsTable as rbatis::Columns>::column_names(),
+        vec!["id", "title", "createTime", "modified"]
+    );
+
+    // the column names are the keys of the serialized table
+    let v = rbs::value!(ColumnsTable::default());
+    for column in <ColumnsTable as rbatis::Columns>::column_names() {
+        assert!(v
+            .as_map()
+            .unwrap()
+            .0
+            .contains_key(&rbs::Value::from(column)));
+    }
+
+    let condition = rbs::value! {(ColumnsTable::COL.name): "a"};
+    assert_eq!(condition["title"], rbs::Value::from("a"));
+    let (sql, _) = rbatis::Query::new()
+        .eq(ColumnsTable::COL.name, "a")
+        .select_sql("columns_table", "sqlite")
+        .unwrap();
+    assert_eq!(sql, "select * from columns_table where title = ?");
+}
@@ -200,3 +200,175 @@ fn test_transaction_guard() {
     let count = result.unwrap();
     assert_eq!(count.count, 1);
 }
+
+#[derive(serde::Deserialize, Debug)]
+struct TxCount {
+    count: i64,
+}
+
+#[test]
+fn test_transaction_closure_commit() {
+    let rb = setup_test();
+    let result = block_on(async move {
+        let rows = rb
+            .transaction(|tx| async move {
+                let r = tx
+                    .exec(
+                        "INSERT INTO test_tx (id, name) VALUES (?, ?)",
+                        vec![Value::I32(11), Value::String("closure".to_string())],
+                    )
+                    .await?;
+                Ok(r.rows_affected)
+            })
+            .await?;
+        let count: TxCount = rb
+            .exec_decode("SELECT COUNT(*) as count FROM test_tx", vec![])
+            .await?;
+        Ok::<_, rbatis::Error>((rows, count))
+    });
+    let (rows, count) = result.unwrap();
+    assert_eq!(rows, 1);
+    assert_eq!(count.count, 1);
+}
+
+#[test]
+fn test_transaction_closure_rollback_on_err() {
+    let rb = setup_test();
+    let result = block_on(async move {
+        let r: Result<(), rbatis::Error> = rb
+            .transaction(|tx| async move {
+                tx.exec(
+                    "INSERT INTO test_tx (id, name) VALUES (?, ?)",
+                    vec![Value::I32(12), Value::String("closure".to_string())],
+                )
+                .await?;
+                Err(rbatis::Error::from("business error"))
+            })
+            .await;
+        assert_eq!(r.unwrap_err().to_string(), "business error");
+        let count: TxCount = rb
+            .exec_decode("SELECT COUNT(*) as count FROM test_tx", vec![])
+            .await?;
+        Ok::<_, rbatis::Error>(count)
+    });
+    assert_eq!(result.unwrap().count, 0);
+}
+
+#[test]
+fn test_transaction_closure_rollback_on_panic() {
+    let rb = setup_test();
+    let rb2 = rb.clone();
+    let panic_result = block_on(async move {
+        let join = tokio::spawn(async move {
+            let _: Result<(), rbatis::Error> = rb2
+                .transaction(|tx| async move {
+                    tx.exec(
+                        "INSERT INTO test_tx (id, name) VALUES (?, ?)",
+                        vec![Value::I32(13), Value::String("panic".to_string())],
+                    )
+                    .await?;
+                    panic!("panic in transaction");
+                })
+                .await;
+        });
+        join.await
+    });
+    assert!(panic_result.is_err());
+    let result = block_on(async move {
+        let count: TxCount = rb
+            .exec_decode("SELECT COUNT(*) as count FROM test_tx", vec![])
+            .await?;
+        Ok::<_, rbatis::Error>(count)
+    });
+    assert_eq!(result.unwrap().count, 0);
+}
+
+#[test]
+fn test_transaction_retry_on_deadlock() {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let rb = setup_test();
+    let result = block_on(async move {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let policy = rbatis::RetryPolicy::new(3).backoff(Duration::from_millis(1));
+        let r = rb
+            .transaction_retry(&policy, |tx| {
+                let attempts = attempts.clone();
+                async move {
+                    tx.exec(
+                        "INSERT INTO test_tx (id, name) VALUES (?, ?)",
+                        vec![Value::I32(14), Value::String("retry".to_string())],
+                    )
+                    .await?;
+                    if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+                        return Err(rbatis::Error::from("deadlock detected"));
+                    }
+                    Ok(())
+                }
+            })
+            .await;
+        let count: TxCount = rb
+            .exec_decode("SELECT COUNT(*) as count FROM test_tx", vec![])
+            .await?;
+        Ok::<_, rbatis::Error>((r, attempts.load(Ordering::SeqCst), count))
+    });
+    let (r, attempts, count) = result.unwrap();
+    assert!(r.is_ok());
+    assert_eq!(attempts, 3);
+    assert_eq!(count.count, 1);
+}
+
+#[test]
+fn test_transaction_retry_give_up() {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let rb = setup_test();
+    let result = block_on(async move {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let policy = rbatis::RetryPolicy::new(1).backoff(Duration::from_millis(1));
+        let r: Result<(), rbatis::Error> = rb
+            .transaction_retry(&policy, |_tx| {
+                let attempts = attempts.clone();
+                async move {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                    Err(rbatis::Error::from("could not serialize access"))
+                }
+            })
+            .await;
+        (r, attempts.load(Ordering::SeqCst))
+    });
+    assert!(result.0.is_err());
+    assert_eq!(result.1, 2);
+}
+
+#[test]
+fn test_is_serialization_failure() {
+    use rbatis::{is_serialization_failure, Error};
+    assert!(is_serialization_failure(&Error::from(
+        "error returned from database: 40001: could not serialize access"
+    )));
+    assert!(is_serialization_failure(&Error::from(
+        "Deadlock found when trying to get lock; try restarting transaction"
+    )));
+    assert!(is_serialization_failure(&Error::from("database is locked")));
+    assert!(!is_serialization_failure(&Error::from(
+        "UNIQUE constraint failed"
+    )));
+    assert!(is_serialization_failure(&Error::from(
+        "Token error: 'Transaction was deadlocked' on server s executing  on line 1 (code: 1205, state: 51, class: 13)"
+    )));
+    // the code or word inside of other word or quoted value is not matched
+    assert!(!is_serialization_failure(&Error::from(
+        "1062 (23000): Duplicate entry '140001' for key 'PRIMARY'"
+    )));
+    assert!(!is_serialization_failure(&Error::from(
+        "1062 (23000): Duplicate entry '40001' for key 'PRIMARY'"
+    )));
+    assert!(!is_serialization_failure(&Error::from(
+        "23505:duplicate key value violates unique constraint \"deadlock_key\""
+    )));
+}
@@ -2,19 +2,27 @@
 /// - RBatisTxExecutor::set_done()
 /// - RBatisTxExecutor::done() state transitions
 /// - RBatisTxExecutor::begin() nested transaction support
+/// - RBatisTxExecutor::begin_nested() savepoint sql
+/// - RBatisConnExecutor::begin_option() isolation level / access mode sql
+/// - RBatisTxExecutor::after_commit() / after_rollback() callbacks
 /// - RBatisTxExecutor::take_connection()
 /// - RBatisTxExecutorGuard::take_connection()
 
 #[cfg(test)]
 mod test {
+    use dark_std::sync::SyncVec;
     use futures_core::future::BoxFuture;
     use futures_core::Stream;
     use rbatis::executor::Executor;
-    use rbatis::{Error, RBatis, RBatisRef};
+    use rbatis::intercept::Intercept;
+    use rbatis::{Error, IsolationLevel, RBatis, RBatisRef, TxOptions};
     use rbdc::db::{ConnectOptions, Connection, Driver, ExecResult, Row};
     use rbdc::rt::block_on;
     use rbs::Value;
     use std::pin::Pin;
+    use std::sync::Arc;
+
+    type RowStream<'a> = Pin<Box<dyn Stream<Item = Result<Box<dyn Row>, Error>> + Send + 'a>>;
 
     // ==================== Mock Infrastructure ====================
 
@@ -95,6 +103,107 @@ mod test {
         }
     }
 
+    /// record every sql executed on the connection, name() is the driver_type
+    #[derive(Debug, Clone)]
+    struct RecordDriver {
+        name: &'static str,
+        sqls: Arc<SyncVec<String>>,
+        fail_once: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl RecordDriver {
+        fn new(name: &'static str) -> Self {
+            Self {
+                name,
+                sqls: Arc::new(SyncVec::new()),
+                fail_once: Arc::new(std::sync::Mutex::new(vec![])),
+            }
+        }
+
+        fn sqls(&self) -> Vec<String> {
+            self.sqls.iter().cloned().collect()
+        }
+
+        /// the next exec of `sql` return an error
+        fn fail_once(&self, sql: &str) {
+            self.fail_once.lock().unwrap().push(sql.to_string());
+        }
+
+        fn connection(&self) -> Box<dyn Connection> {
+            Box::new(RecordConnection {
+                sqls: self.sqls.clone(),
+                fail_once: self.fail_once.clone(),
+            })
+        }
+    }
+
+    impl Driver for RecordDriver {
+        fn name(&self) -> &str {
+            self.name
+        }
+        fn connect(&self, _url: &str) -> BoxFuture<'_, Result<Box<dyn Connection>, Error>> {
+            let conn = self.connection();
+            Box::pin(async { Ok(conn) })
+        }
+        fn connect_opt<'a>(
+            &'a self,
+            _option: &'a dyn ConnectOptions,
+        ) -> BoxFuture<'a, Result<Box<dyn Connection>, Error>> {
+            let conn = self.connection();
+            Box::pin(async { Ok(conn) })
+        }
+        fn default_option(&self) -> Box<dyn ConnectOptions> {
+            Box::new(MockConnectOptions {})
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    struct RecordConnection {
+        sqls: Arc<SyncVec<String>>,
+        fail_once: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl Connection for RecordConnection {
+        fn exec_rows(
+            &mut self,
+            sql: &str,
+            _params: Vec<Value>,
+        ) -> BoxFuture<
+            '_,
+            Result<Pin<Box<dyn Stream<Item = Result<Box<dyn Row>, Error>> + Send + '_>>, Error>,
+        > {
+            self.sqls.push(sql.to_string());
+            Box::pin(async {
+                let stream: RowStream = Box::pin(futures::stream::empty());
+                Ok(stream)
+            })
+        }
+        fn exec(
+            &mut self,
+            sql: &str,
+            _params: Vec<Value>,
+        ) -> BoxFuture<'_, Result<ExecResult, Error>> {
+            self.sqls.push(sql.to_string());
+            let mut fail_once = self.fail_once.lock().unwrap();
+            if let Some(i) = fail_once.iter().position(|v| v == sql) {
+                fail_once.remove(i);
+                return Box::pin(async { Err(Error::from("mock fail")) });
+            }
+            Box::pin(async {
+                Ok(ExecResult {
+                    rows_affected: 0,
+                    last_insert_id: Value::Null,
+                })
+            })
+        }
+        fn close(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+            Box::pin(async { Ok(()) })
+        }
+        fn ping(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+            Box::pin(async { Ok(()) })
+        }
+    }
+
     // ==================== RBatisTxExecutor::done / set_done Tests ====================
 
     #[test]
@@ -220,6 +329,271 @@ mod test {
         block_on(f);
     }
 
+    // ==================== RBatisTxExecutor::begin_nested (savepoint) Tests ====================
+
+    #[tokio::test]
+    async fn test_begin_nested_commit_release_savepoint() {
+        let driver = RecordDriver::new("sqlite");
+        let rb = RBatis::new();
+        rb.init(driver.clone(), "test").unwrap();
+        let tx = rb.acquire_begin().await.unwrap();
+        let nested = tx.begin_nested().await.unwrap();
+        assert!(nested.is_nested());
+        assert!(!tx.is_nested());
+        assert_eq!(nested.tx_id, tx.tx_id);
+        nested.commit().await.unwrap();
+        assert!(nested.done());
+        assert!(!tx.done());
+        tx.commit().await.unwrap();
+        assert_eq!(
+            driver.sqls(),
+            vec![
+                "begin",
+                "savepoint rb_sp_1",
+                "release savepoint rb_sp_1",
+                "commit"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_begin_nested_rollback_to_savepoint() {
+        let driver = RecordDriver::new("postgres");
+        let rb = RBatis::new();
+        rb.init(driver.clone(), "test").unwrap();
+        let tx = rb.acquire_begin().await.unwrap();
+        let nested = tx.begin_nested().await.unwrap();
+        nested.rollback().await.unwrap();
+        assert!(nested.done());
+        assert!(!tx.done());
+        assert_eq!(
+            driver.sqls(),
+            vec![
+                "begin",
+                "savepoint rb_sp_1",
+                "rollback to savepoint rb_sp_1"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_begin_nested_unique_savepoint_name() {
+        let driver = RecordDriver::new("mysql");
+        let rb = RBatis::new();
+        rb.init(driver.clone(), "test").unwrap();
+        let tx = rb.acquire_begin().await.unwrap();
+        let nested1 = tx.begin_nested().await.unwrap();
+        let nested2 = nested1.begin_nested().await.unwrap();
+        assert_eq!(nested1.savepoint(), Some("rb_sp_1"));
+        assert_eq!(nested2.savepoint(), Some("rb_sp_2"));
+        assert_eq!(tx.savepoint(), None);
+    }
+
+    #[tokio::test]
+    async fn test_begin_nested_mssql() {
+        let driver = RecordDriver::new("mssql");
+        let rb = RBatis::new();
+        rb.init(driver.clone(), "test").unwrap();
+        let tx = rb.acquire_begin().await.unwrap();
+        let nested = tx.begin_nested().await.unwrap();
+        nested.rollback().await.unwrap();
+        let nested = tx.begin_nested().await.unwrap();
+        nested.commit().await.unwrap();
+        assert_eq!(
+            driver.sqls(),
+            vec![
+                "begin",
+                "save transaction rb_sp_1",
+                "rollback transaction rb_sp_1",
+                "save transaction rb_sp_2"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_begin_not_use_savepoint() {
+        let driver = RecordDriver::new("sqlite");
+        let rb = RBatis::new();
+        rb.init(driver.clone(), "test").unwrap();
+        let tx = rb.acquire_begin().await.unwrap();
+        let tx = tx.begin().await.unwrap();
+        assert!(!tx.is_nested());
+        assert_eq!(driver.sqls(), vec!["begin", "begin"]);
+    }
+
+    // ==================== TxOptions / begin_option Tests ====================
+
+    async fn begin_option_sqls(driver_type: &'static str, options: TxOptions) -> Vec<String> {
+        let driver = RecordDriver::new(driver_type);
+        let rb = RBatis::new();
+        rb.init(driver.clone(), "test").unwrap();
+        let tx = rb.acquire_begin_option(options).await.unwrap();
+        assert!(!tx.done());
+        driver.sqls()
+    }
+
+    #[tokio::test]
+    async fn test_begin_option_default_use_driver_begin() {
+        let sqls = begin_option_sqls("postgres", TxOptions::new()).await;
+        assert_eq!(sqls, vec!["begin"]);
+    }
+
+    #[tokio::test]
+    async fn test_begin_option_postgres() {
+        let options = TxOptions::new()
+            .isolation_level(IsolationLevel::Serializable)
+            .read_only()
+            .deferrable();
+        let sqls = begin_option_sqls("postgres", options).await;
+        assert_eq!(
+            sqls,
+            vec!["begin isolation level serializable, read only, deferrable"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_begin_option_mysql() {
+        let options = TxOptions::new()
+            .isolation_level(IsolationLevel::RepeatableRead)
+            .read_only();
+        let sqls = begin_option_sqls("mysql", options).await;
+        assert_eq!(
+            sqls,
+            vec![
+                "set transaction isolation level repeatable read",
+                "start transaction read only"
+            ]
+        );
+        let options = TxOptions::new().isolation_level(IsolationLevel::ReadCommitted);
+        let sqls = begin_option_sqls("mysql", options).await;
+        assert_eq!(
+            sqls,
+            vec!["set transaction isolation level read committed", "begin"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_begin_option_mssql() {
+        let options = TxOptions::new().isolation_level(IsolationLevel::Serializable);
+        let sqls = begin_option_sqls("mssql", options).await;
+        assert_eq!(
+            sqls,
+            vec!["set transaction isolation level serializable", "begin"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_begin_option_mssql_reset_isolation_level() {
+        let driver = RecordDriver::new("mssql");
+        let rb = RBatis::new();
+        rb.init(driver.clone(), "test").unwrap();
+        let options = TxOptions::new().isolation_level(IsolationLevel::Serializable);
+        let tx = rb.acquire_begin_option(options.clone()).await.unwrap();
+        let nested = tx.begin_nested().await.unwrap();
+        nested.commit().await.unwrap();
+        tx.commit().await.unwrap();
+        let tx = rb.acquire_begin_option(options).await.unwrap();
+        tx.rollback().await.unwrap();
+        assert_eq!(
+            driver.sqls(),
+            vec![
+                "set transaction isolation level serializable",
+                "begin",
+                "save transaction rb_sp_1",
+                "commit",
+                "set transaction isolation level read committed",
+                "set transaction isolation level serializable",
+                "begin",
+                "rollback",
+                "set transaction isolation level read committed"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_begin_option_sqlite() {
+        let options = TxOptions::new().isolation_level(IsolationLevel::Serializable);
+        let sqls = begin_option_sqls("sqlite", options).await;
+        assert_eq!(sqls, vec!["begin immediate"]);
+        let sqls = begin_option_sqls("sqlite", TxOptions::new().read_write()).await;
+        assert_eq!(sqls, vec!["begin immediate"]);
+        let sqls = begin_option_sqls(
+            "sqlite",
+            TxOptions::new().isolation_level(IsolationLevel::ReadCommitted),
+        )
+        .await;
+        assert_eq!(sqls, vec!["begin deferred"]);
+    }
+
+    #[tokio::test]
+    async fn test_begin_option_unsupported() {
+        for (driver_type, options) in [
+            ("mssql", TxOptions::new().read_only()),
+            ("sqlite", TxOptions::new().read_only()),
+            ("mysql", TxOptions::new().deferrable()),
+        ] {
+            let driver = RecordDriver::new(driver_type);
+            let rb = RBatis::new();
+            rb.init(driver.clone(), "test").unwrap();
+            let err = rb.acquire_begin_option(options).await.unwrap_err();
+            assert!(err.to_string().contains("not support"), "{}", err);
+            assert!(driver.sqls().is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_begin_option_other_driver() {
+        let options = TxOptions::new()
+            .isolation_level(IsolationLevel::ReadUncommitted)
+            .read_write();
+        let sqls = begin_option_sqls("test", options).await;
+        assert_eq!(
+            sqls,
+            vec![
+                "begin",
+                "set transaction isolation level read uncommitted, read write"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_begin_option_rollback_on_begin_hook_error() {
+        #[derive(Debug)]
+        struct RejectBeginIntercept;
+
+        #[async_trait::async_trait]
+        impl Intercept for RejectBeginIntercept {
+            async fn on_begin(&self, _task_id: i64, _rb: &dyn Executor) -> Result<(), Error> {
+                Err(Error::from("reject begin"))
+            }
+        }
+
+        let driver = RecordDriver::new("postgres");
+        let mut rb = RBatis::new();
+        rb.set_intercepts(vec![Arc::new(RejectBeginIntercept)]);
+        rb.init(driver.clone(), "test").unwrap();
+        let err = rb.acquire_begin().await.unwrap_err();
+        assert_eq!(err.to_string(), "reject begin");
+        assert_eq!(driver.sqls(), vec!["begin", "rollback"]);
+    }
+
+    #[tokio::test]
+    async fn test_conn_executor_begin_option() {
+        let driver = RecordDriver::new("postgres");
+        let rb = RBatis::new();
+        rb.init(driver.clone(), "test").unwrap();
+        let conn = rb.acquire().await.unwrap();
+        let tx = conn
+            .begin_option(TxOptions::new().isolation_level(IsolationLevel::RepeatableRead))
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+        assert_eq!(
+            driver.sqls(),
+            vec!["begin isolation level repeatable read", "commit"]
+        );
+    }
+
     // ==================== take_connection Tests ====================
 
     #[test]
@@ -516,4 +890,87 @@ mod test {
         let result = rb.try_acquire_begin().await;
         assert!(result.is_err());
     }
+
+    // ==================== after_commit / after_rollback Tests ====================
+
+    fn record_callbacks(
+        tx: &rbatis::executor::RBatisTxExecutor,
+        events: &Arc<SyncVec<String>>,
+        name: &str,
+    ) {
+        let commit_events = events.clone();
+        let commit_name = format!("{}_commit", name);
+        tx.after_commit(move || async move {
+            commit_events.push(commit_name);
+        });
+        let rollback_events = events.clone();
+        let rollback_name = format!("{}_rollback", name);
+        tx.after_rollback(move || async move {
+            rollback_events.push(rollback_name);
+        });
+    }
+
+    #[tokio::test]
+    async fn test_after_commit_callbacks_in_order() {
+        let rb = RBatis::new();
+        rb.init(MockDriver {}, "test").unwrap();
+        let events = Arc::new(SyncVec::new());
+        let tx = rb.acquire_begin().await.unwrap();
+        record_callbacks(&tx, &events, "a");
+        record_callbacks(&tx, &events, "b");
+        assert!(events.is_empty());
+        tx.commit().await.unwrap();
+        let events: Vec<String> = events.iter().cloned().collect();
+        assert_eq!(events, vec!["a_commit", "b_commit"]);
+    }
+
+    #[tokio::test]
+    async fn test_after_rollback_callbacks_drop_commit() {
+        let rb = RBatis::new();
+        rb.init(MockDriver {}, "test").unwrap();
+        let events = Arc::new(SyncVec::new());
+        let tx = rb.acquire_begin().await.unwrap();
+        record_callbacks(&tx, &events, "a");
+        tx.rollback().await.unwrap();
+        // callbacks run only once
+        tx.commit().await.unwrap();
+        let events: Vec<String> = events.iter().cloned().collect();
+        assert_eq!(events, vec!["a_rollback"]);
+    }
+
+    #[tokio::test]
+    async fn test_after_rollback_callbacks_keep_on_rollback_error() {
+        let driver = RecordDriver::new("postgres");
+        let rb = RBatis::new();
+        rb.init(driver.clone(), "test").unwrap();
+        let events = Arc::new(SyncVec::new());
+        let tx = rb.acquire_begin().await.unwrap();
+        record_callbacks(&tx, &events, "a");
+        driver.fail_once("rollback");
+        assert!(tx.rollback().await.is_err());
+        assert!(!tx.done());
+        assert!(events.is_empty());
+        tx.rollback().await.unwrap();
+        let events: Vec<String> = events.iter().cloned().collect();
+        assert_eq!(events, vec!["a_rollback"]);
+    }
+
+    #[tokio::test]
+    async fn test_after_commit_nested_waits_outer() {
+        let rb = RBatis::new();
+        rb.init(MockDriver {}, "test").unwrap();
+        let events = Arc::new(SyncVec::new());
+        let tx = rb.acquire_begin().await.unwrap();
+        record_callbacks(&tx, &events, "outer");
+        let nested = tx.begin_nested().await.unwrap();
+        record_callbacks(&nested, &events, "committed");
+        nested.commit().await.unwrap();
+        let nested = tx.begin_nested().await.unwrap();
+        record_callbacks(&nested, &events, "rolled_back");
+        nested.rollback().await.unwrap();
+        assert!(events.is_empty());
+        tx.commit().await.unwrap();
+        let events: Vec<String> = events.iter().cloned().collect();
+        assert_eq!(events, vec!["outer_commit", "committed_commit"]);
+    }
 }